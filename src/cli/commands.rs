@@ -1,4 +1,7 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
+
+use crate::db::{DataFormat, Dialect, KeepPolicy, KeywordCase, MultiRowMode, ReportFormat};
+use crate::version::BumpLevel;
 
 #[derive(Parser, Debug)]
 #[command(name = "st")]
@@ -23,6 +26,12 @@ pub enum Commands {
     },
     /// 数据库相关命令
     Db {
+        /// 输入文件编码，例如 gbk、gb18030、utf-8；默认按 BOM 与内容自动识别
+        #[arg(long, global = true)]
+        encoding: Option<String>,
+        /// 输出文件编码，可加 -bom 后缀，例如 utf-8-bom；默认与输入文件相同
+        #[arg(long, global = true)]
+        output_encoding: Option<String>,
         #[command(subcommand)]
         command: DbCommands,
     },
@@ -60,30 +69,478 @@ pub enum RepoCommands {
         old_version: String,
         /// 新版本号
         new_version: String,
+        /// 按依赖图升级指定模块（模块名、artifactId 或仓库名），并联动更新所有依赖方
+        #[arg(short, long)]
+        module: Option<String>,
+        /// 依赖方自身版本的递增级别
+        #[arg(long, value_enum, default_value = "patch")]
+        cascade: BumpLevel,
+        /// 跳过确认直接执行
+        #[arg(short, long)]
+        yes: bool,
+        /// 只展示升级计划，不修改文件
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// 汇总所有仓库在指定范围内的提交，生成 Markdown 变更日志
+    Changelog {
+        /// 提交范围，格式为 <from-tag>..<to-ref>，省略 to 时为 HEAD
+        range: String,
+        /// 自定义分组正则，取命名分组 group 或第一个捕获组作为分组名（默认按 Conventional Commit 类型分组）
+        #[arg(long)]
+        group_pattern: Option<String>,
+        /// 问题单号的匹配正则
+        #[arg(long, default_value = r"[A-Z][A-Z0-9]+-\d+")]
+        issue_pattern: String,
+        /// 问题单链接模板，{id} 会被替换为单号，例如 https://jira.example.com/browse/{id}
+        #[arg(long)]
+        issue_url: Option<String>,
+        /// 输出到文件（默认打印到终端）
+        #[arg(short, long)]
+        output: Option<String>,
     },
 }
 
 #[derive(Subcommand, Debug)]
 pub enum WorkspaceCommands {
     /// 创建新工作区
-    New,
+    New {
+        #[command(flatten)]
+        info: WorkspaceArgs,
+    },
     /// 初始化当前目录为工作区
-    Init,
+    Init {
+        #[command(flatten)]
+        info: WorkspaceArgs,
+    },
     /// 列出所有工作区
     Ls,
-    /// 切换到指定工作区
+    /// 切换到指定工作区，输出 cd 及环境变量命令
     Cd {
         /// 工作区名称
         workspace_name: String,
     },
+    /// 查看工作区的详细配置
+    Show {
+        /// 工作区名称
+        workspace_name: String,
+    },
+    /// 修改工作区配置，值为空时删除对应项
+    Set {
+        /// 工作区名称
+        workspace_name: String,
+        /// 工作区路径
+        #[arg(short, long)]
+        path: Option<String>,
+        /// 描述
+        #[arg(short, long)]
+        description: Option<String>,
+        /// 默认分支
+        #[arg(short = 'b', long = "branch")]
+        branch: Option<String>,
+        /// 仓库分组，格式 组名=仓库[,仓库]，可重复
+        #[arg(short, long = "group")]
+        groups: Vec<String>,
+        /// 远程仓库，格式 名称=地址，可重复
+        #[arg(short, long = "remote")]
+        remotes: Vec<String>,
+        /// 环境变量，格式 变量名=值，可重复
+        #[arg(short, long = "env")]
+        env: Vec<String>,
+    },
+}
+
+/// 创建工作区时的可选信息
+#[derive(Args, Debug, Clone)]
+pub struct WorkspaceArgs {
+    /// 工作区名称，默认为当前目录名
+    #[arg(short, long)]
+    pub name: Option<String>,
+    /// 描述
+    #[arg(short, long)]
+    pub description: Option<String>,
+    /// 默认分支
+    #[arg(short = 'b', long = "branch")]
+    pub branch: Option<String>,
 }
 
 
 #[derive(Subcommand, Debug)]
 pub enum DbCommands {
-    /// 清理 SQL 文件中 INSERT 的 id 字段及对应的首个值（原地修改，生成 .bak 备份）
+    /// 清理 SQL 文件中 INSERT 的 id 字段及对应位置的值
     Rmid {
-        /// SQL 文件路径
-        sql_file_path: String,
+        /// SQL 文件、目录或通配符（如 'sql/**/*.sql'），目录会递归处理其中的 .sql 文件
+        #[arg(required = true)]
+        paths: Vec<String>,
+        #[command(flatten)]
+        output: OutputArgs,
+        /// SQL 方言，决定引号、转义及语句切分规则
+        #[arg(short, long, value_enum, default_value = "mysql")]
+        dialect: Dialect,
+    },
+    /// 删除 SQL 文件中 INSERT 的指定列及每个元组中对应位置的值
+    Dropcol {
+        /// SQL 文件、目录或通配符（如 'sql/**/*.sql'），目录会递归处理其中的 .sql 文件
+        #[arg(required = true)]
+        paths: Vec<String>,
+        #[command(flatten)]
+        output: OutputArgs,
+        /// 要删除的列名，多个用逗号分隔，例如 id,create_time,update_time
+        #[arg(short, long, value_delimiter = ',', required = true)]
+        column: Vec<String>,
+        /// SQL 方言，决定引号、转义及语句切分规则
+        #[arg(short, long, value_enum, default_value = "mysql")]
+        dialect: Dialect,
+    },
+    /// 将 INSERT 语句从一种 SQL 方言转换为另一种
+    Convert {
+        /// SQL 文件、目录或通配符（如 'sql/**/*.sql'），目录会递归处理其中的 .sql 文件
+        #[arg(required = true)]
+        paths: Vec<String>,
+        #[command(flatten)]
+        output: OutputArgs,
+        /// 源方言
+        #[arg(long, value_enum)]
+        from: Dialect,
+        /// 目标方言
+        #[arg(long, value_enum)]
+        to: Dialect,
+        /// 目标库不支持多行 VALUES 时的改写方式
+        #[arg(long, value_enum, default_value = "split")]
+        multi_row: MultiRowMode,
+        /// 保留日期时间字符串，不改写为目标库的日期字面量
+        #[arg(long)]
+        keep_date_strings: bool,
+    },
+    /// 将 INSERT 改写为按主键幂等执行的 upsert 语句
+    Upsert {
+        /// SQL 文件、目录或通配符（如 'sql/**/*.sql'），目录会递归处理其中的 .sql 文件
+        #[arg(required = true)]
+        paths: Vec<String>,
+        #[command(flatten)]
+        output: OutputArgs,
+        /// 主键或唯一键列，多个用逗号分隔，例如 tenant_id,code
+        #[arg(short, long, value_delimiter = ',', required = true)]
+        key: Vec<String>,
+        /// SQL 方言，决定生成 ON DUPLICATE KEY UPDATE、MERGE INTO 还是 ON CONFLICT
+        #[arg(short, long, value_enum, default_value = "mysql")]
+        dialect: Dialect,
+    },
+    /// 将 INSERT 数据按表导出为 CSV 或 JSON 文件，每个 SQL 文件导出到一个目录
+    /// （默认为 SQL 文件同目录下与文件同名的目录，-o 指定输出目录）
+    Export {
+        /// SQL 文件、目录或通配符（如 'sql/**/*.sql'），目录会递归处理其中的 .sql 文件
+        #[arg(required = true)]
+        paths: Vec<String>,
+        #[command(flatten)]
+        output: OutputArgs,
+        /// 导出格式
+        #[arg(short, long, value_enum, default_value = "csv")]
+        format: DataFormat,
+        /// SQL 方言，决定引号、转义及语句切分规则
+        #[arg(short, long, value_enum, default_value = "mysql")]
+        dialect: Dialect,
+    },
+    /// 由 CSV 或 JSON 文件生成 INSERT 脚本，默认写入数据文件同名的 .sql 文件
+    Import {
+        /// 数据文件（.csv 或 .json）、目录或通配符，目录会递归处理其中的 .csv 与 .json 文件
+        #[arg(required = true)]
+        paths: Vec<String>,
+        #[command(flatten)]
+        output: OutputArgs,
+        /// 目标表名，默认为数据文件名
+        #[arg(short, long)]
+        table: Option<String>,
+        /// 列类型，多个用逗号分隔，例如 id=int,price=decimal,birthday=date
+        /// （可选 auto、int、decimal、text、bool、date、datetime、raw）
+        #[arg(long, value_delimiter = ',')]
+        types: Vec<String>,
+        /// SQL 方言，决定引号、转义及字面量写法
+        #[arg(short, long, value_enum, default_value = "mysql")]
+        dialect: Dialect,
+    },
+    /// 根据 INSERT 脚本生成逆序的 DELETE 回滚脚本，默认写入同目录下的 <文件名>.rollback.sql
+    Rollback {
+        /// SQL 文件、目录或通配符（如 'sql/**/*.sql'），目录会递归处理其中的 .sql 文件
+        #[arg(required = true)]
+        paths: Vec<String>,
+        #[command(flatten)]
+        output: OutputArgs,
+        /// 主键列，多个用逗号分隔（复合主键），例如 tenant_id,code
+        #[arg(short, long, value_delimiter = ',', required = true)]
+        key: Vec<String>,
+        /// SQL 方言，决定引号、转义及语句切分规则
+        #[arg(short, long, value_enum, default_value = "mysql")]
+        dialect: Dialect,
     },
+    /// 发布前检查 SQL 脚本，发现 error 级别问题时以非零状态退出
+    Lint {
+        /// 要检查的 SQL 文件、目录或通配符，目录会递归检查其中的 .sql 文件
+        #[arg(required = true)]
+        files: Vec<String>,
+        /// 关闭的规则，多个用逗号分隔，例如 insert-without-columns
+        #[arg(long, value_delimiter = ',')]
+        disable: Vec<String>,
+        /// 调整规则级别，多个用逗号分隔，例如 missing-commit=warning
+        #[arg(long, value_delimiter = ',')]
+        severity: Vec<String>,
+        /// JSON 配置文件：{"rules": {"规则": "error|warning|off"}, "keys": ["id"]}
+        #[arg(long)]
+        config: Option<String>,
+        /// duplicate-key 规则使用的主键列，多个用逗号分隔
+        #[arg(short, long, value_delimiter = ',')]
+        key: Vec<String>,
+        /// 报告格式
+        #[arg(short, long, value_enum, default_value = "text")]
+        format: ReportFormat,
+        /// SQL 方言，决定引号、转义及语句切分规则
+        #[arg(short, long, value_enum, default_value = "mysql")]
+        dialect: Dialect,
+    },
+    /// 在内存 SQLite 中执行建表与数据脚本，报告执行失败的语句与约束冲突
+    Check {
+        /// 数据脚本，可为文件、目录或通配符，按给出的顺序执行
+        #[arg(required = true)]
+        files: Vec<String>,
+        /// 建表脚本，先于数据脚本执行，可指定多次
+        #[arg(short, long)]
+        schema: Vec<String>,
+        /// SQL 方言，决定引号、转义及语句切分规则
+        #[arg(short, long, value_enum, default_value = "mysql")]
+        dialect: Dialect,
+    },
+    /// 替换 SQL 脚本中的表名与 schema 前缀，字符串与注释保持不变
+    Rename {
+        /// SQL 文件、目录或通配符（如 'sql/**/*.sql'），目录会递归处理其中的 .sql 文件
+        #[arg(required = true)]
+        paths: Vec<String>,
+        #[command(flatten)]
+        output: OutputArgs,
+        /// 表名替换，格式为 旧名=新名，可指定多次，例如 t_user=t_user_bak
+        #[arg(short, long)]
+        table: Vec<String>,
+        /// schema 前缀替换，格式为 旧名=新名，可指定多次，例如 DEV_SCHEMA=UAT_SCHEMA
+        #[arg(short, long)]
+        schema: Vec<String>,
+        /// SQL 方言，决定引号、转义及语句切分规则
+        #[arg(short, long, value_enum, default_value = "mysql")]
+        dialect: Dialect,
+    },
+    /// 按主键删除 INSERT 中的重复行，可选按主键排序
+    Dedup {
+        /// SQL 文件、目录或通配符（如 'sql/**/*.sql'），目录会递归处理其中的 .sql 文件
+        #[arg(required = true)]
+        paths: Vec<String>,
+        #[command(flatten)]
+        output: OutputArgs,
+        /// 主键列，多个列用逗号分隔
+        #[arg(short, long, value_delimiter = ',', required = true)]
+        key: Vec<String>,
+        /// 主键重复时保留的行：first 保留第一行，last 保留最后一行，fail 在值不一致时报错且不修改文件
+        #[arg(long, value_enum, default_value = "first")]
+        keep: KeepPolicy,
+        /// 按主键排序数据行，便于比较差异
+        #[arg(long)]
+        sort: bool,
+        /// SQL 方言，决定引号、转义及语句切分规则
+        #[arg(short, long, value_enum, default_value = "mysql")]
+        dialect: Dialect,
+    },
+    /// 按规则脱敏 INSERT 中的敏感列，例如身份证号、手机号、姓名、账号
+    Mask {
+        /// SQL 文件、目录或通配符（如 'sql/**/*.sql'），目录会递归处理其中的 .sql 文件
+        #[arg(required = true)]
+        paths: Vec<String>,
+        #[command(flatten)]
+        output: OutputArgs,
+        /// 脱敏规则，格式为 [表名.]列名=方式，可指定多次。方式：hash[:长度]、partial[:前:后]、digits、idcard、phone、name、null
+        #[arg(short, long, required = true)]
+        rule: Vec<String>,
+        /// 随机种子，相同种子下相同的值脱敏结果相同，外键关联保持一致
+        #[arg(long, default_value = "synapse")]
+        seed: String,
+        /// SQL 方言，决定引号、转义及语句切分规则
+        #[arg(short, long, value_enum, default_value = "mysql")]
+        dialect: Dialect,
+    },
+    /// 按主键比较两份 INSERT 脚本，列出新增、删除与修改的行
+    Diff {
+        /// 旧 SQL 文件路径
+        old_file: String,
+        /// 新 SQL 文件路径
+        new_file: String,
+        /// 主键列，多个列用逗号分隔
+        #[arg(short, long, value_delimiter = ',', required = true)]
+        key: Vec<String>,
+        /// 报告格式
+        #[arg(short, long, value_enum, default_value = "text")]
+        format: ReportFormat,
+        /// 输出把旧数据变为新数据的 DELETE/UPDATE/INSERT 脚本
+        #[arg(short, long)]
+        script: Option<String>,
+        /// SQL 方言，决定引号、转义及语句切分规则
+        #[arg(short, long, value_enum, default_value = "mysql")]
+        dialect: Dialect,
+    },
+    /// 比较两份建表脚本，生成 ALTER TABLE/CREATE INDEX 迁移语句，默认输出到标准输出
+    Schemadiff {
+        /// 旧建表脚本路径
+        old_file: String,
+        /// 新建表脚本路径
+        new_file: String,
+        #[command(flatten)]
+        output: OutputArgs,
+        /// 直接输出删除表、删除列、缩小类型等会丢失数据的语句，默认以注释形式输出
+        #[arg(long)]
+        allow_destructive: bool,
+        /// SQL 方言，决定引号、转义及语句切分规则
+        #[arg(short, long, value_enum, default_value = "mysql")]
+        dialect: Dialect,
+    },
+    /// 按表拆分 SQL 脚本，每张表一个文件，每个 SQL 文件拆分到一个目录
+    /// （默认为与 SQL 文件同名的目录，-o 指定输出目录）
+    Split {
+        /// SQL 文件、目录或通配符（如 'sql/**/*.sql'），目录会递归处理其中的 .sql 文件
+        #[arg(required = true)]
+        paths: Vec<String>,
+        #[command(flatten)]
+        output: OutputArgs,
+        /// SQL 方言，决定引号、转义及语句切分规则
+        #[arg(short, long, value_enum, default_value = "mysql")]
+        dialect: Dialect,
+    },
+    /// 按表与条件抽取 SQL 脚本中的语句与数据行，默认输出到标准输出
+    Extract {
+        /// SQL 文件、目录或通配符（如 'sql/**/*.sql'），目录会递归处理其中的 .sql 文件
+        #[arg(required = true)]
+        paths: Vec<String>,
+        #[command(flatten)]
+        output: OutputArgs,
+        /// 保留的表，多个表用逗号分隔，不指定时保留所有表
+        #[arg(short, long, value_delimiter = ',')]
+        table: Vec<String>,
+        /// 过滤 INSERT 行的 SQL 条件，例如 "status='A'"
+        #[arg(short = 'w', long = "where")]
+        filter: Option<String>,
+        /// SQL 方言，决定引号、转义及语句切分规则
+        #[arg(short, long, value_enum, default_value = "mysql")]
+        dialect: Dialect,
+    },
+    /// 按依赖顺序合并多个 SQL 脚本，去掉重复的会话设置语句，默认输出到标准输出
+    Merge {
+        /// SQL 文件、目录或通配符，目录会递归处理其中的 .sql 文件
+        #[arg(required = true)]
+        paths: Vec<String>,
+        #[command(flatten)]
+        output: OutputArgs,
+        /// SQL 方言，决定引号、转义及语句切分规则
+        #[arg(short, long, value_enum, default_value = "mysql")]
+        dialect: Dialect,
+    },
+    /// 把 INSERT 语句按行数与字节数重新分批
+    Rebatch {
+        /// SQL 文件、目录或通配符（如 'sql/**/*.sql'），目录会递归处理其中的 .sql 文件
+        #[arg(required = true)]
+        paths: Vec<String>,
+        #[command(flatten)]
+        output: OutputArgs,
+        /// 每条 INSERT 最多的行数
+        #[arg(short, long)]
+        rows: Option<usize>,
+        /// 每条 INSERT 最多的字节数，支持 K、M、G 后缀，例如 1M
+        #[arg(short, long)]
+        max_bytes: Option<String>,
+        /// 每输出 N 条 INSERT 插入一次 COMMIT
+        #[arg(short, long)]
+        commit_every: Option<usize>,
+        /// SQL 方言，决定引号、转义及语句切分规则
+        #[arg(short, long, value_enum, default_value = "mysql")]
+        dialect: Dialect,
+    },
+    /// 转换文件、目录或通配符匹配的文件的编码
+    Recode {
+        /// 文件、目录或通配符，目录会递归处理其中 --ext 指定扩展名的文件
+        #[arg(required = true)]
+        paths: Vec<String>,
+        #[command(flatten)]
+        output: OutputArgs,
+        /// 目标编码，可加 -bom 后缀，例如 utf-8、utf-8-bom、gbk
+        #[arg(short, long, default_value = "utf-8")]
+        to: String,
+        /// 处理目录时包含的扩展名，多个用逗号分隔
+        #[arg(short, long, value_delimiter = ',', default_value = "sql")]
+        ext: Vec<String>,
+    },
+    /// 格式化 SQL 脚本：统一关键字大小写与缩进，VALUES 每行一个元组，对齐列定义并补全分号
+    Fmt {
+        /// SQL 文件、目录或通配符（如 'sql/**/*.sql'），目录会递归处理其中的 .sql 文件
+        #[arg(required = true)]
+        paths: Vec<String>,
+        #[command(flatten)]
+        output: OutputArgs,
+        /// 只检查不修改，存在未格式化的文件时以状态码 1 退出
+        #[arg(long, conflicts_with_all = ["output", "stdout", "in_place"])]
+        check: bool,
+        /// 关键字大小写
+        #[arg(short, long, value_enum, default_value = "upper")]
+        keyword_case: KeywordCase,
+        /// 缩进的空格数
+        #[arg(short, long, default_value = "2")]
+        indent: usize,
+        /// SQL 方言，决定引号、转义及语句切分规则
+        #[arg(short, long, value_enum, default_value = "mysql")]
+        dialect: Dialect,
+    },
+    /// 由 INSERT 数据推断列类型、长度、可空性与候选键，生成 CREATE TABLE 语句，默认输出到标准输出
+    InferSchema {
+        /// SQL 文件、目录或通配符，多个文件中的同一张表合并推断
+        #[arg(required = true)]
+        paths: Vec<String>,
+        #[command(flatten)]
+        output: OutputArgs,
+        /// SQL 方言，决定引号、转义、语句切分规则及生成的列类型
+        #[arg(short, long, value_enum, default_value = "mysql")]
+        dialect: Dialect,
+    },
+    /// 把指定表的主键重新编号为连续的值，并同步改写引用它们的外键列
+    RemapId {
+        /// SQL 文件、目录或通配符，所有文件共用同一份新旧主键对照
+        #[arg(required = true)]
+        paths: Vec<String>,
+        #[command(flatten)]
+        output: OutputArgs,
+        /// 要重新编号的表，可指定多次，每张表都从 --start 开始编号
+        #[arg(short, long, required = true)]
+        table: Vec<String>,
+        /// 主键列
+        #[arg(short, long, default_value = "id")]
+        key: String,
+        /// 第一个新主键值
+        #[arg(short, long, default_value = "1", allow_negative_numbers = true)]
+        start: i64,
+        /// 引用主键的外键列，格式为 表.列=被引用表.主键列，可指定多次，例如 t_order.user_id=t_user.id
+        #[arg(long)]
+        fk: Vec<String>,
+        /// SQL 方言，决定引号、转义及语句切分规则
+        #[arg(short, long, value_enum, default_value = "mysql")]
+        dialect: Dialect,
+    },
+}
+
+/// db 命令的输出方式。改写类命令均未指定时原地修改，不生成备份；
+/// 生成新文件的命令不修改原文件，不接受 --in-place 与 --backup-suffix
+#[derive(Args, Debug, Clone)]
+pub struct OutputArgs {
+    /// 输出文件，原文件不变；处理多个文件时为输出目录，按输入的相对路径存放
+    #[arg(short, long, conflicts_with_all = ["stdout", "in_place"])]
+    pub output: Option<String>,
+    /// 输出到标准输出，原文件不变
+    #[arg(long, conflicts_with = "in_place")]
+    pub stdout: bool,
+    /// 原地修改（默认），不生成备份
+    #[arg(long)]
+    pub in_place: bool,
+    /// 原地修改并生成备份，指定备份文件的后缀，例如 .bak
+    #[arg(long, conflicts_with_all = ["output", "stdout"])]
+    pub backup_suffix: Option<String>,
 }
\ No newline at end of file
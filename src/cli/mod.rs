@@ -1,3 +1,3 @@
 pub mod commands;
 
-pub use commands::{Cli, Commands, RepoCommands, WorkspaceCommands, DbCommands};
\ No newline at end of file
+pub use commands::{Cli, Commands, RepoCommands, WorkspaceCommands, WorkspaceArgs, DbCommands, OutputArgs};
\ No newline at end of file
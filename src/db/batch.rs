@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::stream::Output;
+use crate::cli::OutputArgs;
+use crate::utils::{disable_progress, expand_inputs, InputFile, ParallelExecutor};
+
+/// 单个文件的处理结果
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    /// 处理过程中的说明与警告，逐行输出
+    pub lines: Vec<String>,
+    /// 汇总中该文件的一行说明
+    pub summary: String,
+    /// 是否写出了结果
+    pub written: bool,
+    pub failed: bool,
+}
+
+impl Outcome {
+    pub fn done(lines: Vec<String>, summary: String) -> Self {
+        Outcome { lines, summary, written: true, failed: false }
+    }
+
+    pub fn unchanged(lines: Vec<String>, summary: String) -> Self {
+        Outcome { lines, summary, written: false, failed: false }
+    }
+
+    pub fn failed(lines: Vec<String>, summary: String) -> Self {
+        Outcome { lines, summary, written: false, failed: true }
+    }
+}
+
+/// 展开输入的文件、目录与通配符，目录中收集指定扩展名的文件；没有文件时返回错误
+pub fn expand_files(patterns: &[String], extensions: &[String]) -> Result<Vec<InputFile>, String> {
+    let files = expand_inputs(patterns, extensions)?;
+    if files.is_empty() {
+        return Err(format!("未找到 {} 文件: {}", extensions.join("/").to_uppercase(), patterns.join(" ")));
+    }
+    Ok(files)
+}
+
+/// 命令结果的去向
+#[derive(Debug, Clone, Copy)]
+pub enum OutputKind {
+    /// 改写输入文件，默认原地修改
+    Rewrite,
+    /// 每个输入生成一个新文件，默认位置由输入路径得到；None 时默认输出到标准输出
+    File(Option<fn(&Path) -> PathBuf>),
+    /// 每个输入生成一个目录，默认位置由输入路径得到
+    Directory(fn(&Path) -> PathBuf),
+}
+
+/// 按命令行参数确定每个输入文件的输出方式；多个文件时 `-o` 为输出目录，按输入的相对路径存放。
+/// 不同目录下的同名文件会写到同一个输出文件时返回错误
+pub fn batch_outputs(files: &[InputFile], args: &OutputArgs, kind: OutputKind) -> Result<Vec<Output>, String> {
+    check_generated(args, kind)?;
+    let multiple = files.len() > 1;
+    let mut sources: HashMap<PathBuf, &Path> = HashMap::new();
+    files
+        .iter()
+        .map(|file| {
+            if args.stdout {
+                return Ok(Output::Stdout);
+            }
+            let derived = match kind {
+                OutputKind::Rewrite | OutputKind::File(None) => None,
+                OutputKind::File(Some(derive)) | OutputKind::Directory(derive) => Some(derive(&file.path)),
+            };
+            let Some(output) = &args.output else {
+                return Ok(match (kind, derived) {
+                    (OutputKind::Rewrite, _) => Output::InPlace { backup: args.backup_suffix.clone() },
+                    (_, Some(path)) => Output::File(path),
+                    (_, None) => Output::Stdout,
+                });
+            };
+            // 生成的文件沿用默认位置的文件名，例如 a.rollback.sql
+            let name = match derived.as_deref().and_then(Path::file_name) {
+                Some(name) => file.relative.with_file_name(name),
+                None => file.relative.clone(),
+            };
+            let target = Path::new(output);
+            let path = if multiple {
+                target.join(&name)
+            } else if matches!(kind, OutputKind::Directory(_)) || !target.is_dir() {
+                target.to_path_buf()
+            } else {
+                target.join(name.file_name().unwrap_or_default())
+            };
+            if let Some(other) = sources.insert(path.clone(), &file.path) {
+                return Err(format!("{} 与 {} 会写入同一个输出文件 {}，请分别处理或改为传入所在目录", other.display(), file.path.display(), path.display()));
+            }
+            if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                fs::create_dir_all(parent).map_err(|e| format!("创建目录失败 {}: {}", parent.display(), e))?;
+            }
+            Ok(Output::File(path))
+        })
+        .collect()
+}
+
+/// 由多个输入生成一份结果的命令（merge、schemadiff、infer-schema）的输出方式：`-o` 为输出文件，默认输出到标准输出
+pub fn single_output(args: &OutputArgs) -> Result<Output, String> {
+    check_generated(args, OutputKind::File(None))?;
+    Ok(args.output.as_ref().map(|o| Output::File(PathBuf::from(o))).unwrap_or(Output::Stdout))
+}
+
+/// 生成新文件的命令不修改输入文件，不接受原地修改的参数；生成目录的命令不能输出到标准输出
+fn check_generated(args: &OutputArgs, kind: OutputKind) -> Result<(), String> {
+    match kind {
+        OutputKind::Rewrite => Ok(()),
+        _ if args.in_place || args.backup_suffix.is_some() => Err("该命令生成新文件，不支持 --in-place 与 --backup-suffix".to_string()),
+        OutputKind::Directory(_) if args.stdout => Err("该命令为每个输入生成一个目录，不支持 --stdout".to_string()),
+        _ => Ok(()),
+    }
+}
+
+/// 结果去向的说明，例如备份文件或输出文件
+fn output_note(path: &Path, output: &Output, outcome: &Outcome) -> String {
+    match output {
+        _ if !outcome.written => String::new(),
+        Output::InPlace { .. } => output
+            .backup_path(path)
+            .map(|backup| format!("（已生成备份：{}）", backup.file_name().unwrap_or_default().to_string_lossy()))
+            .unwrap_or_default(),
+        Output::File(target) => format!(" -> {}", target.display()),
+        Output::Stdout => String::new(),
+    }
+}
+
+/// 批量执行改写类命令：展开输入后并行处理各文件（输出到标准输出时依次处理），
+/// 按输入顺序输出每个文件的信息，处理多个文件时最后汇总每个文件的改动。
+/// 输出到标准输出时提示信息写到 stderr；有文件失败时返回错误
+pub fn run_batch<F>(patterns: &[String], args: &OutputArgs, process: F) -> Result<(), String>
+where
+    F: Fn(&Path, &Output) -> Result<Outcome, String> + Send + Sync + 'static,
+{
+    run_batch_with(patterns, &["sql".to_string()], args, process)
+}
+
+/// 同 [`run_batch`]，目录中收集指定扩展名的文件
+pub fn run_batch_with<F>(patterns: &[String], extensions: &[String], args: &OutputArgs, process: F) -> Result<(), String>
+where
+    F: Fn(&Path, &Output) -> Result<Outcome, String> + Send + Sync + 'static,
+{
+    run_batch_as(patterns, extensions, args, OutputKind::Rewrite, process)
+}
+
+/// 同 [`run_batch_with`]，按 kind 确定结果的去向，用于生成新文件或目录的命令
+pub fn run_batch_as<F>(patterns: &[String], extensions: &[String], args: &OutputArgs, kind: OutputKind, process: F) -> Result<(), String>
+where
+    F: Fn(&Path, &Output) -> Result<Outcome, String> + Send + Sync + 'static,
+{
+    let files = expand_files(patterns, extensions)?;
+    let outputs = batch_outputs(&files, args, kind)?;
+    let to_stdout = outputs.contains(&Output::Stdout);
+    let say = |line: &str| if to_stdout { eprintln!("{}", line) } else { println!("{}", line) };
+
+    if let [file] = files.as_slice() {
+        let outcome = process(&file.path, &outputs[0]).map_err(|e| format!("处理失败: {}", e))?;
+        outcome.lines.iter().for_each(|line| say(line));
+        if outcome.failed {
+            return Err(outcome.summary);
+        }
+        say(&format!("✅ 处理完成，{}{}", outcome.summary, output_note(&file.path, &outputs[0], &outcome)));
+        return Ok(());
+    }
+
+    disable_progress();
+    let paths: Vec<PathBuf> = files.iter().map(|f| f.path.clone()).collect();
+    let targets: HashMap<PathBuf, Output> = paths.iter().cloned().zip(outputs).collect();
+    let results = if to_stdout {
+        paths.into_iter().map(|path| {
+            let result = process(&path, &targets[&path]);
+            (path, result)
+        }).collect()
+    } else {
+        let targets = targets.clone();
+        ParallelExecutor::map(paths, move |path| process(path, &targets[path]))
+    };
+
+    for (path, result) in &results {
+        let lines = match result {
+            Ok(outcome) => &outcome.lines,
+            Err(_) => continue,
+        };
+        if !lines.is_empty() {
+            say(&format!("{}:", path.display()));
+            lines.iter().for_each(|line| say(&format!("  {}", line)));
+        }
+    }
+
+    say("处理结果：");
+    let (mut written, mut unchanged, mut failed) = (0, 0, 0);
+    for (path, result) in &results {
+        match result {
+            Ok(outcome) if outcome.failed => {
+                failed += 1;
+                say(&format!("  [FAIL] {}: {}", path.display(), outcome.summary));
+            }
+            Ok(outcome) => {
+                if outcome.written { written += 1 } else { unchanged += 1 }
+                say(&format!("  {}: {}{}", path.display(), outcome.summary, output_note(path, &targets[path], outcome)));
+            }
+            Err(e) => {
+                failed += 1;
+                say(&format!("  [FAIL] {}: {}", path.display(), e));
+            }
+        }
+    }
+    let summary = format!("共 {} 个文件：已处理 {} 个，无需改动 {} 个，失败 {} 个", results.len(), written, unchanged, failed);
+    if failed > 0 {
+        return Err(summary);
+    }
+    say(&format!("✅ {}", summary));
+    Ok(())
+}
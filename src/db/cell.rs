@@ -0,0 +1,185 @@
+use std::sync::LazyLock;
+
+use clap::ValueEnum;
+use regex::Regex;
+use serde_json::{Map, Number, Value};
+
+use super::convert::oracle_datetime;
+use super::dialect::Dialect;
+use super::lexer::{Token, TokenKind};
+
+static NUMBER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^-?(\d+\.?\d*|\.\d+)([eE][-+]?\d+)?$").unwrap());
+static INTEGER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^-?\d+$").unwrap());
+static DATE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\d{4}-\d{2}-\d{2}$").unwrap());
+
+/// CSV 中表示 NULL 的单元格
+const CSV_NULL: &str = "\\N";
+/// CSV 中原样写入 SQL 的表达式前缀，例如 `\=NOW()`
+const CSV_RAW: &str = "\\=";
+/// JSON 中原样写入 SQL 的表达式，例如 `{"sql": "NOW()"}`
+const JSON_RAW: &str = "sql";
+
+/// INSERT 元组中的一个值
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cell {
+    Null,
+    /// 数字字面量的原文
+    Number(String),
+    /// 字符串字面量的内容
+    Text(String),
+    /// 其他表达式的原文，例如 NOW()、DATE '2024-01-01'
+    Raw(String),
+}
+
+/// 导入时的列类型，决定单元格生成的 SQL 字面量
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColumnType {
+    /// 按单元格内容判断：数字不加引号，其余为字符串
+    Auto,
+    Int,
+    Decimal,
+    Text,
+    Bool,
+    Date,
+    Datetime,
+    /// 原样写入 SQL 表达式
+    Raw,
+}
+
+impl Cell {
+    /// 由元组中的一项生成单元格
+    pub fn from_item(tokens: &[Token], dialect: Dialect) -> Cell {
+        let significant: Vec<&Token> = tokens.iter().filter(|t| t.is_significant()).collect();
+        match significant.as_slice() {
+            [t] if t.is_keyword("null") => Cell::Null,
+            [t] if t.kind == TokenKind::Number => Cell::Number(t.text.to_string()),
+            [sign, t] if sign.text == "-" && t.kind == TokenKind::Number => Cell::Number(format!("-{}", t.text)),
+            [t] if t.kind == TokenKind::String => match dialect.unquote_string(t) {
+                Some(value) => Cell::Text(value),
+                None => Cell::Raw(t.text.to_string()),
+            },
+            _ => {
+                let first = tokens.iter().position(|t| t.is_significant()).unwrap_or(0);
+                let last = tokens.iter().rposition(|t| t.is_significant()).map(|k| k + 1).unwrap_or(first);
+                Cell::Raw(tokens[first..last].iter().map(|t| t.text).collect())
+            }
+        }
+    }
+
+    /// 比较用的文本：1 与 '1' 在数据库中视为同一个值
+    pub fn normalized(&self) -> String {
+        match self {
+            Cell::Number(v) | Cell::Text(v) => v.clone(),
+            other => other.to_csv(),
+        }
+    }
+
+    /// CSV 单元格：NULL 写作 `\N`，表达式写作 `\=表达式`，以 `\` 开头的文本再加一个 `\`；
+    /// 看起来像数字的文本加引号，以便和数字区分
+    pub fn to_csv(&self) -> String {
+        let (field, force_quote) = match self {
+            Cell::Null => return CSV_NULL.to_string(),
+            Cell::Number(n) => return n.clone(),
+            Cell::Raw(expr) => (format!("{}{}", CSV_RAW, expr), false),
+            Cell::Text(s) if s.starts_with('\\') => (format!("\\{}", s), false),
+            Cell::Text(s) => (s.clone(), NUMBER.is_match(s)),
+        };
+        let needs_quote = force_quote
+            || field.contains([',', '"', '\n', '\r'])
+            || field.starts_with(char::is_whitespace)
+            || field.ends_with(char::is_whitespace);
+        if needs_quote {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field
+        }
+    }
+
+    /// 解析 CSV 单元格，quoted 表示原文是否带引号
+    pub fn from_csv(field: &str, quoted: bool) -> Cell {
+        if let Some(rest) = field.strip_prefix('\\') {
+            if rest.starts_with('\\') {
+                return Cell::Text(rest.to_string());
+            }
+            if rest == "N" {
+                return Cell::Null;
+            }
+            if let Some(expr) = field.strip_prefix(CSV_RAW) {
+                return Cell::Raw(expr.to_string());
+            }
+        }
+        if !quoted && NUMBER.is_match(field) {
+            Cell::Number(field.to_string())
+        } else {
+            Cell::Text(field.to_string())
+        }
+    }
+
+    /// JSON 值：数字能精确表示时为数字，否则与其他表达式一样写作 `{"sql": "..."}`
+    pub fn to_json(&self) -> Value {
+        match self {
+            Cell::Null => Value::Null,
+            Cell::Text(s) => Value::String(s.clone()),
+            Cell::Number(n) => match n.parse::<Number>() {
+                Ok(num) if num.to_string() == *n => Value::Number(num),
+                _ => raw_json(n),
+            },
+            Cell::Raw(expr) => raw_json(expr),
+        }
+    }
+
+    /// 解析 JSON 值
+    pub fn from_json(value: &Value) -> Result<Cell, String> {
+        match value {
+            Value::Null => Ok(Cell::Null),
+            Value::Number(n) => Ok(Cell::Number(n.to_string())),
+            Value::String(s) => Ok(Cell::Text(s.clone())),
+            Value::Bool(b) => Ok(Cell::Raw(if *b { "TRUE" } else { "FALSE" }.to_string())),
+            Value::Object(map) => match map.get(JSON_RAW) {
+                Some(Value::String(expr)) if map.len() == 1 => Ok(Cell::Raw(expr.clone())),
+                _ => Err(format!("无法识别的值: {}", value)),
+            },
+            Value::Array(_) => Err(format!("无法识别的值: {}", value)),
+        }
+    }
+
+    /// 按列类型生成 SQL 字面量
+    pub fn to_sql(&self, ty: ColumnType, dialect: Dialect) -> Result<String, String> {
+        let text = match self {
+            Cell::Null => return Ok("NULL".to_string()),
+            Cell::Raw(expr) => return Ok(expr.clone()),
+            Cell::Number(n) if ty == ColumnType::Auto => return Ok(n.clone()),
+            Cell::Number(s) | Cell::Text(s) => s.as_str(),
+        };
+        let oracle_like = matches!(dialect, Dialect::Oracle | Dialect::Dm);
+        // 表格软件中清空的非文本单元格视为 NULL
+        if text.is_empty() && !matches!(ty, ColumnType::Auto | ColumnType::Text) {
+            return Ok("NULL".to_string());
+        }
+
+        match ty {
+            ColumnType::Auto | ColumnType::Text => Ok(dialect.quote_string(text)),
+            ColumnType::Raw => Ok(text.to_string()),
+            ColumnType::Int if INTEGER.is_match(text) => Ok(text.to_string()),
+            ColumnType::Decimal if NUMBER.is_match(text) => Ok(text.to_string()),
+            ColumnType::Bool => match text.to_ascii_lowercase().as_str() {
+                "true" | "1" | "yes" | "y" => Ok(if oracle_like { "1" } else { "TRUE" }.to_string()),
+                "false" | "0" | "no" | "n" => Ok(if oracle_like { "0" } else { "FALSE" }.to_string()),
+                _ => Err(format!("不是布尔值: {}", text)),
+            },
+            ColumnType::Date if DATE.is_match(text) => Ok(format!("DATE {}", dialect.quote_string(text))),
+            ColumnType::Datetime if oracle_like => {
+                oracle_datetime(text, dialect).ok_or_else(|| format!("不是日期时间: {}", text))
+            }
+            ColumnType::Datetime => Ok(dialect.quote_string(text)),
+            ColumnType::Int | ColumnType::Decimal => Err(format!("不是数字: {}", text)),
+            ColumnType::Date => Err(format!("不是日期: {}", text)),
+        }
+    }
+}
+
+fn raw_json(expr: &str) -> Value {
+    let mut map = Map::new();
+    map.insert(JSON_RAW.to_string(), Value::String(expr.to_string()));
+    Value::Object(map)
+}
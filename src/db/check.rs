@@ -0,0 +1,382 @@
+use std::io::{BufRead, BufReader, Cursor};
+use std::path::Path;
+
+use rusqlite::functions::FunctionFlags;
+use rusqlite::types::Value;
+use rusqlite::{Connection, ErrorCode};
+
+use super::dialect::Dialect;
+use super::encoding::{input_encoding, open_decoded};
+use super::insert::{remove_items, ListSpan};
+use super::lexer::{matching_paren, next_significant, prev_significant, split_top_level, tokenize_with, Token, TokenKind};
+use super::stream::StatementReader;
+
+/// 建表语句中直接删除的 MySQL 列属性
+const DROPPED_ATTRIBUTES: &[&str] = &["auto_increment", "unsigned", "zerofill"];
+/// 后跟一个值、连同该值一起删除的列属性
+const DROPPED_WITH_VALUE: &[&str] = &["comment", "charset", "collate"];
+/// 不在 SQLite 中执行的语句
+const SKIPPED_STATEMENTS: &[&str] =
+    &["lock", "unlock", "use", "commit", "rollback", "start", "savepoint", "release", "delimiter", "grant", "revoke"];
+
+/// 单条语句的处理方式
+#[derive(Debug, PartialEq, Eq)]
+pub enum Normalized {
+    /// 只有空白与注释
+    Empty,
+    /// 跳过，附原因
+    Skip(String),
+    /// 开关外键检查（`SET FOREIGN_KEY_CHECKS = 0|1`）
+    ForeignKeys(bool),
+    /// 可在 SQLite 中执行的语句
+    Sql(String),
+}
+
+/// 执行失败的类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    /// 主键、唯一、非空、外键等约束冲突
+    Constraint,
+    /// 语法错误、表或列不存在等
+    Error,
+}
+
+/// 执行失败的语句
+#[derive(Debug, Clone)]
+pub struct CheckFailure {
+    pub file: String,
+    pub line: usize,
+    pub kind: FailureKind,
+    pub message: String,
+    /// 语句开头，便于定位
+    pub snippet: String,
+}
+
+/// 检查结果
+#[derive(Debug, Default)]
+pub struct CheckReport {
+    pub executed: usize,
+    pub skipped: usize,
+    pub failures: Vec<CheckFailure>,
+}
+
+impl CheckReport {
+    pub fn count(&self, kind: FailureKind) -> usize {
+        self.failures.iter().filter(|f| f.kind == kind).count()
+    }
+}
+
+/// 在内存 SQLite 中逐条执行脚本
+pub struct Checker {
+    conn: Connection,
+    dialect: Dialect,
+    report: CheckReport,
+}
+
+impl Checker {
+    pub fn new(dialect: Dialect) -> Result<Self, String> {
+        let conn = Connection::open_in_memory().map_err(|e| format!("创建 SQLite 数据库失败: {}", e))?;
+        conn.execute_batch("PRAGMA foreign_keys = ON;").map_err(|e| format!("初始化 SQLite 失败: {}", e))?;
+        register_functions(&conn).map_err(|e| format!("初始化 SQLite 失败: {}", e))?;
+        Ok(Checker { conn, dialect, report: CheckReport::default() })
+    }
+
+    /// 执行 SQL 文件，编码由 `--encoding` 指定或按内容识别
+    pub fn run_file(&mut self, path: &Path) -> Result<(), String> {
+        let reader = open_decoded(path, input_encoding(path)?)?;
+        self.run(&path.display().to_string(), BufReader::new(reader))
+    }
+
+    /// 执行 SQL 文本，name 用于报告
+    pub fn run_sql(&mut self, name: &str, sql: &str) -> Result<(), String> {
+        self.run(name, Cursor::new(sql.as_bytes()))
+    }
+
+    fn run<R: BufRead>(&mut self, name: &str, reader: R) -> Result<(), String> {
+        for statement in StatementReader::new(reader, self.dialect) {
+            let statement = statement?;
+            let tokens = tokenize_with(&statement.text, &self.dialect.lexer_config());
+            let line = statement.line + next_significant(&tokens, 0).map(|k| tokens[k].line - 1).unwrap_or(0);
+
+            let sql = match normalize_statement(&statement.text, self.dialect) {
+                Normalized::Empty => continue,
+                Normalized::Skip(_) => {
+                    self.report.skipped += 1;
+                    continue;
+                }
+                Normalized::ForeignKeys(on) => format!("PRAGMA foreign_keys = {};", if on { "ON" } else { "OFF" }),
+                Normalized::Sql(sql) => sql,
+            };
+            match self.conn.execute_batch(&sql) {
+                Ok(()) => self.report.executed += 1,
+                Err(e) => {
+                    let kind = match e.sqlite_error_code() {
+                        Some(ErrorCode::ConstraintViolation) => FailureKind::Constraint,
+                        _ => FailureKind::Error,
+                    };
+                    self.report.failures.push(CheckFailure {
+                        file: name.to_string(),
+                        line,
+                        kind,
+                        message: e.to_string(),
+                        snippet: snippet(&statement.text),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn finish(self) -> CheckReport {
+        self.report
+    }
+}
+
+/// 语句的第一行非空内容，最多 80 个字符
+fn snippet(text: &str) -> String {
+    let line = text.lines().map(|l| l.trim()).find(|l| !l.is_empty() && !l.starts_with("--")).unwrap_or("");
+    let mut out: String = line.chars().take(80).collect();
+    if line.chars().count() > 80 {
+        out.push_str("...");
+    }
+    out
+}
+
+/// 注册 SQLite 缺少的常用 MySQL / Oracle 函数
+fn register_functions(conn: &Connection) -> rusqlite::Result<()> {
+    let flags = FunctionFlags::SQLITE_UTF8;
+    // 只检查能否执行，时间取固定值即可
+    for name in ["now", "sysdate"] {
+        conn.create_scalar_function(name, 0, flags, |_| Ok("1970-01-01 00:00:00".to_string()))?;
+    }
+    conn.create_scalar_function("curdate", 0, flags, |_| Ok("1970-01-01".to_string()))?;
+    conn.create_scalar_function("uuid", 0, flags, |_| Ok("00000000-0000-0000-0000-000000000000".to_string()))?;
+    for name in ["to_date", "to_timestamp", "str_to_date"] {
+        conn.create_scalar_function(name, 2, flags | FunctionFlags::SQLITE_DETERMINISTIC, |ctx| ctx.get::<Value>(0))?;
+    }
+    conn.create_scalar_function("nvl", 2, flags | FunctionFlags::SQLITE_DETERMINISTIC, |ctx| {
+        let first = ctx.get::<Value>(0)?;
+        if first == Value::Null { ctx.get::<Value>(1) } else { Ok(first) }
+    })?;
+    Ok(())
+}
+
+/// 把一条语句改写为 SQLite 可执行的形式
+pub fn normalize_statement(sql: &str, dialect: Dialect) -> Normalized {
+    let tokens = tokenize_with(sql, &dialect.lexer_config());
+    let Some(first) = next_significant(&tokens, 0).filter(|&k| !tokens[k].is_punct(';')) else {
+        // MySQL 条件注释 /*!40014 SET FOREIGN_KEY_CHECKS=0 */ 中的语句
+        return match tokens.iter().find(|t| t.kind == TokenKind::BlockComment && t.text.starts_with("/*!")) {
+            Some(comment) => {
+                let Some(inner) = comment.text.strip_prefix("/*!").and_then(|t| t.strip_suffix("*/")) else {
+                    return Normalized::Skip("未结束的 MySQL 条件注释".to_string());
+                };
+                let inner = inner.trim_start_matches(|c: char| c.is_ascii_digit());
+                match normalize_statement(inner, dialect) {
+                    foreign_keys @ Normalized::ForeignKeys(_) => foreign_keys,
+                    Normalized::Empty => Normalized::Empty,
+                    _ => Normalized::Skip("MySQL 条件注释".to_string()),
+                }
+            }
+            None => Normalized::Empty,
+        };
+    };
+    let word = |k: Option<usize>| k.map(|k| tokens[k].text.to_ascii_lowercase()).unwrap_or_default();
+    let head = word(Some(first));
+    let second = next_significant(&tokens, first + 1);
+
+    if dialect.has_plsql_blocks() && matches!(head.as_str(), "begin" | "declare") {
+        return Normalized::Skip("PL/SQL 块".to_string());
+    }
+    if SKIPPED_STATEMENTS.contains(&head.as_str()) || (head == "begin" && tokens.iter().filter(|t| t.is_significant()).count() <= 2) {
+        return Normalized::Skip(format!("{} 语句", head.to_uppercase()));
+    }
+    if head == "set" {
+        if word(second) == "foreign_key_checks" {
+            let value = (first..tokens.len()).rev().find(|&k| tokens[k].kind == TokenKind::Number).map(|k| tokens[k].text);
+            return Normalized::ForeignKeys(value != Some("0"));
+        }
+        return Normalized::Skip("SET 语句".to_string());
+    }
+    let object = (first + 1..tokens.len())
+        .filter(|&k| tokens[k].kind == TokenKind::Word)
+        .map(|k| tokens[k].text.to_ascii_lowercase())
+        .find(|w| !matches!(w.as_str(), "or" | "replace" | "temporary" | "unique" | "editionable" | "noneditionable"))
+        .unwrap_or_default();
+    if matches!(head.as_str(), "create" | "drop" | "alter") {
+        if matches!(object.as_str(), "database" | "schema" | "procedure" | "function" | "trigger" | "package" | "view" | "sequence" | "user") {
+            return Normalized::Skip(format!("{} {}", head.to_uppercase(), object.to_uppercase()));
+        }
+        if head == "alter" && tokens.iter().any(|t| t.is_keyword("keys")) {
+            return Normalized::Skip("ALTER TABLE ... KEYS".to_string());
+        }
+    }
+
+    let mut out: Vec<Option<String>> = vec![None; tokens.len()];
+    for (k, t) in tokens.iter().enumerate() {
+        out[k] = match t.kind {
+            TokenKind::QuotedIdent => Some(Dialect::Postgres.quote_ident(&t.ident_name())),
+            TokenKind::String => dialect.unquote_string(t).map(|v| Dialect::Postgres.quote_string(&v)),
+            TokenKind::LineComment if t.text.starts_with('#') => Some(format!("--{}", &t.text[1..])),
+            TokenKind::Word if t.is_keyword("sysdate") || t.is_keyword("systimestamp") => {
+                let call = next_significant(&tokens, k + 1).is_some_and(|p| tokens[p].is_punct('('));
+                (!call).then(|| "CURRENT_TIMESTAMP".to_string())
+            }
+            _ => None,
+        };
+    }
+
+    if head == "insert" && word(second) == "ignore" {
+        out[second.unwrap()] = Some("OR IGNORE".to_string());
+    }
+    rewrite_on_duplicate_key(&tokens, &mut out);
+    if head == "create" && object == "table" {
+        rewrite_create_table(&tokens, &mut out);
+    }
+
+    Normalized::Sql(tokens.iter().zip(out).map(|(t, o)| o.unwrap_or_else(|| t.text.to_string())).collect())
+}
+
+/// `ON DUPLICATE KEY UPDATE c = VALUES(c)` -> `ON CONFLICT DO UPDATE SET c = excluded.c`
+fn rewrite_on_duplicate_key(tokens: &[Token], out: &mut [Option<String>]) {
+    let Some(dup) = tokens.iter().position(|t| t.is_keyword("duplicate")) else { return };
+    let on = prev_significant(tokens, dup.saturating_sub(1));
+    let key = next_significant(tokens, dup + 1);
+    let update = key.and_then(|k| next_significant(tokens, k + 1));
+    let (Some(on), Some(key), Some(update)) = (on, key, update) else { return };
+    if !(tokens[on].is_keyword("on") && tokens[key].is_keyword("key") && tokens[update].is_keyword("update")) {
+        return;
+    }
+    out[on] = Some("ON CONFLICT DO UPDATE SET".to_string());
+    for o in &mut out[on + 1..=update] {
+        *o = Some(String::new());
+    }
+    let mut k = update + 1;
+    while k < tokens.len() {
+        if tokens[k].is_keyword("values")
+            && let Some(open) = next_significant(tokens, k + 1).filter(|&p| tokens[p].is_punct('('))
+            && let Some(close) = matching_paren(tokens, open)
+        {
+            let inner: String = tokens[open + 1..close].iter().map(|t| t.text).collect();
+            out[k] = Some(format!("excluded.{}", inner.trim()));
+            for o in &mut out[k + 1..=close] {
+                *o = Some(String::new());
+            }
+            k = close;
+        }
+        k += 1;
+    }
+}
+
+/// 去掉 MySQL / Oracle 建表语句中 SQLite 不支持的部分：表选项、普通索引、列属性等
+fn rewrite_create_table(tokens: &[Token], out: &mut [Option<String>]) {
+    let Some(open) = tokens.iter().position(|t| t.is_punct('(')) else { return };
+    let Some(close) = matching_paren(tokens, open) else { return };
+
+    // 右括号之后的 ENGINE=... COMMENT='...' 等表选项
+    let end = (close + 1..tokens.len()).find(|&k| tokens[k].is_punct(';')).unwrap_or(tokens.len());
+    for k in close + 1..end {
+        if tokens[k].is_significant() {
+            out[k] = Some(String::new());
+        }
+    }
+
+    let list = ListSpan { open, close, items: split_top_level(tokens, open, close) };
+    let mut dropped_items = Vec::new();
+    for (pos, &(a, b)) in list.items.iter().enumerate() {
+        let Some(first) = next_significant(tokens, a).filter(|&f| f < b) else { continue };
+        let lead = tokens[first].text.to_ascii_lowercase();
+        if matches!(lead.as_str(), "key" | "index" | "fulltext" | "spatial") {
+            dropped_items.push(pos);
+            continue;
+        }
+        rewrite_definition(tokens, out, first, b);
+    }
+    let mut removed = vec![false; tokens.len()];
+    remove_items(tokens, &list, &dropped_items, &mut removed);
+    for (k, flag) in removed.iter().enumerate() {
+        if *flag {
+            out[k] = Some(String::new());
+        }
+    }
+}
+
+/// 改写一列定义或约束定义，范围为 [from, to)
+fn rewrite_definition(tokens: &[Token], out: &mut [Option<String>], from: usize, to: usize) {
+    let significant: Vec<usize> = (from..to).filter(|&k| tokens[k].is_significant()).collect();
+    let mut n = 0;
+    while n < significant.len() {
+        let k = significant[n];
+        let t = &tokens[k];
+        let next = significant.get(n + 1).copied();
+        let is_next = |kw: &str| next.is_some_and(|p| tokens[p].is_keyword(kw));
+        // 列属性只出现在列名与类型之后，列名本身可能就叫 comment
+        let attribute = n >= 2;
+
+        if attribute && DROPPED_ATTRIBUTES.iter().any(|a| t.is_keyword(a)) {
+            out[k] = Some(String::new());
+        } else if attribute
+            && (DROPPED_WITH_VALUE.iter().any(|a| t.is_keyword(a)) || (t.is_keyword("character") && is_next("set")))
+        {
+            // CHARACTER SET x 比 CHARSET x 多一个词
+            let span = if t.is_keyword("character") { 3 } else { 2 };
+            let mut last = k;
+            for &p in significant.iter().skip(n).take(span) {
+                out[p] = Some(String::new());
+                last = p;
+            }
+            // 等号形式：COMMENT = 'x'
+            if let Some(&p) = significant.get(n + span).filter(|_| tokens[significant[n + 1]].text == "=") {
+                out[p] = Some(String::new());
+                last = p;
+            }
+            n = significant.iter().position(|&p| p == last).unwrap_or(n);
+        } else if t.is_keyword("on")
+            && is_next("update")
+            && significant.get(n + 2).is_some_and(|&p| ["current_timestamp", "now", "localtimestamp"].iter().any(|w| tokens[p].is_keyword(w)))
+        {
+            // ON UPDATE CURRENT_TIMESTAMP[(n)]
+            let mut p = n;
+            let mut last = significant[n + 2];
+            if let Some(open) = significant.get(n + 3).filter(|&&o| tokens[o].is_punct('('))
+                && let Some(close) = matching_paren(tokens, *open)
+            {
+                last = close;
+            }
+            while p < significant.len() && significant[p] <= last {
+                out[significant[p]] = Some(String::new());
+                p += 1;
+            }
+            n = p;
+            continue;
+        } else if t.is_keyword("using") && (is_next("btree") || is_next("hash")) {
+            out[k] = Some(String::new());
+            out[next.unwrap()] = Some(String::new());
+            n += 1;
+        } else if (t.is_keyword("enum") || t.is_keyword("set")) && next.is_some_and(|p| tokens[p].is_punct('(')) {
+            // enum('a','b') 类型改为 TEXT
+            if let Some(close) = matching_paren(tokens, next.unwrap()) {
+                out[k] = Some("TEXT".to_string());
+                for o in &mut out[k + 1..=close] {
+                    *o = Some(String::new());
+                }
+                n = significant.iter().position(|&p| p == close).unwrap_or(n);
+            }
+        } else if (t.is_keyword("byte") || t.is_keyword("char"))
+            && next.is_some_and(|p| tokens[p].is_punct(')'))
+            && n > 0
+            && tokens[significant[n - 1]].kind == TokenKind::Number
+        {
+            // Oracle VARCHAR2(64 CHAR)
+            out[k] = Some(String::new());
+        } else if t.is_keyword("unique") && (is_next("key") || is_next("index")) {
+            // UNIQUE KEY uk_name (a, b) -> UNIQUE (a, b)
+            let key = next.unwrap();
+            out[key] = Some(String::new());
+            if let Some(&name) = significant.get(n + 2).filter(|&&p| !tokens[p].is_punct('(')) {
+                out[name] = Some(String::new());
+            }
+        }
+        n += 1;
+    }
+}
@@ -0,0 +1,357 @@
+use std::path::Path;
+use std::sync::LazyLock;
+
+use clap::ValueEnum;
+use regex::Regex;
+
+use super::dialect::Dialect;
+use super::insert::parse_inserts;
+use super::lexer::{matching_paren, next_significant, prev_significant, split_top_level, tokenize_with, Token, TokenKind};
+use super::stream::{rewrite_file, Output};
+
+/// 形如 2024-01-01、2024-01-01 08:30:00、2024-01-01 08:30:00.123 的日期时间字符串
+static DATETIME: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\d{4}-\d{2}-\d{2}(?: \d{2}:\d{2}:\d{2}(\.\d{1,9})?)?$").unwrap());
+
+/// Oracle / 达梦按大写保存的普通标识符
+static SIMPLE_IDENT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[A-Za-z_][A-Za-z0-9_$#]*$").unwrap());
+
+/// Oracle 保留字，作标识符时必须加引号
+const ORACLE_RESERVED: &[&str] = &[
+    "ACCESS", "ADD", "ALL", "ALTER", "AND", "ANY", "AS", "ASC", "AUDIT", "BETWEEN", "BY", "CHAR", "CHECK", "CLUSTER", "COLUMN",
+    "COMMENT", "COMPRESS", "CONNECT", "CREATE", "CURRENT", "DATE", "DECIMAL", "DEFAULT", "DELETE", "DESC", "DISTINCT", "DROP",
+    "ELSE", "EXCLUSIVE", "EXISTS", "FILE", "FLOAT", "FOR", "FROM", "GRANT", "GROUP", "HAVING", "IDENTIFIED", "IMMEDIATE", "IN",
+    "INCREMENT", "INDEX", "INITIAL", "INSERT", "INTEGER", "INTERSECT", "INTO", "IS", "LEVEL", "LIKE", "LOCK", "LONG",
+    "MAXEXTENTS", "MINUS", "MLSLABEL", "MODE", "MODIFY", "NOAUDIT", "NOCOMPRESS", "NOT", "NOWAIT", "NULL", "NUMBER", "OF",
+    "OFFLINE", "ON", "ONLINE", "OPTION", "OR", "ORDER", "PCTFREE", "PRIOR", "PUBLIC", "RAW", "RENAME", "RESOURCE", "REVOKE",
+    "ROW", "ROWID", "ROWNUM", "ROWS", "SELECT", "SESSION", "SET", "SHARE", "SIZE", "SMALLINT", "START", "SUCCESSFUL",
+    "SYNONYM", "SYSDATE", "TABLE", "THEN", "TO", "TRIGGER", "UID", "UNION", "UNIQUE", "UPDATE", "USER", "VALIDATE", "VALUES",
+    "VARCHAR", "VARCHAR2", "VIEW", "WHENEVER", "WHERE", "WITH",
+];
+
+/// Oracle 中可以直接还原为字符串的 TO_DATE / TO_TIMESTAMP 格式
+const ORACLE_DATE_FORMATS: &[&str] = &["YYYY-MM-DD", "YYYY-MM-DD HH24:MI:SS", "YYYY-MM-DD HH24:MI:SS.FF", "YYYY-MM-DD HH24:MI:SS.FF3"];
+
+/// 目标库不支持多行 VALUES 时的改写方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum MultiRowMode {
+    /// 拆分为多条单行 INSERT
+    Split,
+    /// 改写为 INSERT ALL ... SELECT 1 FROM DUAL
+    All,
+}
+
+/// 方言转换选项
+#[derive(Debug, Clone, Copy)]
+pub struct ConvertOptions {
+    pub from: Dialect,
+    pub to: Dialect,
+    pub multi_row: MultiRowMode,
+    /// 把 INSERT 中的日期时间字符串改写为目标库的日期字面量
+    pub date_literals: bool,
+}
+
+/// 转换结果
+#[derive(Debug, Default)]
+pub struct ConvertReport {
+    /// 被改写的语句数
+    pub statements: usize,
+    /// (行号, 说明) 形式的无法转换提示
+    pub warnings: Vec<(usize, String)>,
+}
+
+impl ConvertReport {
+    fn warn(&mut self, line: usize, message: impl Into<String>) {
+        let message = message.into();
+        if !self.warnings.iter().any(|(l, m)| *l == line && *m == message) {
+            self.warnings.push((line, message));
+        }
+    }
+}
+
+/// Oracle 与达梦：没有多行 VALUES、布尔字面量，空字符串等同于 NULL
+fn oracle_like(dialect: Dialect) -> bool {
+    matches!(dialect, Dialect::Oracle | Dialect::Dm)
+}
+
+/// 转换 SQL 文件到目标方言，按语句流式处理并按 output 写出结果
+pub fn convert_file(sql_path: &Path, output: &Output, options: &ConvertOptions) -> Result<ConvertReport, String> {
+    if options.from == options.to {
+        return Err("源方言与目标方言相同，无需转换".to_string());
+    }
+    let mut report = ConvertReport::default();
+    rewrite_file(sql_path, output, options.from, |statement| convert_sql(&statement.text, statement.line, options, &mut report))?;
+    Ok(report)
+}
+
+/// 转换一段 SQL 文本；line 为文本首行在文件中的行号，用于提示
+pub fn convert_sql(sql: &str, line: usize, options: &ConvertOptions, report: &mut ConvertReport) -> String {
+    let tokens = tokenize_with(sql, &options.from.lexer_config());
+    let at = |k: usize| line + tokens[k].line - 1;
+    let mut out: Vec<Option<String>> = vec![None; tokens.len()];
+
+    check_statement(&tokens, options, report, line);
+
+    // 逐个 token 转换引号、转义、布尔值与注释
+    for (k, token) in tokens.iter().enumerate() {
+        out[k] = convert_token(token, options, report, at(k));
+    }
+
+    convert_functions(&tokens, &mut out, options, report, &at);
+    if options.date_literals && oracle_like(options.to) && !oracle_like(options.from) {
+        convert_date_values(&tokens, &mut out, options);
+    }
+    restructure_inserts(&tokens, &mut out, options);
+
+    let changed = out.iter().zip(&tokens).any(|(o, t)| o.as_deref().is_some_and(|s| s != t.text));
+    if changed {
+        report.statements += 1;
+    }
+    tokens
+        .iter()
+        .zip(out)
+        .map(|(t, o)| o.unwrap_or_else(|| t.text.to_string()))
+        .collect()
+}
+
+/// 检查目标库无法直接执行的语句类型
+fn check_statement(tokens: &[Token], options: &ConvertOptions, report: &mut ConvertReport, line: usize) {
+    let Some(first) = next_significant(tokens, 0) else { return };
+    let line = line + tokens[first].line - 1;
+    let word = tokens[first].text.to_ascii_lowercase();
+    let second = next_significant(tokens, first + 1).map(|k| tokens[k].text.to_ascii_lowercase()).unwrap_or_default();
+
+    match word.as_str() {
+        "lock" | "unlock" => report.warn(line, "LOCK/UNLOCK TABLES 为 MySQL 专有语句，未转换"),
+        "set" => report.warn(line, "SET 会话设置语句未转换"),
+        "replace" => report.warn(line, "REPLACE INTO 无法直接转换，请改用 upsert"),
+        "create" | "alter" | "drop" => report.warn(line, "DDL 语句只转换了引号与字面量，数据类型等需人工检查"),
+        "begin" | "declare" if oracle_like(options.from) => {
+            report.warn(line, "PL/SQL 块无法自动转换")
+        }
+        "insert" if second == "ignore" => report.warn(line, "INSERT IGNORE 无法直接转换，已保留 IGNORE"),
+        _ => {}
+    }
+    if word == "create" && is_block_header(tokens) && oracle_like(options.from) {
+        report.warn(line, "存储过程/函数/触发器无法自动转换");
+    }
+
+    for (k, t) in tokens.iter().enumerate() {
+        if t.is_keyword("duplicate") && next_significant(tokens, k + 1).is_some_and(|n| tokens[n].is_keyword("key")) {
+            report.warn(line + t.line - 1, "ON DUPLICATE KEY UPDATE 无法直接转换");
+        }
+        if t.kind == TokenKind::Operator && t.text == "::" && options.from == Dialect::Postgres {
+            report.warn(line + t.line - 1, "PostgreSQL 的 :: 类型转换未转换");
+        }
+        if t.kind == TokenKind::BlockComment && t.text.starts_with("/*!") {
+            report.warn(line + t.line - 1, "MySQL 条件注释 /*! */ 会被目标库忽略");
+        }
+    }
+}
+
+fn is_block_header(tokens: &[Token]) -> bool {
+    tokens
+        .iter()
+        .filter(|t| t.kind == TokenKind::Word)
+        .take(5)
+        .any(|t| ["procedure", "function", "trigger", "package"].iter().any(|w| t.is_keyword(w)))
+}
+
+fn convert_token(token: &Token, options: &ConvertOptions, report: &mut ConvertReport, line: usize) -> Option<String> {
+    match token.kind {
+        TokenKind::QuotedIdent => Some(convert_ident(&token.ident_name(), options.to)),
+        TokenKind::String => match options.from.unquote_string(token) {
+            Some(value) => {
+                if value.is_empty() && oracle_like(options.to) {
+                    report.warn(line, "空字符串在 Oracle/达梦 中等同于 NULL");
+                }
+                let national = token.text.get(..2).is_some_and(|p| p.eq_ignore_ascii_case("n'"));
+                let quoted = options.to.quote_string(&value);
+                Some(if national { format!("N{}", quoted) } else { quoted })
+            }
+            None => {
+                report.warn(line, format!("无法转换的字面量 {}", token.text));
+                None
+            }
+        },
+        TokenKind::Word if oracle_like(options.to) && token.is_keyword("true") => Some("1".to_string()),
+        TokenKind::Word if oracle_like(options.to) && token.is_keyword("false") => Some("0".to_string()),
+        TokenKind::LineComment if token.text.starts_with('#') && options.to != Dialect::Mysql => {
+            Some(format!("--{}", &token.text[1..]))
+        }
+        _ => None,
+    }
+}
+
+/// 转换带引号的标识符。Oracle / 达梦中未加引号的标识符按大写保存，
+/// 普通标识符转为大写，只在以下划线开头或为保留字时保留引号，其余原样加引号以保留大小写
+fn convert_ident(name: &str, to: Dialect) -> String {
+    if !oracle_like(to) || !SIMPLE_IDENT.is_match(name) {
+        return to.quote_ident(name);
+    }
+    let upper = name.to_ascii_uppercase();
+    if upper.starts_with('_') || ORACLE_RESERVED.contains(&upper.as_str()) {
+        to.quote_ident(&upper)
+    } else {
+        upper
+    }
+}
+
+/// 转换常用的日期与空值函数
+fn convert_functions(tokens: &[Token], out: &mut [Option<String>], options: &ConvertOptions, report: &mut ConvertReport, at: &dyn Fn(usize) -> usize) {
+    let to_oracle = oracle_like(options.to);
+    for k in 0..tokens.len() {
+        let t = &tokens[k];
+        if t.kind != TokenKind::Word {
+            continue;
+        }
+        let call = next_significant(tokens, k + 1).filter(|&p| tokens[p].is_punct('('));
+        let empty_call = call.and_then(|open| {
+            let close = next_significant(tokens, open + 1)?;
+            tokens[close].is_punct(')').then_some((open, close))
+        });
+        // 跳过限定名中的标识符，例如 t.now
+        if k > 0 && prev_significant(tokens, k - 1).is_some_and(|p| tokens[p].is_punct('.')) {
+            continue;
+        }
+
+        match t.text.to_ascii_lowercase().as_str() {
+            "now" if to_oracle => {
+                if let Some((open, close)) = empty_call {
+                    out[k] = Some("SYSDATE".to_string());
+                    blank(out, open, close);
+                }
+            }
+            "sysdate" | "systimestamp" if !to_oracle && call.is_none() => {
+                out[k] = Some(if options.to == Dialect::Mysql { "NOW()" } else { "CURRENT_TIMESTAMP" }.to_string());
+            }
+            "ifnull" if to_oracle && call.is_some() => out[k] = Some("NVL".to_string()),
+            "ifnull" if options.to == Dialect::Postgres && call.is_some() => out[k] = Some("COALESCE".to_string()),
+            "nvl" if !to_oracle && call.is_some() => {
+                out[k] = Some(if options.to == Dialect::Mysql { "IFNULL" } else { "COALESCE" }.to_string());
+            }
+            "to_date" | "to_timestamp" if !to_oracle => {
+                let Some(open) = call else { continue };
+                let Some(close) = matching_paren(tokens, open) else { continue };
+                let args = split_top_level(tokens, open, close);
+                let literal = args.first().and_then(|&(a, b)| single_string(tokens, a, b));
+                let format = args.get(1).and_then(|&(a, b)| single_string(tokens, a, b)).and_then(|f| options.from.unquote_string(&tokens[f]));
+                match (literal, format) {
+                    (Some(lit), Some(fmt)) if args.len() == 2 && ORACLE_DATE_FORMATS.iter().any(|f| f.eq_ignore_ascii_case(&fmt)) => {
+                        out[k] = out[lit].clone().or_else(|| Some(tokens[lit].text.to_string()));
+                        blank(out, k + 1, close);
+                    }
+                    _ => report.warn(at(k), format!("{} 的格式无法自动转换", t.text.to_uppercase())),
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 列表项若为单个字符串字面量，返回其 token 下标
+fn single_string(tokens: &[Token], start: usize, end: usize) -> Option<usize> {
+    let mut significant = (start..end).filter(|&k| tokens[k].is_significant());
+    match (significant.next(), significant.next()) {
+        (Some(k), None) if tokens[k].kind == TokenKind::String => Some(k),
+        _ => None,
+    }
+}
+
+fn blank(out: &mut [Option<String>], from: usize, to: usize) {
+    for o in &mut out[from..=to] {
+        *o = Some(String::new());
+    }
+}
+
+/// INSERT 值中的日期时间字符串改写为 Oracle 的 DATE / TO_DATE / TO_TIMESTAMP
+fn convert_date_values(tokens: &[Token], out: &mut [Option<String>], options: &ConvertOptions) {
+    for k in 0..tokens.len() {
+        if !tokens[k].is_keyword("insert") {
+            continue;
+        }
+        for insert in parse_inserts(tokens, k, options.from) {
+            for row in &insert.rows {
+                for &(a, b) in &row.items {
+                    let Some(s) = single_string(tokens, a, b) else { continue };
+                    let Some(value) = options.from.unquote_string(&tokens[s]) else { continue };
+                    if let Some(literal) = oracle_datetime(&value, options.to) {
+                        out[s] = Some(literal);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 日期时间字符串对应的 Oracle 字面量：DATE '...'、TO_DATE(...) 或 TO_TIMESTAMP(...)；
+/// 不是日期时间格式时返回 None
+pub(crate) fn oracle_datetime(value: &str, dialect: Dialect) -> Option<String> {
+    let caps = DATETIME.captures(value)?;
+    let quoted = dialect.quote_string(value);
+    Some(if value.len() == 10 {
+        format!("DATE {}", quoted)
+    } else if caps.get(1).is_some() {
+        format!("TO_TIMESTAMP({}, 'YYYY-MM-DD HH24:MI:SS.FF')", quoted)
+    } else {
+        format!("TO_DATE({}, 'YYYY-MM-DD HH24:MI:SS')", quoted)
+    })
+}
+
+fn range_text(tokens: &[Token], out: &[Option<String>], from: usize, to: usize) -> String {
+    (from..to).map(|k| out[k].clone().unwrap_or_else(|| tokens[k].text.to_string())).collect()
+}
+
+/// 改写多行 VALUES（目标为 Oracle/达梦）以及 INSERT ALL（目标为其他库）
+fn restructure_inserts(tokens: &[Token], out: &mut [Option<String>], options: &ConvertOptions) {
+    let mut k = 0;
+    while k < tokens.len() {
+        if !tokens[k].is_keyword("insert") {
+            k += 1;
+            continue;
+        }
+        let spans = parse_inserts(tokens, k, options.from);
+        let Some(last) = spans.last() else {
+            k += 1;
+            continue;
+        };
+        let next = last.end;
+
+        if spans[0].all && !oracle_like(options.to) {
+            // INSERT ALL -> 多条单表 INSERT；删除结尾的 SELECT ... FROM DUAL
+            let stmt_end = (last.end..tokens.len()).find(|&p| tokens[p].is_punct(';')).unwrap_or(tokens.len());
+            let stmt_last = prev_significant(tokens, stmt_end.saturating_sub(1)).filter(|&p| p >= last.end).unwrap_or(last.end - 1);
+            let statements: Vec<String> = spans
+                .iter()
+                .map(|s| format!("INSERT INTO {}", range_text(tokens, out, s.table.0, s.end)))
+                .collect();
+            for o in &mut out[k..=stmt_last] {
+                *o = Some(String::new());
+            }
+            out[k] = Some(statements.join(";\n"));
+            k = stmt_end;
+            continue;
+        }
+
+        let span = &spans[0];
+        if spans.len() == 1 && !span.all && span.rows.len() > 1 && oracle_like(options.to) {
+            let target = range_text(tokens, out, span.table.0, span.values + 1);
+            let rows: Vec<String> = span.rows.iter().map(|r| range_text(tokens, out, r.open, r.close + 1)).collect();
+            let text = match options.multi_row {
+                MultiRowMode::Split => {
+                    let head = range_text(tokens, out, span.insert, span.values + 1);
+                    rows.iter().map(|r| format!("{} {}", head, r)).collect::<Vec<_>>().join(";\n")
+                }
+                MultiRowMode::All => {
+                    let clauses: Vec<String> = rows.iter().map(|r| format!("  INTO {} {}", target, r)).collect();
+                    format!("INSERT ALL\n{}\nSELECT 1 FROM DUAL", clauses.join("\n"))
+                }
+            };
+            for o in &mut out[span.insert..span.end] {
+                *o = Some(String::new());
+            }
+            out[span.insert] = Some(text);
+        }
+        k = next;
+    }
+}
@@ -0,0 +1,110 @@
+use std::io::BufRead;
+
+/// 一个 CSV 单元格：内容及原文是否带引号
+pub type CsvField = (String, bool);
+
+/// 逐行读取 CSV 记录（RFC 4180），支持引号内的逗号、换行与 `""` 转义，并去掉开头的 UTF-8 BOM
+pub struct CsvReader<R> {
+    reader: R,
+    line: usize,
+    first: bool,
+}
+
+impl<R: BufRead> CsvReader<R> {
+    pub fn new(reader: R) -> Self {
+        CsvReader { reader, line: 0, first: true }
+    }
+
+    /// 最近一条记录结束处的行号
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    fn read_line(&mut self, buf: &mut String) -> Result<bool, String> {
+        let n = self.reader.read_line(buf).map_err(|e| format!("读取文件失败: {}", e))?;
+        if n > 0 {
+            self.line += 1;
+        }
+        if self.first {
+            self.first = false;
+            if buf.starts_with('\u{feff}') {
+                buf.drain(..'\u{feff}'.len_utf8());
+            }
+        }
+        Ok(n > 0)
+    }
+}
+
+impl<R: BufRead> Iterator for CsvReader<R> {
+    type Item = Result<Vec<CsvField>, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut buf = String::new();
+        match self.read_line(&mut buf) {
+            Ok(true) => {}
+            Ok(false) => return None,
+            Err(e) => return Some(Err(e)),
+        }
+
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut quoted = false;
+        let mut in_quotes = false;
+        let mut pos = 0;
+        loop {
+            let Some(ch) = buf[pos..].chars().next() else {
+                if !in_quotes {
+                    break;
+                }
+                // 引号内的换行：继续读下一行
+                match self.read_line(&mut buf) {
+                    Ok(true) => continue,
+                    Ok(false) => return Some(Err(format!("第 {} 行: 引号未闭合", self.line))),
+                    Err(e) => return Some(Err(e)),
+                }
+            };
+            pos += ch.len_utf8();
+            if in_quotes {
+                if ch == '"' {
+                    if buf[pos..].starts_with('"') {
+                        field.push('"');
+                        pos += 1;
+                    } else {
+                        in_quotes = false;
+                    }
+                } else {
+                    field.push(ch);
+                }
+                continue;
+            }
+            match ch {
+                '"' if field.is_empty() && !quoted => {
+                    quoted = true;
+                    in_quotes = true;
+                }
+                ',' => fields.push((std::mem::take(&mut field), std::mem::replace(&mut quoted, false))),
+                '\n' => break,
+                '\r' if buf[pos..].starts_with('\n') || pos == buf.len() => {}
+                _ => field.push(ch),
+            }
+        }
+        fields.push((field, quoted));
+        Some(Ok(fields))
+    }
+}
+
+/// 拼接一行 CSV，单元格需已完成引号处理
+pub fn csv_line(fields: &[String]) -> String {
+    let mut line = fields.join(",");
+    line.push('\n');
+    line
+}
+
+/// 列名等普通文本的 CSV 单元格
+pub fn csv_text(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
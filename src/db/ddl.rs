@@ -0,0 +1,445 @@
+use std::io::Cursor;
+use std::path::Path;
+
+use super::dialect::Dialect;
+use super::lexer::{matching_paren, next_significant, split_top_level, tokenize_with, Token, TokenKind};
+use super::stream::{open_statements, RawStatement, StatementReader};
+
+/// 列类型之后的子句关键字，类型到此为止
+const COLUMN_CLAUSES: &[&str] = &[
+    "not", "null", "default", "primary", "unique", "comment", "auto_increment", "constraint", "check", "references",
+    "generated", "on", "identity", "enable", "disable",
+];
+/// 单独解析的列属性，其余属性原样保留在 extra 中
+const COLUMN_ATTRIBUTES: &[&str] = &["not", "null", "default", "primary", "unique", "comment", "constraint"];
+/// 表级约束的起始关键字
+const TABLE_CONSTRAINTS: &[&str] =
+    &["constraint", "primary", "unique", "key", "index", "foreign", "check", "fulltext", "spatial", "exclude"];
+
+/// 列定义
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnDef {
+    /// 去掉引号的列名
+    pub name: String,
+    /// 列名原文
+    pub text: String,
+    /// 规范化的类型，例如 `VARCHAR(64)`
+    pub data_type: String,
+    pub nullable: bool,
+    pub default: Option<String>,
+    /// 其余属性，例如 `AUTO_INCREMENT`、`ON UPDATE CURRENT_TIMESTAMP`
+    pub extra: String,
+    pub comment: Option<String>,
+}
+
+/// 索引或唯一约束，名称与列均为原文
+#[derive(Debug, Clone)]
+pub struct IndexDef {
+    pub name: Option<String>,
+    pub columns: Vec<String>,
+    pub unique: bool,
+    /// 以 `CONSTRAINT name UNIQUE` 定义，删除时需要 DROP CONSTRAINT
+    pub constraint: bool,
+    /// 由单独的 CREATE INDEX / ALTER TABLE 语句定义，不在 CREATE TABLE 中
+    pub standalone: bool,
+}
+
+impl IndexDef {
+    /// 是否为同一个索引：有名称时按名称，否则按列
+    pub fn same_index(&self, other: &IndexDef) -> bool {
+        match (&self.name, &other.name) {
+            (Some(a), Some(b)) => ident_key(a) == ident_key(b),
+            (None, None) => self.same_definition(other),
+            _ => false,
+        }
+    }
+
+    /// 列与唯一性是否相同
+    pub fn same_definition(&self, other: &IndexDef) -> bool {
+        self.unique == other.unique && same_columns(&self.columns, &other.columns)
+    }
+}
+
+/// 主键，名称与列均为原文
+#[derive(Debug, Clone)]
+pub struct PrimaryKey {
+    pub name: Option<String>,
+    pub columns: Vec<String>,
+    pub standalone: bool,
+}
+
+/// 表定义
+#[derive(Debug, Clone)]
+pub struct TableDef {
+    /// 去掉引号的表名，保留 schema 前缀
+    pub name: String,
+    /// 表名原文
+    pub text: String,
+    pub columns: Vec<ColumnDef>,
+    pub primary_key: Option<PrimaryKey>,
+    pub indexes: Vec<IndexDef>,
+    pub comment: Option<String>,
+    /// CREATE TABLE 语句原文，不含末尾分号
+    pub ddl: String,
+}
+
+impl TableDef {
+    pub fn column(&self, name: &str) -> Option<&ColumnDef> {
+        self.columns.iter().find(|c| c.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// 从 DDL 脚本中解析出的表结构
+#[derive(Debug, Default)]
+pub struct Schema {
+    pub tables: Vec<TableDef>,
+    /// (行号, 说明) 形式的未处理语句
+    pub warnings: Vec<(usize, String)>,
+}
+
+impl Schema {
+    pub fn table(&self, name: &str) -> Option<&TableDef> {
+        self.tables.iter().find(|t| t.name.eq_ignore_ascii_case(name))
+    }
+
+    fn table_mut(&mut self, name: &str) -> Option<&mut TableDef> {
+        self.tables.iter_mut().find(|t| t.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// 解析 DDL 文件：CREATE TABLE、CREATE INDEX、ALTER TABLE ADD 主键/唯一约束/索引、COMMENT ON
+pub fn parse_schema_file(path: &Path, dialect: Dialect) -> Result<Schema, String> {
+    let (reader, _) = open_statements(path, dialect)?;
+    let mut schema = Schema::default();
+    for statement in reader {
+        schema.statement(&statement?, dialect);
+    }
+    Ok(schema)
+}
+
+/// 解析 DDL 文本
+pub fn parse_schema(sql: &str, dialect: Dialect) -> Schema {
+    let mut schema = Schema::default();
+    for statement in StatementReader::new(Cursor::new(sql), dialect).flatten() {
+        schema.statement(&statement, dialect);
+    }
+    schema
+}
+
+impl Schema {
+    fn statement(&mut self, statement: &RawStatement, dialect: Dialect) {
+        let tokens = tokenize_with(&statement.text, &dialect.lexer_config());
+        let words = leading_words(&tokens, 6);
+        let Some(first) = next_significant(&tokens, 0) else { return };
+        let line = statement.line + tokens[first].line - 1;
+        let word = |n: usize| words.get(n).map(|(_, w)| w.as_str()).unwrap_or("");
+
+        let result = match (word(0), word(1)) {
+            ("create", _) => {
+                let kind = words[1..].iter().find(|(_, w)| !matches!(w.as_str(), "or" | "replace" | "global" | "temporary" | "unique"));
+                match kind {
+                    Some((k, w)) if w == "table" => self.create_table(&tokens, *k, first, dialect),
+                    Some((k, w)) if w == "index" => {
+                        let unique = words.iter().any(|(_, w)| w == "unique");
+                        self.create_index(&tokens, *k, unique)
+                    }
+                    _ => Ok(()),
+                }
+            }
+            ("alter", "table") => self.alter_table(&tokens, words[1].0),
+            ("comment", "on") => self.comment_on(&tokens, words[1].0, dialect),
+            _ => Ok(()),
+        };
+        if let Err(message) = result {
+            self.warnings.push((line, message));
+        }
+    }
+
+    fn create_table(&mut self, tokens: &[Token], table_kw: usize, first: usize, dialect: Dialect) -> Result<(), String> {
+        let mut k = skip_words(tokens, table_kw + 1, &["if", "not", "exists"]).ok_or("CREATE TABLE 缺少表名")?;
+        let (name, text, next) = name_at(tokens, k).ok_or("CREATE TABLE 缺少表名")?;
+        k = next_significant(tokens, next).filter(|&p| tokens[p].is_punct('(')).ok_or_else(|| format!("{}: 暂不支持 CREATE TABLE ... AS SELECT", name))?;
+        let close = matching_paren(tokens, k).ok_or_else(|| format!("{}: 括号不匹配", name))?;
+
+        let end = (0..tokens.len()).rev().find(|&p| tokens[p].is_significant() && !tokens[p].is_punct(';')).map_or(tokens.len(), |p| p + 1);
+        let mut table = TableDef {
+            name: name.clone(),
+            text,
+            columns: Vec::new(),
+            primary_key: None,
+            indexes: Vec::new(),
+            comment: None,
+            ddl: tokens[first..end].iter().map(|t| t.text).collect(),
+        };
+
+        for (a, b) in split_top_level(tokens, k, close) {
+            let Some(f) = next_significant(tokens, a).filter(|&f| f < b) else { continue };
+            if TABLE_CONSTRAINTS.iter().any(|kw| tokens[f].is_keyword(kw)) {
+                table_constraint(tokens, f, b, &mut table, false);
+            } else {
+                let (column, primary, unique) = parse_column(tokens, f, b, dialect);
+                if primary {
+                    table.primary_key = Some(PrimaryKey { name: None, columns: vec![column.text.clone()], standalone: false });
+                }
+                if unique {
+                    let index = IndexDef { name: None, columns: vec![column.text.clone()], unique: true, constraint: false, standalone: false };
+                    table.indexes.push(index);
+                }
+                table.columns.push(column);
+            }
+        }
+
+        // MySQL 表选项中的 COMMENT='...'
+        let mut p = close + 1;
+        while let Some(q) = next_significant(tokens, p) {
+            if tokens[q].is_keyword("comment") {
+                let value = next_significant(tokens, q + 1).and_then(|v| if tokens[v].text == "=" { next_significant(tokens, v + 1) } else { Some(v) });
+                table.comment = value.and_then(|v| dialect.unquote_string(&tokens[v]));
+            }
+            p = q + 1;
+        }
+
+        match self.table_mut(&name) {
+            Some(existing) => *existing = table,
+            None => self.tables.push(table),
+        }
+        Ok(())
+    }
+
+    fn create_index(&mut self, tokens: &[Token], index_kw: usize, unique: bool) -> Result<(), String> {
+        let k = skip_words(tokens, index_kw + 1, &["concurrently", "if", "not", "exists"]).ok_or("CREATE INDEX 缺少索引名")?;
+        let (_, name_text, next) = name_at(tokens, k).ok_or("CREATE INDEX 缺少索引名")?;
+        let on = next_significant(tokens, next).filter(|&p| tokens[p].is_keyword("on")).ok_or("CREATE INDEX 缺少 ON")?;
+        let (table, _, next) = name_at(tokens, next_significant(tokens, on + 1).ok_or("CREATE INDEX 缺少表名")?).ok_or("CREATE INDEX 缺少表名")?;
+        let columns = column_list(tokens, next).ok_or_else(|| format!("{}: 无法解析索引列", name_text))?;
+
+        let target = self.table_mut(&table).ok_or_else(|| format!("索引 {} 所在的表 {} 未定义", name_text, table))?;
+        target.indexes.push(IndexDef { name: Some(name_text), columns, unique, constraint: false, standalone: true });
+        Ok(())
+    }
+
+    fn alter_table(&mut self, tokens: &[Token], table_kw: usize) -> Result<(), String> {
+        let k = skip_words(tokens, table_kw + 1, &["only", "if", "exists"]).ok_or("ALTER TABLE 缺少表名")?;
+        let (table, _, next) = name_at(tokens, k).ok_or("ALTER TABLE 缺少表名")?;
+        let action = next_significant(tokens, next).filter(|&p| tokens[p].is_keyword("add"));
+        let start = action.and_then(|a| next_significant(tokens, a + 1));
+        let Some(start) = start.filter(|&s| TABLE_CONSTRAINTS.iter().any(|kw| tokens[s].is_keyword(kw))) else {
+            return Err(format!("{}: 暂不处理该 ALTER TABLE 语句", table));
+        };
+        let target = self.table_mut(&table).ok_or_else(|| format!("ALTER TABLE 的表 {} 未定义", table))?;
+        table_constraint(tokens, start, tokens.len(), target, true);
+        Ok(())
+    }
+
+    fn comment_on(&mut self, tokens: &[Token], on: usize, dialect: Dialect) -> Result<(), String> {
+        let kind = next_significant(tokens, on + 1).ok_or("COMMENT ON 缺少对象")?;
+        let target = next_significant(tokens, kind + 1).ok_or("COMMENT ON 缺少对象")?;
+        let parts = name_parts(tokens, target);
+        let is = next_significant(tokens, parts.last().map_or(target, |&p| p + 1)).filter(|&p| tokens[p].is_keyword("is"));
+        let value = is.and_then(|p| next_significant(tokens, p + 1)).ok_or("COMMENT ON 缺少 IS")?;
+        let comment = dialect.unquote_string(&tokens[value]).filter(|c| !c.is_empty());
+        let names: Vec<String> = parts.iter().map(|&p| tokens[p].ident_name()).collect();
+
+        if tokens[kind].is_keyword("table") {
+            let name = names.join(".");
+            self.table_mut(&name).ok_or_else(|| format!("注释所在的表 {} 未定义", name))?.comment = comment;
+        } else if tokens[kind].is_keyword("column") && names.len() >= 2 {
+            let table = names[..names.len() - 1].join(".");
+            let column = &names[names.len() - 1];
+            let target = self.table_mut(&table).ok_or_else(|| format!("注释所在的表 {} 未定义", table))?;
+            let col = target.columns.iter_mut().find(|c| c.name.eq_ignore_ascii_case(column));
+            col.ok_or_else(|| format!("注释所在的列 {}.{} 未定义", table, column))?.comment = comment;
+        }
+        Ok(())
+    }
+}
+
+/// 解析列定义，返回列及其是否带有内联的 PRIMARY KEY、UNIQUE
+fn parse_column(tokens: &[Token], f: usize, end: usize, dialect: Dialect) -> (ColumnDef, bool, bool) {
+    let mut column = ColumnDef {
+        name: tokens[f].ident_name(),
+        text: tokens[f].text.to_string(),
+        data_type: String::new(),
+        nullable: true,
+        default: None,
+        extra: String::new(),
+        comment: None,
+    };
+    let (mut primary, mut unique) = (false, false);
+
+    let type_end = clause_end(tokens, f + 1, end, COLUMN_CLAUSES);
+    column.data_type = format_tokens(&tokens[f + 1..type_end], true);
+
+    let mut extra = Vec::new();
+    let mut k = type_end;
+    while let Some(p) = next_significant(tokens, k).filter(|&p| p < end) {
+        let t = &tokens[p];
+        let after = |n: usize| next_significant(tokens, n + 1).filter(|&q| q < end);
+        k = if t.is_keyword("not") && after(p).is_some_and(|q| tokens[q].is_keyword("null")) {
+            column.nullable = false;
+            after(p).unwrap() + 1
+        } else if t.is_keyword("null") {
+            column.nullable = true;
+            p + 1
+        } else if t.is_keyword("default") {
+            let start = after(p).unwrap_or(end);
+            let stop = term_end(tokens, start, end);
+            column.default = Some(format_tokens(&tokens[start..stop], true));
+            stop
+        } else if t.is_keyword("primary") || t.is_keyword("unique") {
+            primary |= t.is_keyword("primary");
+            unique |= t.is_keyword("unique");
+            after(p).filter(|&q| tokens[q].is_keyword("key")).map_or(p + 1, |q| q + 1)
+        } else if t.is_keyword("comment") {
+            column.comment = after(p).and_then(|q| dialect.unquote_string(&tokens[q]));
+            after(p).map_or(end, |q| q + 1)
+        } else if t.is_keyword("constraint") {
+            // 列级约束名不参与比较
+            after(p).map_or(end, |q| q + 1)
+        } else {
+            let stop = clause_end(tokens, p + 1, end, COLUMN_ATTRIBUTES);
+            extra.push(format_tokens(&tokens[p..stop], true));
+            stop
+        };
+    }
+    column.extra = extra.join(" ");
+    (column, primary, unique)
+}
+
+/// 解析表级约束：主键、唯一约束、普通索引。外键与检查约束不参与比较
+fn table_constraint(tokens: &[Token], f: usize, end: usize, table: &mut TableDef, standalone: bool) {
+    let mut k = f;
+    let mut constraint_name = None;
+    if tokens[k].is_keyword("constraint") {
+        let Some(n) = next_significant(tokens, k + 1) else { return };
+        constraint_name = Some(tokens[n].text.to_string());
+        let Some(next) = next_significant(tokens, n + 1) else { return };
+        k = next;
+    }
+    let t = &tokens[k];
+    let Some(open) = (k..end).find(|&p| tokens[p].is_punct('(')) else { return };
+    let Some(columns) = column_list(tokens, open) else { return };
+    // UNIQUE KEY name (...)、KEY name (...) 中的索引名
+    let index_name = (k + 1..open)
+        .filter(|&p| tokens[p].is_significant())
+        .find(|&p| !["key", "index", "using", "btree", "hash"].iter().any(|kw| tokens[p].is_keyword(kw)))
+        .map(|p| tokens[p].text.to_string());
+
+    if t.is_keyword("primary") {
+        table.primary_key = Some(PrimaryKey { name: constraint_name, columns, standalone });
+    } else if t.is_keyword("unique") || t.is_keyword("key") || t.is_keyword("index") {
+        let unique = t.is_keyword("unique");
+        let constraint = constraint_name.is_some();
+        let index = IndexDef { name: constraint_name.or(index_name), columns, unique, constraint, standalone };
+        table.indexes.retain(|i| !i.same_index(&index));
+        table.indexes.push(index);
+    }
+}
+
+/// 从 k 开始查找括号列表，返回各项原文
+fn column_list(tokens: &[Token], k: usize) -> Option<Vec<String>> {
+    let open = (k..tokens.len()).find(|&p| tokens[p].is_punct('('))?;
+    let close = matching_paren(tokens, open)?;
+    Some(split_top_level(tokens, open, close).into_iter().map(|(a, b)| format_tokens(&tokens[a..b], false)).collect())
+}
+
+/// 语句开头的 n 个单词（小写）及其下标
+pub(crate) fn leading_words(tokens: &[Token], n: usize) -> Vec<(usize, String)> {
+    let mut words = Vec::new();
+    let mut k = 0;
+    while words.len() < n {
+        let Some(p) = next_significant(tokens, k) else { break };
+        words.push((p, tokens[p].text.to_ascii_lowercase()));
+        k = p + 1;
+    }
+    words
+}
+
+/// 从 k 开始跳过指定关键字，返回之后第一个有效 token
+pub(crate) fn skip_words(tokens: &[Token], k: usize, words: &[&str]) -> Option<usize> {
+    let mut p = next_significant(tokens, k)?;
+    while words.iter().any(|w| tokens[p].is_keyword(w)) {
+        p = next_significant(tokens, p + 1)?;
+    }
+    Some(p)
+}
+
+/// 从 k 开始的 `a.b.c` 限定名中各个标识符的下标
+pub(crate) fn name_parts(tokens: &[Token], k: usize) -> Vec<usize> {
+    let mut parts = Vec::new();
+    let mut p = Some(k);
+    while let Some(i) = p.filter(|&i| matches!(tokens[i].kind, TokenKind::Word | TokenKind::QuotedIdent)) {
+        parts.push(i);
+        p = next_significant(tokens, i + 1).filter(|&d| tokens[d].is_punct('.')).and_then(|d| next_significant(tokens, d + 1));
+    }
+    parts
+}
+
+/// 限定名：(去掉引号的名称, 原文, 之后的下标)
+pub(crate) fn name_at(tokens: &[Token], k: usize) -> Option<(String, String, usize)> {
+    let parts = name_parts(tokens, k);
+    let last = *parts.last()?;
+    let name = parts.iter().map(|&p| tokens[p].ident_name()).collect::<Vec<_>>().join(".");
+    let text = tokens[k..=last].iter().map(|t| t.text).collect();
+    Some((name, text, last + 1))
+}
+
+/// 从 k 开始到下一个顶层子句关键字之前的位置
+fn clause_end(tokens: &[Token], k: usize, end: usize, clauses: &[&str]) -> usize {
+    let mut depth = 0usize;
+    for (p, t) in tokens.iter().enumerate().take(end).skip(k) {
+        if t.is_punct('(') {
+            depth += 1;
+        } else if t.is_punct(')') {
+            depth = depth.saturating_sub(1);
+        } else if depth == 0 && t.kind == TokenKind::Word && clauses.iter().any(|c| t.is_keyword(c)) {
+            return p;
+        }
+    }
+    end
+}
+
+/// DEFAULT 之后的一个值：可带符号、函数调用参数，以及 PostgreSQL 的 `::类型` 转换
+fn term_end(tokens: &[Token], start: usize, end: usize) -> usize {
+    let Some(mut p) = next_significant(tokens, start).filter(|&p| p < end) else { return end };
+    if matches!(tokens[p].text, "-" | "+") {
+        p = next_significant(tokens, p + 1).filter(|&q| q < end).unwrap_or(p);
+    }
+    let mut stop = if tokens[p].is_punct('(') { matching_paren(tokens, p).map_or(end, |c| c + 1) } else { p + 1 };
+    if let Some(open) = next_significant(tokens, stop).filter(|&q| q < end && tokens[q].is_punct('(')) {
+        stop = matching_paren(tokens, open).map_or(end, |c| c + 1);
+    }
+    if next_significant(tokens, stop).is_some_and(|q| q < end && tokens[q].text.starts_with(':')) {
+        stop = clause_end(tokens, stop, end, COLUMN_CLAUSES);
+    }
+    stop
+}
+
+/// 规范化 token 序列：去掉注释与多余空白，只在相邻的单词之间保留一个空格；upper 为真时单词转为大写
+pub fn format_tokens(tokens: &[Token], upper: bool) -> String {
+    let mut out = String::new();
+    let mut last_word = false;
+    for t in tokens.iter().filter(|t| t.is_significant()) {
+        let word = matches!(t.kind, TokenKind::Word | TokenKind::QuotedIdent | TokenKind::String | TokenKind::Number);
+        if word && last_word {
+            out.push(' ');
+        }
+        if upper && t.kind == TokenKind::Word {
+            out.push_str(&t.text.to_ascii_uppercase());
+        } else {
+            out.push_str(t.text);
+        }
+        last_word = word;
+    }
+    out
+}
+
+/// 比较用的标识符：去掉引号并转为小写
+pub fn ident_key(text: &str) -> String {
+    text.chars().filter(|c| !matches!(c, '`' | '"')).collect::<String>().to_ascii_lowercase()
+}
+
+/// 列列表是否相同（不区分引号与大小写）
+pub fn same_columns(a: &[String], b: &[String]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(x, y)| ident_key(x) == ident_key(y))
+}
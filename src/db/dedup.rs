@@ -0,0 +1,284 @@
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+
+use clap::ValueEnum;
+
+use super::cell::Cell;
+use super::dialect::Dialect;
+use super::insert::{insert_spans, remove_items, ListSpan};
+use super::lexer::{next_significant, tokenize_with, TokenKind};
+use super::stream::{open_statements, rewrite_file_buffered, Output};
+
+/// 主键重复时保留哪一行
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum KeepPolicy {
+    First,
+    Last,
+    /// 存在值不一致的重复行时报错，不修改文件；值完全相同的重复行仍保留第一行
+    Fail,
+}
+
+/// 同一张表中出现多次的主键
+#[derive(Debug, Clone)]
+pub struct Duplicate {
+    pub table: String,
+    pub key: Vec<String>,
+    /// 各次出现所在的行号
+    pub lines: Vec<usize>,
+    /// 保留的那一行所在的行号
+    pub kept: usize,
+    /// 各行的值是否不一致
+    pub conflict: bool,
+}
+
+/// 去重结果
+#[derive(Debug, Default)]
+pub struct DedupReport {
+    /// 扫描的数据行数
+    pub rows: usize,
+    /// 删除的重复行数
+    pub removed: usize,
+    pub duplicates: Vec<Duplicate>,
+    /// (行号, 说明) 形式的未处理原因
+    pub warnings: Vec<(usize, String)>,
+    /// 是否改写了文件
+    pub applied: bool,
+}
+
+impl DedupReport {
+    /// 值不一致的重复主键数
+    pub fn conflicts(&self) -> usize {
+        self.duplicates.iter().filter(|d| d.conflict).count()
+    }
+}
+
+/// 主键的一次出现
+struct Occurrence {
+    /// 数据行在文件中的序号
+    ordinal: usize,
+    line: usize,
+    /// 按列名排序后的行内容摘要，用于判断值是否一致
+    digest: u64,
+}
+
+/// 按主键对 SQL 文件中所有 INSERT 的数据行去重，可选按主键排序。
+/// 第一遍扫描找出重复行，第二遍按语句流式改写并按 output 写出结果。
+/// keep 为 Fail 且存在值不一致的重复行时不写出结果；原地改写且无需改动时不修改文件
+pub fn dedup_file(sql_path: &Path, output: &Output, keys: &[String], keep: KeepPolicy, sort: bool, dialect: Dialect) -> Result<DedupReport, String> {
+    let mut report = DedupReport::default();
+    let drop = find_duplicates(sql_path, keys, keep, dialect, &mut report)?;
+    if (keep == KeepPolicy::Fail && report.conflicts() > 0) || (drop.is_empty() && !sort && output.in_place()) {
+        return Ok(report);
+    }
+
+    let mut rewriter = Rewriter { keys, dialect, sort, drop: &drop, ordinal: 0, run: Vec::new() };
+    rewrite_file_buffered(sql_path, output, dialect, |statement| match statement {
+        Some(statement) => rewriter.statement(&statement.text),
+        None => rewriter.flush(),
+    })?;
+    report.removed = drop.len();
+    report.applied = true;
+    Ok(report)
+}
+
+/// 第一遍：记录每个主键的出现位置，返回要删除的数据行序号
+fn find_duplicates(
+    sql_path: &Path,
+    keys: &[String],
+    keep: KeepPolicy,
+    dialect: Dialect,
+    report: &mut DedupReport,
+) -> Result<HashSet<usize>, String> {
+    let (reader, _) = open_statements(sql_path, dialect)?;
+    let mut seen: HashMap<(String, Vec<String>), Vec<Occurrence>> = HashMap::new();
+    // 按首次出现的顺序报告
+    let mut order: Vec<(String, Vec<String>)> = Vec::new();
+    let mut ordinal = 0usize;
+
+    for statement in reader {
+        let statement = statement?;
+        let tokens = tokenize_with(&statement.text, &dialect.lexer_config());
+        for insert in insert_spans(&tokens, dialect) {
+            let at = |k: usize| statement.line + tokens[k].line - 1;
+            let table = insert.table_name(&tokens);
+            let first = ordinal;
+            ordinal += insert.rows.len();
+            let positions = match insert.key_positions(&tokens, keys) {
+                Ok(positions) => positions,
+                Err(reason) => {
+                    report.warnings.push((at(insert.insert), format!("{}: {}，跳过该语句", table, reason)));
+                    continue;
+                }
+            };
+            let columns: Vec<String> = insert.column_names(&tokens).iter().map(|c| c.to_ascii_lowercase()).collect();
+
+            for (n, row) in insert.rows.iter().enumerate() {
+                report.rows += 1;
+                if row.items.len() != columns.len() {
+                    report.warnings.push((at(row.open), format!("{}: 值个数与列数不一致，跳过该行", table)));
+                    continue;
+                }
+                let values: Vec<String> = row.items.iter().map(|&(a, b)| Cell::from_item(&tokens[a..b], dialect).normalized()).collect();
+                let key: Vec<String> = positions.iter().map(|&p| values[p].clone()).collect();
+                let mut pairs: Vec<(&String, &String)> = columns.iter().zip(&values).collect();
+                pairs.sort();
+                let mut hasher = DefaultHasher::new();
+                pairs.hash(&mut hasher);
+
+                let entry = (table.to_ascii_lowercase(), key);
+                let occurrences = seen.entry(entry.clone()).or_default();
+                if occurrences.is_empty() {
+                    order.push(entry);
+                }
+                occurrences.push(Occurrence { ordinal: first + n, line: at(row.open), digest: hasher.finish() });
+            }
+        }
+    }
+
+    let mut drop = HashSet::new();
+    for entry in order {
+        let occurrences = &seen[&entry];
+        if occurrences.len() < 2 {
+            continue;
+        }
+        let kept = if keep == KeepPolicy::Last { occurrences.len() - 1 } else { 0 };
+        drop.extend(occurrences.iter().enumerate().filter(|(n, _)| *n != kept).map(|(_, o)| o.ordinal));
+        report.duplicates.push(Duplicate {
+            table: entry.0,
+            key: entry.1,
+            lines: occurrences.iter().map(|o| o.line).collect(),
+            kept: occurrences[kept].line,
+            conflict: occurrences.iter().any(|o| o.digest != occurrences[0].digest),
+        });
+    }
+    Ok(drop)
+}
+
+/// 第二遍：删除重复行并排序
+struct Rewriter<'a> {
+    keys: &'a [String],
+    dialect: Dialect,
+    sort: bool,
+    drop: &'a HashSet<usize>,
+    ordinal: usize,
+    /// 连续的同表单行 INSERT：(表名, 主键, 前导空白与注释, 语句本身)
+    run: Vec<(String, Vec<Cell>, String, String)>,
+}
+
+impl Rewriter<'_> {
+    fn statement(&mut self, sql: &str) -> String {
+        let tokens = tokenize_with(sql, &self.dialect.lexer_config());
+        let inserts = insert_spans(&tokens, self.dialect);
+        let mut removed = vec![false; tokens.len()];
+        let mut out: Vec<Option<String>> = vec![None; tokens.len()];
+        let mut emptied = 0usize;
+        // 只剩一行的单表 INSERT 的主键，用于语句间排序
+        let mut single_row_key = None;
+
+        for insert in &inserts {
+            let first = self.ordinal;
+            self.ordinal += insert.rows.len();
+            let dropped: Vec<usize> = (0..insert.rows.len()).filter(|n| self.drop.contains(&(first + n))).collect();
+            let kept: Vec<usize> = (0..insert.rows.len()).filter(|n| !dropped.contains(n)).collect();
+
+            if kept.is_empty() {
+                // INSERT ALL 中删除整个 INTO 子句
+                if insert.all {
+                    removed[insert.insert..insert.end].iter_mut().for_each(|r| *r = true);
+                }
+                emptied += 1;
+                continue;
+            }
+            if !dropped.is_empty() {
+                let rows = ListSpan {
+                    open: insert.values,
+                    close: insert.end,
+                    items: insert.rows.iter().map(|r| (r.open, r.close + 1)).collect(),
+                };
+                remove_items(&tokens, &rows, &dropped, &mut removed);
+            }
+
+            let Ok(positions) = insert.key_positions(&tokens, self.keys) else { continue };
+            let key_of = |row: &ListSpan| -> Option<Vec<Cell>> {
+                positions.iter().map(|&p| row.items.get(p).map(|&(a, b)| Cell::from_item(&tokens[a..b], self.dialect))).collect()
+            };
+            if kept.len() == 1 && inserts.len() == 1 && !insert.all {
+                single_row_key = key_of(&insert.rows[kept[0]]).map(|key| (insert.table_name(&tokens).to_ascii_lowercase(), key));
+            }
+            if self.sort && kept.len() > 1 {
+                let mut rows: Vec<(Vec<Cell>, String)> = Vec::new();
+                for &n in &kept {
+                    let row = &insert.rows[n];
+                    let Some(key) = key_of(row) else { break };
+                    rows.push((key, tokens[row.open..=row.close].iter().map(|t| t.text).collect()));
+                }
+                if rows.len() == kept.len() {
+                    rows.sort_by(|a, b| compare_keys(&a.0, &b.0));
+                    for (&n, (_, text)) in kept.iter().zip(rows) {
+                        let row = &insert.rows[n];
+                        out[row.open] = Some(text);
+                        removed[row.open + 1..=row.close].iter_mut().for_each(|r| *r = true);
+                    }
+                }
+            }
+        }
+
+        let body_start = next_significant(&tokens, 0).unwrap_or(tokens.len());
+        if !inserts.is_empty() && emptied == inserts.len() {
+            // 整条语句被删除时只保留其前面的注释
+            let comment_end = tokens[..body_start]
+                .iter()
+                .rposition(|t| matches!(t.kind, TokenKind::LineComment | TokenKind::BlockComment))
+                .map_or(0, |k| k + 1);
+            return tokens[..comment_end].iter().map(|t| t.text).collect();
+        }
+        let prefix: String = tokens[..body_start].iter().map(|t| t.text).collect();
+        let body: String = (body_start..tokens.len())
+            .filter(|&k| !removed[k])
+            .map(|k| out[k].take().unwrap_or_else(|| tokens[k].text.to_string()))
+            .collect();
+
+        match single_row_key.filter(|_| self.sort && tokens.get(body_start).is_some_and(|t| t.is_keyword("insert"))) {
+            Some((table, key)) => {
+                let flushed = if self.run.first().is_some_and(|r| r.0 != table) { self.flush() } else { String::new() };
+                self.run.push((table, key, prefix, body));
+                flushed
+            }
+            None => self.flush() + &prefix + &body,
+        }
+    }
+
+    /// 输出缓存的单行 INSERT：语句按主键排序，前导空白与注释留在原位置
+    fn flush(&mut self) -> String {
+        let mut run = std::mem::take(&mut self.run);
+        let prefixes: Vec<String> = run.iter_mut().map(|r| std::mem::take(&mut r.2)).collect();
+        run.sort_by(|a, b| compare_keys(&a.1, &b.1));
+        prefixes.into_iter().zip(run).map(|(prefix, r)| prefix + &r.3).collect()
+    }
+}
+
+/// 主键排序：NULL 在前，数字（包括加了引号的数字）按数值，字符串按字典序
+fn compare_keys(a: &[Cell], b: &[Cell]) -> Ordering {
+    a.iter().zip(b).map(|(x, y)| compare_cells(x, y)).find(|o| o.is_ne()).unwrap_or(Ordering::Equal)
+}
+
+fn compare_cells(a: &Cell, b: &Cell) -> Ordering {
+    let number = |c: &Cell| match c {
+        Cell::Number(v) | Cell::Text(v) => v.trim().parse::<f64>().ok(),
+        _ => None,
+    };
+    let rank = |c: &Cell| match c {
+        Cell::Null => 0,
+        _ if number(c).is_some() => 1,
+        Cell::Number(_) | Cell::Text(_) => 2,
+        Cell::Raw(_) => 3,
+    };
+    match (a, b, number(a), number(b)) {
+        (_, _, Some(m), Some(n)) => m.partial_cmp(&n).unwrap_or(Ordering::Equal),
+        (Cell::Number(x) | Cell::Text(x), Cell::Number(y) | Cell::Text(y), None, None) | (Cell::Raw(x), Cell::Raw(y), _, _) => x.cmp(y),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
@@ -0,0 +1,149 @@
+use clap::ValueEnum;
+
+use super::lexer::{LexerConfig, Token, TokenKind};
+
+/// 目标数据库方言
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Dialect {
+    #[default]
+    Mysql,
+    Oracle,
+    Postgres,
+    /// 达梦数据库（兼容 Oracle 语法）
+    Dm,
+}
+
+impl Dialect {
+    /// 方言对应的词法规则
+    pub fn lexer_config(&self) -> LexerConfig {
+        match self {
+            Dialect::Mysql => LexerConfig {
+                backslash_escapes: true,
+                double_quoted_strings: true,
+                backtick_idents: true,
+                hash_comments: true,
+                dollar_quotes: false,
+                q_quotes: false,
+            },
+            Dialect::Oracle | Dialect::Dm => LexerConfig {
+                backslash_escapes: false,
+                double_quoted_strings: false,
+                backtick_idents: false,
+                hash_comments: false,
+                dollar_quotes: false,
+                q_quotes: true,
+            },
+            Dialect::Postgres => LexerConfig {
+                backslash_escapes: false,
+                double_quoted_strings: false,
+                backtick_idents: false,
+                hash_comments: false,
+                dollar_quotes: true,
+                q_quotes: false,
+            },
+        }
+    }
+
+    /// 是否使用 SQL*Plus 风格的 PL/SQL 块（以单独一行的 `/` 结束）
+    pub fn has_plsql_blocks(&self) -> bool {
+        matches!(self, Dialect::Oracle | Dialect::Dm)
+    }
+
+    /// 是否支持 `INSERT ALL INTO ... SELECT ... FROM dual`
+    pub fn has_insert_all(&self) -> bool {
+        matches!(self, Dialect::Oracle | Dialect::Dm)
+    }
+
+    /// 按方言给标识符加引号
+    pub fn quote_ident(&self, name: &str) -> String {
+        match self {
+            Dialect::Mysql => format!("`{}`", name.replace('`', "``")),
+            _ => format!("\"{}\"", name.replace('"', "\"\"")),
+        }
+    }
+
+    /// 按方言生成字符串字面量
+    pub fn quote_string(&self, value: &str) -> String {
+        let mut out = String::with_capacity(value.len() + 2);
+        out.push('\'');
+        for ch in value.chars() {
+            match ch {
+                '\'' => out.push_str("''"),
+                '\\' if *self == Dialect::Mysql => out.push_str("\\\\"),
+                '\0' if *self == Dialect::Mysql => out.push_str("\\0"),
+                _ => out.push(ch),
+            }
+        }
+        out.push('\'');
+        out
+    }
+
+    /// 还原字符串字面量的内容；十六进制、位串等非文本字面量返回 None
+    pub fn unquote_string(&self, token: &Token) -> Option<String> {
+        if token.kind != TokenKind::String {
+            return None;
+        }
+        let text = token.text;
+
+        if let Some(rest) = text.strip_prefix('$') {
+            let tag_end = rest.find('$')? + 2;
+            let body_end = text.len().checked_sub(tag_end)?;
+            return text.get(tag_end..body_end.max(tag_end)).map(|s| s.to_string());
+        }
+
+        let quote_pos = text.find(['\'', '"'])?;
+        let prefix = text[..quote_pos].to_ascii_lowercase();
+        let body = &text[quote_pos..];
+        match prefix.as_str() {
+            "q" | "nq" => {
+                // q'[...]'：去掉 q' + 开始定界符，以及结束定界符 + '
+                body.get(2..body.len().checked_sub(2)?).map(|s| s.to_string())
+            }
+            "x" | "b" => None,
+            "e" => Some(unescape(body, true)),
+            _ => Some(unescape(body, self.lexer_config().backslash_escapes)),
+        }
+    }
+}
+
+/// 去掉首尾引号，还原重复引号及（可选的）反斜杠转义
+fn unescape(body: &str, backslash: bool) -> String {
+    let mut chars = body.chars();
+    let quote = chars.next().unwrap_or('\'');
+    let inner: Vec<char> = chars.collect();
+    let inner = match inner.last() {
+        Some(&c) if c == quote => &inner[..inner.len() - 1],
+        _ => &inner[..],
+    };
+
+    let mut out = String::with_capacity(inner.len());
+    let mut i = 0;
+    while i < inner.len() {
+        let c = inner[i];
+        if backslash && c == '\\' && i + 1 < inner.len() {
+            let next = inner[i + 1];
+            match next {
+                'n' => out.push('\n'),
+                't' => out.push('\t'),
+                'r' => out.push('\r'),
+                '0' => out.push('\0'),
+                'b' => out.push('\u{8}'),
+                'Z' => out.push('\u{1a}'),
+                // LIKE 通配符的转义保持原样
+                '%' | '_' => {
+                    out.push('\\');
+                    out.push(next);
+                }
+                other => out.push(other),
+            }
+            i += 2;
+        } else if c == quote && inner.get(i + 1) == Some(&quote) {
+            out.push(quote);
+            i += 2;
+        } else {
+            out.push(c);
+            i += 1;
+        }
+    }
+    out
+}
@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde_json::{json, Map, Value};
+
+use super::cell::Cell;
+use super::dialect::Dialect;
+use super::insert::{insert_spans, item_text};
+use super::lexer::tokenize_with;
+use super::stream::open_statements;
+
+/// 一行数据，列名去掉了引号，值为原文
+#[derive(Debug, Clone)]
+pub struct DataRow {
+    pub line: usize,
+    /// 比较用的主键值
+    pub key: Vec<String>,
+    pub columns: Vec<String>,
+    pub values: Vec<String>,
+    /// 列名原文，生成 SQL 时使用
+    column_texts: Vec<String>,
+    normalized: Vec<String>,
+}
+
+impl DataRow {
+    fn value(&self, column: &str) -> Option<usize> {
+        self.columns.iter().position(|c| c.eq_ignore_ascii_case(column))
+    }
+}
+
+/// 一列的变化，None 表示该侧没有这一列
+#[derive(Debug, Clone)]
+pub struct ColumnChange {
+    pub column: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ModifiedRow {
+    pub old: DataRow,
+    pub new: DataRow,
+    pub changes: Vec<ColumnChange>,
+}
+
+/// 单张表的差异
+#[derive(Debug, Clone)]
+pub struct TableDiff {
+    pub table: String,
+    /// 表名原文，生成 SQL 时使用
+    table_text: String,
+    pub added: Vec<DataRow>,
+    pub removed: Vec<DataRow>,
+    pub modified: Vec<ModifiedRow>,
+}
+
+impl TableDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// 两份数据脚本的差异，按表在旧文件中首次出现的顺序排列，新文件独有的表在后
+#[derive(Debug, Default)]
+pub struct DataDiff {
+    pub tables: Vec<TableDiff>,
+    /// (文件, 行号, 说明) 形式的未处理原因
+    pub warnings: Vec<(String, usize, String)>,
+    keys: Vec<String>,
+}
+
+/// 从文件中读出的一张表
+struct Table {
+    name: String,
+    text: String,
+    rows: Vec<DataRow>,
+    index: HashMap<Vec<String>, usize>,
+}
+
+/// 按主键比较两份 INSERT 脚本，找出新增、删除与修改的行
+pub fn diff_files(old_path: &Path, new_path: &Path, keys: &[String], dialect: Dialect) -> Result<DataDiff, String> {
+    let mut diff = DataDiff { keys: keys.to_vec(), ..Default::default() };
+    let old = load_tables(old_path, keys, dialect, &mut diff.warnings)?;
+    let new = load_tables(new_path, keys, dialect, &mut diff.warnings)?;
+
+    let find = |tables: &[Table], name: &str| tables.iter().position(|t| t.name.eq_ignore_ascii_case(name));
+    for table in &old {
+        let other = find(&new, &table.name).map(|n| &new[n]);
+        diff.tables.push(diff_table(table, other));
+    }
+    for table in new.iter().filter(|t| find(&old, &t.name).is_none()) {
+        let mut table_diff = diff_table(table, None);
+        std::mem::swap(&mut table_diff.added, &mut table_diff.removed);
+        diff.tables.push(table_diff);
+    }
+    diff.tables.retain(|t| !t.is_empty());
+    Ok(diff)
+}
+
+/// new 为 None 时旧表中的行全部视为删除
+fn diff_table(old: &Table, new: Option<&Table>) -> TableDiff {
+    let mut diff = TableDiff {
+        table: old.name.clone(),
+        table_text: new.map_or(&old.text, |n| &n.text).clone(),
+        added: Vec::new(),
+        removed: Vec::new(),
+        modified: Vec::new(),
+    };
+    let Some(new) = new else {
+        diff.removed = old.rows.clone();
+        return diff;
+    };
+
+    for row in &new.rows {
+        let Some(&o) = old.index.get(&row.key) else {
+            diff.added.push(row.clone());
+            continue;
+        };
+        let old_row = &old.rows[o];
+        let mut columns: Vec<&String> = row.columns.iter().collect();
+        columns.extend(old_row.columns.iter().filter(|c| row.value(c).is_none()));
+
+        let changes: Vec<ColumnChange> = columns
+            .into_iter()
+            .filter_map(|column| {
+                let before = old_row.value(column);
+                let after = row.value(column);
+                // 一侧没有这一列时按 NULL 比较，新增一个值为 NULL 的列不算修改
+                let null = Cell::Null.normalized();
+                let same = before.map_or(&null, |b| &old_row.normalized[b]) == after.map_or(&null, |a| &row.normalized[a]);
+                (!same).then(|| ColumnChange {
+                    column: column.clone(),
+                    old: before.map(|b| old_row.values[b].clone()),
+                    new: after.map(|a| row.values[a].clone()),
+                })
+            })
+            .collect();
+        if !changes.is_empty() {
+            diff.modified.push(ModifiedRow { old: old_row.clone(), new: row.clone(), changes });
+        }
+    }
+    diff.removed = old.rows.iter().filter(|r| !new.index.contains_key(&r.key)).cloned().collect();
+    diff
+}
+
+/// 读取文件中所有 INSERT 的数据行，同一张表中主键重复时以后出现的行为准
+fn load_tables(
+    path: &Path,
+    keys: &[String],
+    dialect: Dialect,
+    warnings: &mut Vec<(String, usize, String)>,
+) -> Result<Vec<Table>, String> {
+    let file = path.display().to_string();
+    let (reader, _) = open_statements(path, dialect)?;
+    let mut tables: Vec<Table> = Vec::new();
+
+    for statement in reader {
+        let statement = statement?;
+        let tokens = tokenize_with(&statement.text, &dialect.lexer_config());
+        for insert in insert_spans(&tokens, dialect) {
+            let at = |k: usize| statement.line + tokens[k].line - 1;
+            let name = insert.table_name(&tokens);
+            let positions = match insert.key_positions(&tokens, keys) {
+                Ok(positions) => positions,
+                Err(reason) => {
+                    warnings.push((file.clone(), at(insert.insert), format!("{}: {}，跳过该语句", name, reason)));
+                    continue;
+                }
+            };
+            let columns = insert.column_names(&tokens);
+            let column_texts: Vec<String> = insert.columns.iter().flat_map(|c| &c.items).map(|&(a, b)| item_text(&tokens, a, b)).collect();
+
+            let t = match tables.iter().position(|t| t.name.eq_ignore_ascii_case(&name)) {
+                Some(t) => t,
+                None => {
+                    let text = tokens[insert.table.0..insert.table.1].iter().map(|t| t.text).collect();
+                    tables.push(Table { name: name.clone(), text, rows: Vec::new(), index: HashMap::new() });
+                    tables.len() - 1
+                }
+            };
+            let table = &mut tables[t];
+
+            for row in &insert.rows {
+                if row.items.len() != columns.len() {
+                    warnings.push((file.clone(), at(row.open), format!("{}: 值个数与列数不一致，跳过该行", name)));
+                    continue;
+                }
+                let normalized: Vec<String> =
+                    row.items.iter().map(|&(a, b)| Cell::from_item(&tokens[a..b], dialect).normalized()).collect();
+                let data = DataRow {
+                    line: at(row.open),
+                    key: positions.iter().map(|&p| normalized[p].clone()).collect(),
+                    columns: columns.clone(),
+                    values: row.items.iter().map(|&(a, b)| item_text(&tokens, a, b)).collect(),
+                    column_texts: column_texts.clone(),
+                    normalized,
+                };
+                match table.index.get(&data.key) {
+                    Some(&existing) => {
+                        warnings.push((
+                            file.clone(),
+                            data.line,
+                            format!("{}: 主键 ({}) 与第 {} 行重复，以该行为准", name, data.key.join(", "), table.rows[existing].line),
+                        ));
+                        table.rows[existing] = data;
+                    }
+                    None => {
+                        table.index.insert(data.key.clone(), table.rows.len());
+                        table.rows.push(data);
+                    }
+                }
+            }
+        }
+    }
+    Ok(tables)
+}
+
+impl DataDiff {
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    /// (新增, 删除, 修改) 的行数
+    pub fn counts(&self) -> (usize, usize, usize) {
+        self.tables.iter().fold((0, 0, 0), |(a, r, m), t| (a + t.added.len(), r + t.removed.len(), m + t.modified.len()))
+    }
+
+    /// 主键的展示形式，例如 `id=1, code=a`
+    pub fn key_label(&self, row: &DataRow) -> String {
+        self.keys.iter().zip(&row.key).map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join(", ")
+    }
+
+    /// 把旧数据变为新数据的脚本：先按表的逆序删除，再更新，最后按表的顺序插入
+    pub fn script(&self, header: &str) -> String {
+        let mut lines = vec![format!("-- {}", header)];
+        for table in self.tables.iter().rev() {
+            for row in table.removed.iter().rev() {
+                lines.push(format!("DELETE FROM {} WHERE {};", table.table_text, self.condition(row)));
+            }
+        }
+        for table in &self.tables {
+            for row in &table.modified {
+                // 新数据中没有的列无法用 UPDATE 表达，保持不变
+                let sets: Vec<String> = row
+                    .changes
+                    .iter()
+                    .filter_map(|c| {
+                        let n = row.new.value(&c.column)?;
+                        Some(format!("{} = {}", row.new.column_texts[n], row.new.values[n]))
+                    })
+                    .collect();
+                if !sets.is_empty() {
+                    lines.push(format!("UPDATE {} SET {} WHERE {};", table.table_text, sets.join(", "), self.condition(&row.new)));
+                }
+            }
+        }
+        for table in &self.tables {
+            for row in &table.added {
+                lines.push(format!(
+                    "INSERT INTO {} ({}) VALUES ({});",
+                    table.table_text,
+                    row.column_texts.join(", "),
+                    row.values.join(", ")
+                ));
+            }
+        }
+        lines.push("COMMIT;".to_string());
+        lines.join("\n") + "\n"
+    }
+
+    /// 按主键定位一行的 WHERE 条件
+    fn condition(&self, row: &DataRow) -> String {
+        self.keys
+            .iter()
+            .filter_map(|key| row.value(key))
+            .map(|p| {
+                if row.values[p].eq_ignore_ascii_case("null") {
+                    format!("{} IS NULL", row.column_texts[p])
+                } else {
+                    format!("{} = {}", row.column_texts[p], row.values[p])
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" AND ")
+    }
+
+    /// 机器可读的 JSON 报告
+    pub fn to_json(&self) -> String {
+        let row_json = |row: &DataRow| {
+            let values: Map<String, Value> =
+                row.columns.iter().zip(&row.values).map(|(c, v)| (c.clone(), Value::String(v.clone()))).collect();
+            json!({ "key": self.key_label(row), "line": row.line, "values": values })
+        };
+        let tables: Vec<Value> = self
+            .tables
+            .iter()
+            .map(|t| {
+                let modified: Vec<Value> = t
+                    .modified
+                    .iter()
+                    .map(|m| {
+                        let changes: Vec<Value> =
+                            m.changes.iter().map(|c| json!({ "column": c.column, "old": c.old, "new": c.new })).collect();
+                        json!({ "key": self.key_label(&m.new), "old_line": m.old.line, "new_line": m.new.line, "changes": changes })
+                    })
+                    .collect();
+                json!({
+                    "table": t.table,
+                    "added": t.added.iter().map(row_json).collect::<Vec<_>>(),
+                    "removed": t.removed.iter().map(row_json).collect::<Vec<_>>(),
+                    "modified": modified,
+                })
+            })
+            .collect();
+        let (added, removed, modified) = self.counts();
+        let report = json!({ "tables": tables, "added": added, "removed": removed, "modified": modified });
+        serde_json::to_string_pretty(&report).unwrap_or_default()
+    }
+}
@@ -0,0 +1,129 @@
+use std::path::Path;
+
+use super::dialect::Dialect;
+use super::insert::{parse_inserts, remove_items};
+use super::lexer::tokenize_with;
+use super::stream::{rewrite_file, Output};
+
+/// 单张表的删除统计
+#[derive(Debug, Clone, Default)]
+pub struct TableDropStat {
+    pub table: String,
+    /// 删除了列的 INSERT 语句数
+    pub statements: usize,
+    /// 删除了值的数据行数
+    pub rows: usize,
+}
+
+/// 删除列的处理结果，按表首次出现的顺序排列
+#[derive(Debug, Default)]
+pub struct DropColumnsReport {
+    pub tables: Vec<TableDropStat>,
+    /// (行号, 说明) 形式的未处理原因
+    pub warnings: Vec<(usize, String)>,
+}
+
+impl DropColumnsReport {
+    fn record(&mut self, table: &str, rows: usize) {
+        match self.tables.iter_mut().find(|t| t.table == table) {
+            Some(stat) => {
+                stat.statements += 1;
+                stat.rows += rows;
+            }
+            None => self.tables.push(TableDropStat { table: table.to_string(), statements: 1, rows }),
+        }
+    }
+
+    /// 合并另一份统计结果
+    pub fn merge(&mut self, other: DropColumnsReport) {
+        for stat in other.tables {
+            match self.tables.iter_mut().find(|t| t.table == stat.table) {
+                Some(existing) => {
+                    existing.statements += stat.statements;
+                    existing.rows += stat.rows;
+                }
+                None => self.tables.push(stat),
+            }
+        }
+        self.warnings.extend(other.warnings);
+    }
+
+    /// 修改处总数：每条语句的列列表计 1 处，每个元组计 1 处
+    pub fn changed_count(&self) -> usize {
+        self.tables.iter().map(|t| t.statements + t.rows).sum()
+    }
+}
+
+/// 处理 SQL 文件：删除 INSERT 中指定的列及每个元组中对应位置的值。
+/// 按语句流式处理并按 output 写出结果。
+pub fn dropcol_file(sql_path: &Path, output: &Output, columns: &[String], dialect: Dialect) -> Result<DropColumnsReport, String> {
+    let mut report = DropColumnsReport::default();
+    rewrite_file(sql_path, output, dialect, |statement| {
+        let (processed, mut stmt_report) = drop_columns_sql(&statement.text, columns, dialect);
+        // 语句内的行号换算为文件中的行号
+        stmt_report.warnings.iter_mut().for_each(|(line, _)| *line += statement.line - 1);
+        report.merge(stmt_report);
+        processed
+    })?;
+    Ok(report)
+}
+
+/// 删除 SQL 文本中 INSERT 的指定列（不区分大小写）。没有列列表或不含这些列的语句保持不变；
+/// 有元组的值个数与列数不一致时无法确定对应关系，该语句保持不变并记录警告
+pub fn drop_columns_sql(sql: &str, columns: &[String], dialect: Dialect) -> (String, DropColumnsReport) {
+    let tokens = tokenize_with(sql, &dialect.lexer_config());
+    let mut removed = vec![false; tokens.len()];
+    let mut report = DropColumnsReport::default();
+
+    let mut i = 0usize;
+    while i < tokens.len() {
+        let inserts = if tokens[i].is_keyword("insert") { parse_inserts(&tokens, i, dialect) } else { Vec::new() };
+        let Some(last) = inserts.last() else {
+            i += 1;
+            continue;
+        };
+        i = last.end;
+
+        for insert in &inserts {
+            let Some(col_list) = &insert.columns else { continue };
+            let mut positions: Vec<usize> = columns
+                .iter()
+                .filter_map(|c| insert.column_index(&tokens, c))
+                .collect();
+            positions.sort_unstable();
+            positions.dedup();
+            if positions.is_empty() {
+                continue;
+            }
+
+            let table = insert.table_name(&tokens);
+            if let Some((n, row)) = insert.rows.iter().enumerate().find(|(_, row)| row.items.len() != col_list.items.len()) {
+                report.warnings.push((
+                    tokens[insert.insert].line,
+                    format!(
+                        "{}: 第 {} 个元组有 {} 个值，与 {} 列不一致，语句保持原样",
+                        table,
+                        n + 1,
+                        row.items.len(),
+                        col_list.items.len()
+                    ),
+                ));
+                continue;
+            }
+
+            remove_items(&tokens, col_list, &positions, &mut removed);
+            for row in &insert.rows {
+                remove_items(&tokens, row, &positions, &mut removed);
+            }
+            report.record(&table, insert.rows.len());
+        }
+    }
+
+    let out: String = tokens
+        .iter()
+        .zip(&removed)
+        .filter(|(_, r)| !**r)
+        .map(|(t, _)| t.text)
+        .collect();
+    (out, report)
+}
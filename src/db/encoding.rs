@@ -0,0 +1,367 @@
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::sync::OnceLock;
+
+use encoding_rs::{Decoder, DecoderResult, Encoder, EncoderResult, Encoding, GB18030, GBK, REPLACEMENT, UTF_16BE, UTF_16LE, UTF_8};
+
+use super::stream::{output_target, write_atomically, Output};
+
+/// 文件的字符编码及是否带 BOM
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileEncoding {
+    pub encoding: &'static Encoding,
+    pub bom: bool,
+}
+
+impl FileEncoding {
+    pub const UTF8: FileEncoding = FileEncoding { encoding: UTF_8, bom: false };
+
+    /// 解析 `utf-8`、`utf-8-bom`、`gbk`、`gb18030`、`utf-16le` 等编码名称，UTF-16 总是带 BOM
+    pub fn parse(label: &str) -> Result<Self, String> {
+        let lower = label.trim().to_ascii_lowercase();
+        let (name, bom) = match lower.strip_suffix("-bom").or_else(|| lower.strip_suffix("-sig")) {
+            Some(name) => (name, true),
+            None => (lower.as_str(), false),
+        };
+        let encoding = Encoding::for_label(name.as_bytes())
+            .filter(|&e| e != REPLACEMENT)
+            .ok_or_else(|| format!("不支持的编码: {}", label))?;
+        let utf16 = encoding == UTF_16LE || encoding == UTF_16BE;
+        if bom && encoding != UTF_8 && !utf16 {
+            return Err(format!("只有 UTF 编码可以带 BOM: {}", label));
+        }
+        Ok(FileEncoding { encoding, bom: bom || utf16 })
+    }
+
+    /// 按内容识别编码：有 BOM 时按 BOM；全部是合法 UTF-8 时为 UTF-8；
+    /// 否则按 GBK，出现 GB18030 的四字节序列时为 GB18030
+    pub fn detect(path: &Path) -> Result<Self, String> {
+        let mut file = File::open(path).map_err(|e| format!("读取文件失败: {}", e))?;
+        let mut detector = Detector::default();
+        let mut buf = vec![0u8; 64 * 1024];
+        loop {
+            let n = file.read(&mut buf).map_err(|e| format!("读取文件失败: {}", e))?;
+            if n == 0 || detector.feed(&buf[..n]) {
+                break;
+            }
+        }
+        Ok(detector.finish())
+    }
+
+    /// 与 [`FileEncoding::detect`] 相同，识别内存中的内容
+    pub fn detect_bytes(bytes: &[u8]) -> Self {
+        let mut detector = Detector::default();
+        detector.feed(bytes);
+        detector.finish()
+    }
+
+    /// 显示用的名称，例如 `GBK`、`UTF-8 (BOM)`
+    pub fn name(&self) -> String {
+        if self.bom && self.encoding == UTF_8 {
+            format!("{} (BOM)", self.encoding.name())
+        } else {
+            self.encoding.name().to_string()
+        }
+    }
+
+    fn bom_bytes(&self) -> &'static [u8] {
+        match self.encoding {
+            _ if !self.bom => b"",
+            e if e == UTF_8 => b"\xEF\xBB\xBF",
+            e if e == UTF_16LE => b"\xFF\xFE",
+            e if e == UTF_16BE => b"\xFE\xFF",
+            _ => b"",
+        }
+    }
+
+    /// 把文本转换为该编码，带 BOM 时写在开头；遇到无法表示的字符时报错
+    pub fn encode(&self, text: &str) -> Result<Vec<u8>, String> {
+        let mut writer = EncodedWriter::new(Vec::new(), *self);
+        writer.write_all(text.as_bytes()).map_err(|e| e.to_string())?;
+        Ok(writer.into_inner())
+    }
+
+    /// 把该编码的内容转换为文本，开头的 BOM 会被去掉；遇到无效字节时报错
+    pub fn decode(&self, bytes: &[u8]) -> Result<String, String> {
+        let mut text = String::new();
+        DecodeReader::new(bytes, *self).read_to_string(&mut text).map_err(|e| e.to_string())?;
+        Ok(text)
+    }
+}
+
+/// 命令行通过 `--encoding`、`--output-encoding` 指定的编码
+static OVERRIDES: OnceLock<(Option<FileEncoding>, Option<FileEncoding>)> = OnceLock::new();
+
+/// 设置命令行指定的输入与输出编码，只在启动时调用一次
+pub fn set_overrides(input: Option<FileEncoding>, output: Option<FileEncoding>) {
+    let _ = OVERRIDES.set((input, output));
+}
+
+/// 输入文件的编码：命令行指定了 `--encoding` 时直接使用，否则按内容识别
+pub fn input_encoding(path: &Path) -> Result<FileEncoding, String> {
+    match OVERRIDES.get().and_then(|o| o.0) {
+        Some(encoding) => Ok(encoding),
+        None => FileEncoding::detect(path),
+    }
+}
+
+/// 由输入文件生成的文件使用的编码：命令行指定了 `--output-encoding` 时使用，否则与输入相同
+pub fn output_encoding(input: FileEncoding) -> FileEncoding {
+    OVERRIDES.get().and_then(|o| o.1).unwrap_or(input)
+}
+
+/// 写到标准输出时使用的编码：命令行指定了 `--output-encoding` 时使用（不带 BOM），否则为 UTF-8
+pub fn stdout_encoding() -> FileEncoding {
+    OVERRIDES.get().and_then(|o| o.1).map(|e| FileEncoding { bom: e.bom && e.encoding != UTF_8, ..e }).unwrap_or(FileEncoding::UTF8)
+}
+
+/// 逐块识别编码
+#[derive(Default)]
+struct Detector {
+    started: bool,
+    bom: Option<FileEncoding>,
+    /// 上一块末尾不完整的 UTF-8 字节
+    carry: Vec<u8>,
+    not_utf8: bool,
+    /// 上一个字节是 GBK 双字节字符的首字节
+    lead: bool,
+    four_byte: bool,
+}
+
+impl Detector {
+    /// 读入一块内容，已能确定编码时返回 true
+    fn feed(&mut self, chunk: &[u8]) -> bool {
+        if !self.started {
+            self.started = true;
+            if let Some((encoding, _)) = Encoding::for_bom(chunk) {
+                self.bom = Some(FileEncoding { encoding, bom: true });
+                return true;
+            }
+        }
+        if !self.not_utf8 {
+            let mut data = std::mem::take(&mut self.carry);
+            data.extend_from_slice(chunk);
+            match std::str::from_utf8(&data) {
+                Ok(_) => {}
+                Err(e) if e.error_len().is_none() => self.carry = data[e.valid_up_to()..].to_vec(),
+                Err(_) => self.not_utf8 = true,
+            }
+        }
+        if !self.four_byte {
+            for &b in chunk {
+                if self.lead {
+                    // GB18030 四字节序列的第二个字节是数字，GBK 的尾字节不会是数字
+                    self.four_byte |= b.is_ascii_digit();
+                    self.lead = false;
+                } else if (0x81..=0xFE).contains(&b) {
+                    self.lead = true;
+                }
+            }
+        }
+        self.not_utf8 && self.four_byte
+    }
+
+    fn finish(self) -> FileEncoding {
+        match self.bom {
+            Some(bom) => bom,
+            None if !self.not_utf8 && self.carry.is_empty() => FileEncoding::UTF8,
+            None if self.four_byte => FileEncoding { encoding: GB18030, bom: false },
+            None => FileEncoding { encoding: GBK, bom: false },
+        }
+    }
+}
+
+/// 按指定编码解码的读取器，读出的是 UTF-8 文本，开头的 BOM 会被去掉；
+/// 遇到该编码下无效的字节时报错，而不是替换为 U+FFFD
+pub struct DecodeReader<R> {
+    inner: R,
+    encoding: FileEncoding,
+    decoder: Decoder,
+    input: Vec<u8>,
+    start: usize,
+    end: usize,
+    /// 已解码的原始字节数，用于报告出错位置
+    consumed: u64,
+    output: Vec<u8>,
+    pos: usize,
+    eof: bool,
+    done: bool,
+}
+
+impl<R: Read> DecodeReader<R> {
+    pub fn new(inner: R, encoding: FileEncoding) -> Self {
+        DecodeReader {
+            inner,
+            encoding,
+            decoder: encoding.encoding.new_decoder_with_bom_removal(),
+            input: vec![0u8; 64 * 1024],
+            start: 0,
+            end: 0,
+            consumed: 0,
+            output: Vec::new(),
+            pos: 0,
+            eof: false,
+            done: false,
+        }
+    }
+}
+
+impl<R: Read> Read for DecodeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.pos < self.output.len() {
+                let n = buf.len().min(self.output.len() - self.pos);
+                buf[..n].copy_from_slice(&self.output[self.pos..self.pos + n]);
+                self.pos += n;
+                return Ok(n);
+            }
+            if self.done {
+                return Ok(0);
+            }
+            if self.start == self.end && !self.eof {
+                self.start = 0;
+                self.end = self.inner.read(&mut self.input)?;
+                self.eof = self.end == 0;
+            }
+
+            let src = &self.input[self.start..self.end];
+            let capacity = self.decoder.max_utf8_buffer_length_without_replacement(src.len()).unwrap_or(src.len() * 3 + 16);
+            self.output.resize(capacity, 0);
+            self.pos = 0;
+            let (result, read, written) = self.decoder.decode_to_utf8_without_replacement(src, &mut self.output, self.eof);
+            self.output.truncate(written);
+            self.start += read;
+            self.consumed += read as u64;
+            match result {
+                DecoderResult::Malformed(bad, after) => {
+                    let offset = self.consumed - after as u64 - bad as u64;
+                    self.done = true;
+                    self.output.clear();
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("按 {} 解码失败：第 {} 字节处的内容无效", self.encoding.name(), offset + 1),
+                    ));
+                }
+                DecoderResult::InputEmpty if self.eof => self.done = true,
+                _ => {}
+            }
+        }
+    }
+}
+
+/// 把写入的 UTF-8 文本转换为指定编码后写入下层，第一次写入时先写 BOM
+pub struct EncodedWriter<W: Write> {
+    inner: W,
+    encoding: FileEncoding,
+    encoder: Encoder,
+    /// 末尾不完整的 UTF-8 字节，等待下次写入补全
+    pending: Vec<u8>,
+    started: bool,
+}
+
+impl<W: Write> EncodedWriter<W> {
+    pub fn new(inner: W, encoding: FileEncoding) -> Self {
+        let encoder = encoding.encoding.output_encoding().new_encoder();
+        EncodedWriter { inner, encoding, encoder, pending: Vec::new(), started: false }
+    }
+
+    /// 取回下层写入器
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    fn write_text(&mut self, text: &str) -> io::Result<()> {
+        if !self.started {
+            self.started = true;
+            self.inner.write_all(self.encoding.bom_bytes())?;
+        }
+        let encoding = self.encoding.encoding;
+        if encoding == UTF_8 {
+            return self.inner.write_all(text.as_bytes());
+        }
+        if encoding == UTF_16LE || encoding == UTF_16BE {
+            let le = encoding == UTF_16LE;
+            let bytes: Vec<u8> = text.encode_utf16().flat_map(|u| if le { u.to_le_bytes() } else { u.to_be_bytes() }).collect();
+            return self.inner.write_all(&bytes);
+        }
+
+        let mut out = Vec::new();
+        let mut rest = text;
+        loop {
+            out.reserve(self.encoder.max_buffer_length_from_utf8_without_replacement(rest.len()).unwrap_or(rest.len() * 4));
+            let (result, read) = self.encoder.encode_from_utf8_to_vec_without_replacement(rest, &mut out, false);
+            rest = &rest[read..];
+            match result {
+                EncoderResult::InputEmpty => break,
+                EncoderResult::OutputFull => continue,
+                EncoderResult::Unmappable(c) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("字符 '{}' (U+{:04X}) 无法用 {} 表示", c, c as u32, self.encoding.name()),
+                    ));
+                }
+            }
+        }
+        self.inner.write_all(&out)
+    }
+}
+
+impl<W: Write> Write for EncodedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut data = std::mem::take(&mut self.pending);
+        data.extend_from_slice(buf);
+        let valid = match std::str::from_utf8(&data) {
+            Ok(text) => text.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => return Err(io::Error::new(io::ErrorKind::InvalidData, "写入的内容不是合法的 UTF-8")),
+        };
+        self.write_text(std::str::from_utf8(&data[..valid]).unwrap())?;
+        self.pending = data[valid..].to_vec();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// 打开文件并按指定编码解码
+pub fn open_decoded(path: &Path, encoding: FileEncoding) -> Result<DecodeReader<File>, String> {
+    let file = File::open(path).map_err(|e| format!("读取文件失败: {}", e))?;
+    Ok(DecodeReader::new(file, encoding))
+}
+
+/// 创建文件，写入的文本按指定编码保存
+pub fn create_encoded(path: &Path, encoding: FileEncoding) -> Result<EncodedWriter<io::BufWriter<File>>, String> {
+    let file = File::create(path).map_err(|e| format!("创建文件失败 {}: {}", path.display(), e))?;
+    Ok(EncodedWriter::new(io::BufWriter::new(file), encoding))
+}
+
+/// 按编码写入整个文件
+pub fn write_encoded(path: &Path, text: &str, encoding: FileEncoding) -> Result<(), String> {
+    let bytes = encoding.encode(text)?;
+    fs::write(path, bytes).map_err(|e| format!("写入文件失败: {}", e))
+}
+
+/// 转换单个文件的编码，按 output 写出结果，返回原编码；已是目标编码时返回 None。
+/// 先完整解码再写入，原内容含无效字节或目标编码无法表示的字符时不写出。
+/// 原地修改且已是目标编码时不改动文件，其余输出方式照常写出
+pub fn recode_file(path: &Path, output: &Output, to: FileEncoding) -> Result<Option<FileEncoding>, String> {
+    let from = input_encoding(path)?;
+    if from == to && output.in_place() {
+        return Ok(None);
+    }
+    let bytes = fs::read(path).map_err(|e| format!("读取文件失败: {}", e))?;
+    let encoded = to.encode(&from.decode(&bytes)?)?;
+
+    match output_target(path, output)? {
+        // 指定了目标编码，标准输出同样按目标编码写出
+        None => io::stdout().lock().write_all(&encoded).map_err(|e| format!("写入内容失败: {}", e))?,
+        Some(target) => write_atomically(path, target, output, |mut file| {
+            file.write_all(&encoded).map_err(|e| format!("写入内容失败: {}", e))
+        })?,
+    }
+    Ok((from != to).then_some(from))
+}
+
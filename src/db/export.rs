@@ -0,0 +1,168 @@
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use serde_json::{Map, Value};
+
+use super::cell::Cell;
+use super::csv::{csv_line, csv_text};
+use super::dialect::Dialect;
+use super::encoding::{create_encoded, input_encoding, output_encoding, EncodedWriter, FileEncoding};
+use super::insert::parse_inserts;
+use super::lexer::tokenize_with;
+use super::stream::open_statements_as;
+use crate::utils::progress::Progress;
+
+/// 表格数据文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DataFormat {
+    Csv,
+    Json,
+}
+
+impl DataFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DataFormat::Csv => "csv",
+            DataFormat::Json => "json",
+        }
+    }
+
+    /// 按扩展名判断格式
+    pub fn from_path(path: &Path) -> Option<DataFormat> {
+        let ext = path.extension()?.to_string_lossy().to_ascii_lowercase();
+        match ext.as_str() {
+            "csv" => Some(DataFormat::Csv),
+            "json" => Some(DataFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// 导出的一张表
+#[derive(Debug, Clone)]
+pub struct ExportedTable {
+    pub table: String,
+    pub columns: Vec<String>,
+    pub rows: usize,
+    pub path: PathBuf,
+}
+
+/// 导出结果，按表首次出现的顺序排列
+#[derive(Debug, Default)]
+pub struct ExportReport {
+    pub tables: Vec<ExportedTable>,
+    /// (行号, 说明) 形式的未导出原因
+    pub warnings: Vec<(usize, String)>,
+}
+
+struct TableWriter {
+    info: ExportedTable,
+    writer: EncodedWriter<BufWriter<File>>,
+}
+
+impl TableWriter {
+    /// CSV 按 encoding 保存，UTF-8 时带 BOM 以便表格软件识别中文；JSON 总是 UTF-8
+    fn create(dir: &Path, table: &str, columns: Vec<String>, format: DataFormat, encoding: FileEncoding) -> Result<Self, String> {
+        let name: String = table.chars().map(|c| if matches!(c, '/' | '\\' | ':') { '_' } else { c }).collect();
+        let path = dir.join(format!("{}.{}", name, format.extension()));
+        let encoding = match format {
+            DataFormat::Csv if encoding.encoding == FileEncoding::UTF8.encoding => FileEncoding { bom: true, ..encoding },
+            DataFormat::Csv => encoding,
+            DataFormat::Json => FileEncoding::UTF8,
+        };
+        let mut writer = create_encoded(&path, encoding)?;
+        let header = match format {
+            DataFormat::Csv => csv_line(&columns.iter().map(|c| csv_text(c)).collect::<Vec<_>>()),
+            DataFormat::Json => "[\n".to_string(),
+        };
+        writer.write_all(header.as_bytes()).map_err(|e| format!("写入内容失败: {}", e))?;
+        Ok(TableWriter { info: ExportedTable { table: table.to_string(), columns, rows: 0, path }, writer })
+    }
+
+    fn write_row(&mut self, cells: &[Cell], format: DataFormat) -> Result<(), String> {
+        let line = match format {
+            DataFormat::Csv => csv_line(&cells.iter().map(|c| c.to_csv()).collect::<Vec<_>>()),
+            DataFormat::Json => {
+                let object: Map<String, Value> = self.info.columns.iter().cloned().zip(cells.iter().map(|c| c.to_json())).collect();
+                let sep = if self.info.rows == 0 { "" } else { ",\n" };
+                format!("{}  {}", sep, Value::Object(object))
+            }
+        };
+        self.writer.write_all(line.as_bytes()).map_err(|e| format!("写入内容失败: {}", e))?;
+        self.info.rows += 1;
+        Ok(())
+    }
+
+    fn finish(mut self, format: DataFormat) -> Result<ExportedTable, String> {
+        if format == DataFormat::Json {
+            let tail = if self.info.rows == 0 { "]\n" } else { "\n]\n" };
+            self.writer.write_all(tail.as_bytes()).map_err(|e| format!("写入内容失败: {}", e))?;
+        }
+        self.writer.flush().map_err(|e| format!("写入内容失败: {}", e))?;
+        Ok(self.info)
+    }
+}
+
+/// 把 SQL 文件中的 INSERT 数据按表导出为 CSV 或 JSON 文件，每张表一个文件
+pub fn export_file(sql_path: &Path, out_dir: &Path, format: DataFormat, dialect: Dialect) -> Result<ExportReport, String> {
+    let source = input_encoding(sql_path)?;
+    let encoding = output_encoding(source);
+    let (mut reader, total) = open_statements_as(sql_path, dialect, source)?;
+    fs::create_dir_all(out_dir).map_err(|e| format!("创建目录失败: {}", e))?;
+
+    let mut writers: Vec<TableWriter> = Vec::new();
+    let mut report = ExportReport::default();
+    let mut progress = Progress::new("导出中", total);
+
+    while let Some(statement) = reader.next() {
+        let statement = statement?;
+        let tokens = tokenize_with(&statement.text, &dialect.lexer_config());
+        let mut i = 0usize;
+        while i < tokens.len() {
+            let inserts = if tokens[i].is_keyword("insert") { parse_inserts(&tokens, i, dialect) } else { Vec::new() };
+            let Some(last) = inserts.last() else {
+                i += 1;
+                continue;
+            };
+            let line = statement.line + tokens[i].line - 1;
+            i = last.end;
+
+            for insert in &inserts {
+                let table = insert.table_name(&tokens);
+                let columns = insert.column_names(&tokens);
+                if columns.is_empty() {
+                    report.warnings.push((line, format!("{}: 缺少列列表，未导出", table)));
+                    continue;
+                }
+                let writer = match writers.iter().position(|w| w.info.table == table) {
+                    Some(pos) => &mut writers[pos],
+                    None => {
+                        writers.push(TableWriter::create(out_dir, &table, columns.clone(), format, encoding)?);
+                        writers.last_mut().unwrap()
+                    }
+                };
+                if writer.info.columns != columns {
+                    report.warnings.push((line, format!("{}: 列与之前的语句不一致，未导出", table)));
+                    continue;
+                }
+                for row in &insert.rows {
+                    if row.items.len() != columns.len() {
+                        report.warnings.push((line, format!("{}: 值个数与列数不一致，未导出该行", table)));
+                        continue;
+                    }
+                    let cells: Vec<Cell> = row.items.iter().map(|&(a, b)| Cell::from_item(&tokens[a..b], dialect)).collect();
+                    writer.write_row(&cells, format)?;
+                }
+            }
+        }
+        progress.update(reader.bytes_read());
+    }
+    progress.finish();
+
+    for writer in writers {
+        report.tables.push(writer.finish(format)?);
+    }
+    Ok(report)
+}
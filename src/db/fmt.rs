@@ -0,0 +1,460 @@
+use std::io::Cursor;
+use std::ops::Range;
+use std::path::Path;
+
+use clap::ValueEnum;
+
+use super::ddl::{leading_words, name_parts, skip_words};
+use super::dialect::Dialect;
+use super::insert::{insert_spans, InsertSpan, ListSpan};
+use super::lexer::{matching_paren, next_significant, prev_significant, split_top_level, tokenize_with, Token, TokenKind};
+use super::stream::{is_plsql_block, open_statements, rewrite_file_buffered, Output, StatementReader};
+
+/// 任何位置都按关键字处理的单词。常被用作表名的单词（user、comment、session 等）不在其中
+const KEYWORDS: &[&str] = &[
+    "add", "all", "alter", "and", "as", "asc", "begin", "between", "by", "cascade", "case", "column", "commit",
+    "conflict", "constraint", "create", "cross", "default", "delayed", "delete", "desc", "distinct", "do", "drop",
+    "duplicate", "else", "end", "exists", "false", "first", "foreign", "from", "full", "grant", "group", "having",
+    "high_priority", "if", "ignore", "in", "index", "inner", "insert", "interval", "into", "is", "join", "key", "last",
+    "left", "like", "limit", "lock", "low_priority", "matched", "merge", "modify", "not", "nothing", "null", "nulls",
+    "offset", "on", "or", "order", "outer", "primary", "references", "rename", "replace", "restrict", "returning",
+    "revoke", "right", "rollback", "savepoint", "select", "set", "start", "table", "tables", "then", "to",
+    "transaction", "true", "truncate", "union", "unique", "unlock", "update", "using", "values", "view", "when",
+    "where", "with",
+];
+
+/// 只在建表语句的列定义、约束与表选项中按关键字处理的单词，多为类型名与列属性
+const DDL_KEYWORDS: &[&str] = &[
+    "always", "auto_increment", "bigint", "bigserial", "binary", "bit", "blob", "bool", "boolean", "byte", "char",
+    "character", "charset", "check", "clob", "collate", "comment", "current_timestamp", "date", "datetime", "decimal",
+    "double", "engine", "enum", "float", "fulltext", "generated", "identity", "int", "integer", "json", "jsonb",
+    "longblob", "longtext", "mediumblob", "mediumint", "mediumtext", "nchar", "nclob", "number", "numeric",
+    "nvarchar", "nvarchar2", "precision", "raw", "real", "serial", "smallint", "spatial", "stored", "text", "time",
+    "timestamp", "tinyblob", "tinyint", "tinytext", "unsigned", "uuid", "varbinary", "varchar", "varchar2", "varying",
+    "virtual", "year", "zerofill", "zone",
+];
+
+/// 建表语句中以这些单词开头的定义是约束或索引，不是列
+const CONSTRAINT_STARTS: &[&str] = &["check", "constraint", "foreign", "fulltext", "index", "key", "primary", "spatial", "unique"];
+
+/// 多行 VALUES 中超过该宽度的列不再补齐空格
+const ALIGN_LIMIT: usize = 30;
+
+/// 关键字大小写
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum KeywordCase {
+    Upper,
+    Lower,
+}
+
+/// 格式化选项
+#[derive(Debug, Clone, Copy)]
+pub struct FmtOptions {
+    pub keyword_case: KeywordCase,
+    /// 缩进的空格数
+    pub indent: usize,
+}
+
+impl Default for FmtOptions {
+    fn default() -> Self {
+        FmtOptions { keyword_case: KeywordCase::Upper, indent: 2 }
+    }
+}
+
+/// 格式化 SQL 文本
+pub fn format_sql(sql: &str, options: &FmtOptions, dialect: Dialect) -> String {
+    let mut formatter = Formatter { options, dialect, started: false };
+    let mut out = String::new();
+    for statement in StatementReader::new(Cursor::new(sql), dialect).flatten() {
+        out.push_str(&formatter.statement(&statement.text));
+    }
+    out + formatter.finish()
+}
+
+/// 检查文件是否已格式化，未格式化时返回第一处不同所在的行号
+pub fn check_file(sql_path: &Path, options: &FmtOptions, dialect: Dialect) -> Result<Option<usize>, String> {
+    let (reader, _) = open_statements(sql_path, dialect)?;
+    let mut formatter = Formatter { options, dialect, started: false };
+    // 只保留两边尚未比较的部分
+    let (mut original, mut formatted) = (String::new(), String::new());
+    let mut line = 1;
+    for statement in reader {
+        let statement = statement?;
+        original.push_str(&statement.text);
+        formatted.push_str(&formatter.statement(&statement.text));
+        if !advance(&mut original, &mut formatted, &mut line) {
+            return Ok(Some(line));
+        }
+    }
+    formatted.push_str(formatter.finish());
+    let same = advance(&mut original, &mut formatted, &mut line) && original.is_empty() && formatted.is_empty();
+    Ok((!same).then_some(line))
+}
+
+/// 格式化文件，原地改写且已格式化时不改动；否则按语句流式改写并按 output 写出结果。
+/// 返回是否写出了结果
+pub fn fmt_file(sql_path: &Path, output: &Output, options: &FmtOptions, dialect: Dialect) -> Result<bool, String> {
+    if output.in_place() && check_file(sql_path, options, dialect)?.is_none() {
+        return Ok(false);
+    }
+    let mut formatter = Formatter { options, dialect, started: false };
+    rewrite_file_buffered(sql_path, output, dialect, |statement| match statement {
+        Some(statement) => formatter.statement(&statement.text),
+        None => formatter.finish().to_string(),
+    })?;
+    Ok(true)
+}
+
+/// 去掉两段文本的公共前缀并累计其中的换行数；出现不同的字符时返回 false
+fn advance(a: &mut String, b: &mut String, line: &mut usize) -> bool {
+    let common = a.bytes().zip(b.bytes()).take_while(|(x, y)| x == y).count();
+    *line += a[..common].matches('\n').count();
+    if common < a.len().min(b.len()) {
+        return false;
+    }
+    a.drain(..common);
+    b.drain(..common);
+    true
+}
+
+/// 单词按哪种规则转换大小写
+#[derive(Clone, Copy, PartialEq)]
+enum Words {
+    /// 保持原样，用于表名、列名
+    Keep,
+    /// 转换 KEYWORDS 中的单词
+    Keywords,
+    /// 括号外的 KEYWORDS 与 DDL_KEYWORDS 都转换，括号内保持原样
+    Ddl,
+}
+
+/// 建表语句中的一项定义
+enum Definition {
+    Column { name: String, ty: String, rest: String },
+    Other(String),
+}
+
+struct Formatter<'a> {
+    options: &'a FmtOptions,
+    dialect: Dialect,
+    /// 是否已有输出
+    started: bool,
+}
+
+impl Formatter<'_> {
+    /// 格式化一条语句及其前面的注释
+    fn statement(&mut self, text: &str) -> String {
+        let tokens = tokenize_with(text, &self.dialect.lexer_config());
+        let Some(first) = next_significant(&tokens, 0) else {
+            // 文件末尾只剩空白与注释
+            return self.comments(&tokens).0;
+        };
+        // `/*!40101 SET NAMES utf8 */;` 这类只有注释与分号的语句整体保留
+        let start = match tokens[first].is_punct(';') {
+            true => (0..first).rev().find(|&k| tokens[k].kind != TokenKind::Whitespace).unwrap_or(first),
+            false => first,
+        };
+        let (mut out, newlines) = self.comments(&tokens[..start]);
+        out.push_str(self.line_break(newlines));
+        out.push_str(&self.body(&tokens[start..]));
+        out
+    }
+
+    /// 文件以一个换行结尾
+    fn finish(&self) -> &'static str {
+        if self.started { "\n" } else { "" }
+    }
+
+    /// 语句前的注释各占一行，最多保留一个空行；紧跟在上一条语句之后的注释留在同一行。
+    /// 返回格式化结果与末尾空白中的换行数
+    fn comments(&mut self, tokens: &[Token]) -> (String, usize) {
+        let mut out = String::new();
+        let mut newlines = 0;
+        for t in tokens {
+            if t.kind == TokenKind::Whitespace {
+                newlines += t.text.matches('\n').count();
+                continue;
+            }
+            if newlines == 0 && self.started && out.is_empty() {
+                out.push(' ');
+            } else {
+                out.push_str(self.line_break(newlines));
+            }
+            out.push_str(t.text.trim_end());
+            newlines = 0;
+        }
+        (out, newlines)
+    }
+
+    /// 另起一行，原文有空行时保留一个空行；文件开头不换行
+    fn line_break(&mut self, newlines: usize) -> &'static str {
+        match (self.started, newlines) {
+            (false, _) => {
+                self.started = true;
+                ""
+            }
+            (true, 0 | 1) => "\n",
+            _ => "\n\n",
+        }
+    }
+
+    /// 语句本身，从第一个有效 token 开始
+    fn body(&self, tokens: &[Token]) -> String {
+        let text: String = tokens.iter().map(|t| t.text).collect();
+        let text = text.trim_end();
+        if self.dialect.has_plsql_blocks() && is_plsql_block(text, self.dialect) {
+            return text.to_string();
+        }
+        let last = tokens.iter().rposition(|t| t.kind != TokenKind::Whitespace).unwrap_or(0);
+        let terminated = tokens[last].is_punct(';');
+
+        // 语句中间有注释时只补上分号，不移动注释
+        let commented = tokens.iter().any(|t| matches!(t.kind, TokenKind::LineComment | TokenKind::BlockComment));
+        if commented {
+            let open = tokens[last].kind != TokenKind::LineComment && !terminated;
+            return if open { format!("{};", text) } else { text.to_string() };
+        }
+
+        let end = if terminated { last } else { last + 1 };
+        let out = self
+            .insert(tokens, end)
+            .or_else(|| self.create_table(tokens, end))
+            .unwrap_or_else(|| self.flat(tokens, 0..end, Words::Keywords, true));
+        out + ";"
+    }
+
+    /// `INSERT ... VALUES`：每个元组一行，多行时各列对齐；INSERT ALL 的每个 INTO 子句一行
+    fn insert(&self, tokens: &[Token], end: usize) -> Option<String> {
+        if !tokens[0].is_keyword("insert") {
+            return None;
+        }
+        let spans = insert_spans(&tokens[..end], self.dialect);
+        let (first, last) = (spans.first()?, spans.last()?);
+        let adjacent = spans.windows(2).all(|w| next_significant(tokens, w[0].end) == Some(w[1].insert));
+        if !adjacent || spans.iter().any(|s| s.rows.is_empty()) {
+            return None;
+        }
+        let rest = next_significant(tokens, last.end).filter(|&k| k < end).map(|k| self.flat(tokens, k..end, Words::Keywords, false));
+        let indent = " ".repeat(self.options.indent);
+
+        if first.all {
+            let mut out = self.flat(tokens, 0..first.insert, Words::Keywords, true);
+            for span in &spans {
+                let head = format!("{} {} {}", self.case(tokens[span.insert].text), self.target(tokens, span), self.case(tokens[span.values].text));
+                for row in &span.rows {
+                    out += &format!("\n{}{} {}", indent, head, tuple(&self.items(tokens, row), None));
+                }
+            }
+            if let Some(rest) = rest {
+                out += &format!("\n{}", rest);
+            }
+            return Some(out);
+        }
+        if spans.len() != 1 {
+            return None;
+        }
+
+        let head = self.flat(tokens, 0..first.table.0, Words::Keywords, true);
+        let head = format!("{} {} {}", head, self.target(tokens, first), self.case(tokens[first.values].text));
+        let rows: Vec<Vec<String>> = first.rows.iter().map(|row| self.items(tokens, row)).collect();
+        let mut out = match rows.as_slice() {
+            [row] => format!("{} {}", head, tuple(row, None)),
+            _ => {
+                let mut widths: Vec<usize> = Vec::new();
+                for row in &rows {
+                    for (j, item) in row.iter().enumerate() {
+                        let width = display_width(item);
+                        match widths.get_mut(j) {
+                            Some(w) => *w = (*w).max(width),
+                            None => widths.push(width),
+                        }
+                    }
+                }
+                let lines: Vec<String> = rows.iter().map(|row| format!("{}{}", indent, tuple(row, Some(&widths)))).collect();
+                format!("{}\n{}", head, lines.join(",\n"))
+            }
+        };
+        if let Some(rest) = rest {
+            out += if rows.len() == 1 { " " } else { "\n" };
+            out += &rest;
+        }
+        Some(out)
+    }
+
+    /// 表名与列列表
+    fn target(&self, tokens: &[Token], span: &InsertSpan) -> String {
+        let table = self.flat(tokens, span.table.0..span.table.1, Words::Keep, false);
+        match &span.columns {
+            Some(columns) => {
+                let names: Vec<String> = columns.items.iter().map(|&(a, b)| self.flat(tokens, a..b, Words::Keep, false)).collect();
+                format!("{} ({})", table, names.join(", "))
+            }
+            None => table,
+        }
+    }
+
+    fn items(&self, tokens: &[Token], row: &ListSpan) -> Vec<String> {
+        row.items.iter().map(|&(a, b)| self.flat(tokens, a..b, Words::Keywords, false)).collect()
+    }
+
+    /// `CREATE TABLE`：每项定义一行，列名与类型分别对齐
+    fn create_table(&self, tokens: &[Token], end: usize) -> Option<String> {
+        let words = leading_words(tokens, 5);
+        let table = words.iter().position(|(_, w)| w == "table")?;
+        let modifiers = ["global", "local", "temporary", "temp", "unlogged"];
+        if words[0].1 != "create" || !words[1..table].iter().all(|(_, w)| modifiers.contains(&w.as_str())) {
+            return None;
+        }
+        let name = skip_words(tokens, words[table].0 + 1, &["if", "not", "exists"])?;
+        let name_end = *name_parts(tokens, name).last()? + 1;
+        let open = next_significant(tokens, name_end).filter(|&k| tokens[k].is_punct('('))?;
+        let close = matching_paren(tokens, open).filter(|&k| k < end)?;
+        let items = split_top_level(tokens, open, close);
+        if items.is_empty() {
+            return None;
+        }
+
+        let definitions: Vec<Definition> = items.iter().map(|&(a, b)| self.definition(tokens, a, b)).collect();
+        let columns = || definitions.iter().filter_map(|d| match d {
+            Definition::Column { name, ty, rest } => Some((name, ty, rest)),
+            Definition::Other(_) => None,
+        });
+        let name_width = columns().map(|(name, _, _)| display_width(name)).max().unwrap_or(0);
+        let type_width = columns().filter(|(_, _, rest)| !rest.is_empty()).map(|(_, ty, _)| display_width(ty)).max().unwrap_or(0);
+
+        let indent = " ".repeat(self.options.indent);
+        let lines: Vec<String> = definitions
+            .iter()
+            .map(|d| match d {
+                Definition::Column { name, ty, rest } => {
+                    let line = format!("{}{} {} {}", indent, pad(name, name_width), pad(ty, type_width), rest);
+                    line.trim_end().to_string()
+                }
+                Definition::Other(text) => format!("{}{}", indent, text),
+            })
+            .collect();
+
+        let mut out = format!(
+            "{} {} (\n{}\n)",
+            self.flat(tokens, 0..name, Words::Keywords, true),
+            self.flat(tokens, name..name_end, Words::Keep, false),
+            lines.join(",\n")
+        );
+        if next_significant(tokens, close + 1).is_some_and(|k| k < end) {
+            out += " ";
+            out += &self.flat(tokens, close + 1..end, Words::Ddl, false);
+        }
+        Some(out)
+    }
+
+    /// 列定义拆为列名、类型（含括号中的长度）与其余部分
+    fn definition(&self, tokens: &[Token], a: usize, b: usize) -> Definition {
+        let Some(first) = next_significant(tokens, a).filter(|&k| k < b) else {
+            return Definition::Other(String::new());
+        };
+        if CONSTRAINT_STARTS.iter().any(|w| tokens[first].is_keyword(w)) {
+            return Definition::Other(self.flat(tokens, a..b, Words::Ddl, false));
+        }
+        let name = tokens[first].text.to_string();
+        let Some(ty) = next_significant(tokens, first + 1).filter(|&k| k < b) else {
+            return Definition::Column { name, ty: String::new(), rest: String::new() };
+        };
+        let ty_end = next_significant(tokens, ty + 1)
+            .filter(|&k| k < b && tokens[k].is_punct('('))
+            .and_then(|k| matching_paren(tokens, k))
+            .map_or(ty + 1, |k| k + 1);
+        Definition::Column {
+            name,
+            ty: self.flat(tokens, ty..ty_end, Words::Ddl, false),
+            rest: self.flat(tokens, ty_end..b, Words::Ddl, false),
+        }
+    }
+
+    /// 把 range 内的 token 排成一行：空白合并为一个空格，逗号后一个空格，
+    /// 括号内侧与逗号、分号前不留空格。lead 为真时第一个单词总按关键字处理
+    fn flat(&self, tokens: &[Token], range: Range<usize>, words: Words, lead: bool) -> String {
+        let mut out = String::new();
+        let mut space = false;
+        let mut prev: Option<&Token> = None;
+        let mut depth = 0usize;
+        for k in range {
+            let t = &tokens[k];
+            if !t.is_significant() {
+                space = true;
+                continue;
+            }
+            if let Some(p) = prev {
+                let tight = t.is_punct(',') || t.is_punct(')') || t.is_punct(';') || p.is_punct('(');
+                if !tight && (space || p.is_punct(',')) {
+                    out.push(' ');
+                }
+            }
+            if t.is_punct(')') {
+                depth = depth.saturating_sub(1);
+            }
+            let listed = |list: &[&str]| list.iter().any(|w| t.is_keyword(w));
+            let keyword = match words {
+                Words::Keep => false,
+                Words::Keywords => listed(KEYWORDS) || (lead && prev.is_none() && t.kind == TokenKind::Word),
+                Words::Ddl => depth == 0 && (listed(KEYWORDS) || listed(DDL_KEYWORDS)),
+            };
+            if keyword && !qualified(tokens, k) {
+                out.push_str(&self.case(t.text));
+            } else {
+                out.push_str(t.text);
+            }
+            if t.is_punct('(') {
+                depth += 1;
+            }
+            space = false;
+            prev = Some(t);
+        }
+        out
+    }
+
+    fn case(&self, word: &str) -> String {
+        match self.options.keyword_case {
+            KeywordCase::Upper => word.to_ascii_uppercase(),
+            KeywordCase::Lower => word.to_ascii_lowercase(),
+        }
+    }
+}
+
+/// 单词是否为 `a.b` 限定名的一部分
+fn qualified(tokens: &[Token], k: usize) -> bool {
+    let dot = |p: Option<usize>| p.is_some_and(|p| tokens[p].is_punct('.'));
+    dot(k.checked_sub(1).and_then(|p| prev_significant(tokens, p))) || dot(next_significant(tokens, k + 1))
+}
+
+/// 元组文本；给出各列宽度时在逗号后补齐空格，使各行的列对齐
+fn tuple(items: &[String], widths: Option<&[usize]>) -> String {
+    let mut out = String::from("(");
+    for (j, item) in items.iter().enumerate() {
+        out.push_str(item);
+        if j + 1 == items.len() {
+            break;
+        }
+        out.push_str(", ");
+        if let Some(&width) = widths.and_then(|w| w.get(j)).filter(|&&w| w <= ALIGN_LIMIT) {
+            out.extend(std::iter::repeat_n(' ', width - display_width(item)));
+        }
+    }
+    out.push(')');
+    out
+}
+
+/// 补齐空格到指定显示宽度
+fn pad(text: &str, width: usize) -> String {
+    format!("{}{}", text, " ".repeat(width.saturating_sub(display_width(text))))
+}
+
+/// 等宽字体下的显示宽度，中日韩文字与全角符号占两列
+fn display_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| match c as u32 {
+            0x1100..=0x115F | 0x2E80..=0x303E | 0x3041..=0x33FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xA000..=0xA4CF
+            | 0xAC00..=0xD7A3 | 0xF900..=0xFAFF | 0xFE30..=0xFE4F | 0xFF00..=0xFF60 | 0xFFE0..=0xFFE6 | 0x20000..=0x3FFFD => 2,
+            _ => 1,
+        })
+        .sum()
+}
@@ -0,0 +1,150 @@
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde_json::Value;
+
+use super::cell::{Cell, ColumnType};
+use super::csv::CsvReader;
+use super::dialect::Dialect;
+use super::encoding::{input_encoding, open_decoded, output_encoding, FileEncoding};
+use super::export::DataFormat;
+use super::stream::{write_generated, Output};
+
+/// 无需加引号的标识符
+static PLAIN_IDENT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[A-Za-z_][A-Za-z0-9_$]*$").unwrap());
+
+/// 导入选项
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    /// 目标表名，可带 schema 前缀
+    pub table: String,
+    /// 指定类型的列，其余列按 auto 处理
+    pub types: Vec<(String, ColumnType)>,
+    pub dialect: Dialect,
+}
+
+/// 解析 `列名=类型` 形式的列类型声明
+pub fn parse_column_types(specs: &[String]) -> Result<Vec<(String, ColumnType)>, String> {
+    specs
+        .iter()
+        .map(|spec| {
+            let (column, ty) = spec.split_once('=').ok_or_else(|| format!("列类型格式应为 列名=类型: {}", spec))?;
+            let ty = <ColumnType as clap::ValueEnum>::from_str(ty.trim(), true).map_err(|_| format!("未知的列类型: {}", ty))?;
+            Ok((column.trim().to_string(), ty))
+        })
+        .collect()
+}
+
+/// 按需给标识符加引号，schema 前缀分别处理
+fn ident(name: &str, dialect: Dialect) -> String {
+    name.split('.')
+        .map(|part| if PLAIN_IDENT.is_match(part) { part.to_string() } else { dialect.quote_ident(part) })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// 读取 CSV 或 JSON 文件，每行生成一条 INSERT，按 output 写入 SQL 文件或输出到标准输出，返回行数。
+/// SQL 文件与数据文件编码相同（不带 BOM），或按 `--output-encoding` 保存
+pub fn import_file(data_path: &Path, output: &Output, options: &ImportOptions) -> Result<usize, String> {
+    let format = DataFormat::from_path(data_path).ok_or_else(|| "仅支持 .csv 或 .json 文件".to_string())?;
+    let encoding = input_encoding(data_path)?;
+    let reader = BufReader::new(open_decoded(data_path, encoding)?);
+    let mut rows = 0usize;
+
+    write_generated(&[data_path], output, output_encoding(FileEncoding { bom: false, ..encoding }), |writer| {
+        match format {
+            DataFormat::Csv => {
+                let mut records = CsvReader::new(reader);
+                let header = records.next().ok_or_else(|| "文件为空".to_string())??;
+                let columns: Vec<String> = header.into_iter().map(|(name, _)| name).collect();
+                let insert = InsertWriter::new(&columns, options)?;
+                while let Some(record) = records.next() {
+                    let record = record?;
+                    // 跳过空行
+                    if record.len() == 1 && record[0].0.is_empty() && !record[0].1 {
+                        continue;
+                    }
+                    if record.len() != columns.len() {
+                        return Err(format!("第 {} 行: 有 {} 列，表头为 {} 列", records.line(), record.len(), columns.len()));
+                    }
+                    let cells: Vec<Cell> = record.iter().map(|(field, quoted)| Cell::from_csv(field, *quoted)).collect();
+                    let sql = insert.statement(&cells).map_err(|e| format!("第 {} 行: {}", records.line(), e))?;
+                    writer.write_all(sql.as_bytes()).map_err(|e| format!("写入内容失败: {}", e))?;
+                    rows += 1;
+                }
+            }
+            DataFormat::Json => {
+                let value: Value = serde_json::from_reader(reader).map_err(|e| format!("解析 JSON 失败: {}", e))?;
+                let Value::Array(items) = value else {
+                    return Err("JSON 顶层应为对象数组".to_string());
+                };
+                // 列为所有对象键的并集，按首次出现的顺序；缺少的键视为 NULL
+                let mut columns: Vec<String> = Vec::new();
+                for item in &items {
+                    let Value::Object(map) = item else { return Err("JSON 顶层应为对象数组".to_string()) };
+                    for key in map.keys() {
+                        if !columns.contains(key) {
+                            columns.push(key.clone());
+                        }
+                    }
+                }
+                let insert = InsertWriter::new(&columns, options)?;
+                for (n, item) in items.iter().enumerate() {
+                    let sql = columns
+                        .iter()
+                        .map(|c| item.get(c).map(Cell::from_json).unwrap_or(Ok(Cell::Null)))
+                        .collect::<Result<Vec<_>, _>>()
+                        .and_then(|cells| insert.statement(&cells))
+                        .map_err(|e| format!("第 {} 个对象: {}", n + 1, e))?;
+                    writer.write_all(sql.as_bytes()).map_err(|e| format!("写入内容失败: {}", e))?;
+                    rows += 1;
+                }
+            }
+        }
+        Ok(())
+    })?;
+    Ok(rows)
+}
+
+/// 生成单行 INSERT 语句
+struct InsertWriter {
+    head: String,
+    types: Vec<ColumnType>,
+    dialect: Dialect,
+}
+
+impl InsertWriter {
+    fn new(columns: &[String], options: &ImportOptions) -> Result<Self, String> {
+        if columns.is_empty() {
+            return Err("没有任何列".to_string());
+        }
+        if let Some((unknown, _)) = options.types.iter().find(|(c, _)| !columns.iter().any(|col| col.eq_ignore_ascii_case(c))) {
+            return Err(format!("数据中没有列 {}", unknown));
+        }
+        let types = columns
+            .iter()
+            .map(|col| {
+                options
+                    .types
+                    .iter()
+                    .find(|(c, _)| c.eq_ignore_ascii_case(col))
+                    .map(|(_, ty)| *ty)
+                    .unwrap_or(ColumnType::Auto)
+            })
+            .collect();
+        let names: Vec<String> = columns.iter().map(|c| ident(c, options.dialect)).collect();
+        let head = format!("INSERT INTO {} ({}) VALUES ", ident(&options.table, options.dialect), names.join(", "));
+        Ok(InsertWriter { head, types, dialect: options.dialect })
+    }
+
+    fn statement(&self, cells: &[Cell]) -> Result<String, String> {
+        let values = cells
+            .iter()
+            .zip(&self.types)
+            .map(|(cell, ty)| cell.to_sql(*ty, self.dialect))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(format!("{}({});\n", self.head, values.join(", ")))
+    }
+}
@@ -0,0 +1,430 @@
+use std::collections::HashSet;
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use super::cell::Cell;
+use super::dialect::Dialect;
+use super::fmt::{format_sql, FmtOptions};
+use super::insert::{insert_spans, item_text};
+use super::lexer::tokenize_with;
+use super::stream::{open_statements, RawStatement, StatementReader};
+
+static INTEGER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^-?\d+$").unwrap());
+static DECIMAL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^-?(\d*)\.(\d+)$").unwrap());
+static DATE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\d{4}-\d{2}-\d{2}$").unwrap());
+static DATETIME: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\d{4}-\d{2}-\d{2}[ T]\d{2}:\d{2}(:\d{2}(\.\d+)?)?$").unwrap());
+
+/// 取得当前时间或把字符串转为时间的函数，出现在值中时推断为日期时间列
+const DATETIME_FUNCTIONS: &[&str] =
+    &["now(", "current_timestamp", "localtimestamp", "sysdate", "systimestamp", "to_date(", "to_timestamp(", "str_to_date("];
+/// 字符串长度向上取整的档位，超过最后一档时使用大文本类型
+const LENGTHS: &[usize] = &[16, 32, 64, 128, 255, 512, 1024, 2000, 4000];
+/// 至少两个值且所有值等长、不超过该长度时推断为 CHAR
+const FIXED_LENGTH: usize = 10;
+/// 寻找复合候选键时参与组合的列数上限
+const PAIR_COLUMNS: usize = 6;
+
+/// 由数据推断出的值类型，按可容纳的范围合并
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ValueKind {
+    Bool,
+    Integer,
+    Decimal,
+    Float,
+    Date,
+    Datetime,
+    Text,
+}
+
+impl ValueKind {
+    /// 同一列出现不同类型的值时取能容纳两者的类型
+    fn merge(self, other: ValueKind) -> ValueKind {
+        use ValueKind::*;
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Bool, Integer) | (Integer, Bool) => Integer,
+            (Bool | Integer | Decimal, Decimal) | (Decimal, Bool | Integer) => Decimal,
+            (Bool | Integer | Decimal | Float, Float) | (Float, Bool | Integer | Decimal) => Float,
+            (Date, Datetime) | (Datetime, Date) => Datetime,
+            _ => Text,
+        }
+    }
+}
+
+/// 推断出的列
+#[derive(Debug, Clone)]
+pub struct InferredColumn {
+    /// 去掉引号的列名
+    pub name: String,
+    /// 列名原文
+    pub text: String,
+    pub data_type: String,
+    pub nullable: bool,
+}
+
+/// 推断出的表结构
+#[derive(Debug, Clone)]
+pub struct InferredTable {
+    /// 去掉引号的表名，保留 schema 前缀
+    pub name: String,
+    /// 表名原文
+    pub text: String,
+    pub rows: usize,
+    pub columns: Vec<InferredColumn>,
+    /// 数据中取值非空且互不相同的列或列组合
+    pub candidate_keys: Vec<Vec<String>>,
+    /// 从候选键中选出的主键
+    pub primary_key: Option<Vec<String>>,
+    /// 需要人工确认的地方，例如只有 NULL 的列
+    pub notes: Vec<String>,
+}
+
+/// 推断结果，按表首次出现的顺序排列
+#[derive(Debug, Default)]
+pub struct InferredSchema {
+    pub tables: Vec<InferredTable>,
+    /// (文件, 行号, 说明) 形式的未处理原因
+    pub warnings: Vec<(String, usize, String)>,
+}
+
+impl InferredSchema {
+    /// 生成 CREATE TABLE 脚本，每张表前以注释列出行数、候选键与需要确认的地方
+    pub fn render(&self, header: &str, dialect: Dialect) -> String {
+        let mut sql = format!("-- {}\n", header);
+        for table in &self.tables {
+            let keys: Vec<String> = table.candidate_keys.iter().map(|k| k.join(" + ")).collect();
+            let keys = if keys.is_empty() { "无".to_string() } else { keys.join("，") };
+            sql.push_str(&format!("\n-- 表 {}：{} 行，候选键：{}\n", table.name, table.rows, keys));
+            for note in &table.notes {
+                sql.push_str(&format!("-- [注意] {}\n", note));
+            }
+
+            let mut definitions: Vec<String> = table
+                .columns
+                .iter()
+                .map(|c| format!("{} {}{}", c.text, c.data_type, if c.nullable { "" } else { " NOT NULL" }))
+                .collect();
+            if let Some(key) = &table.primary_key {
+                let texts: Vec<&str> = key.iter().filter_map(|k| table.columns.iter().find(|c| &c.name == k)).map(|c| c.text.as_str()).collect();
+                definitions.push(format!("PRIMARY KEY ({})", texts.join(", ")));
+            }
+            sql.push_str(&format!("CREATE TABLE {} ({});\n", table.text, definitions.join(", ")));
+        }
+        format_sql(&sql, &FmtOptions::default(), dialect)
+    }
+}
+
+/// 一列的统计
+struct ColumnStats {
+    name: String,
+    text: String,
+    /// 只出现过 NULL 时为 None
+    kind: Option<ValueKind>,
+    /// 非 NULL 值的个数
+    values: usize,
+    /// 整数部分的最大位数
+    digits: usize,
+    /// 小数部分的最大位数
+    scale: usize,
+    /// 值的最短与最长字符数、最长字节数
+    min_chars: usize,
+    max_chars: usize,
+    max_bytes: usize,
+    /// 含无法识别类型的表达式
+    expression: bool,
+}
+
+impl ColumnStats {
+    fn new(name: String, text: String) -> Self {
+        ColumnStats {
+            name,
+            text,
+            kind: None,
+            values: 0,
+            digits: 0,
+            scale: 0,
+            min_chars: usize::MAX,
+            max_chars: 0,
+            max_bytes: 0,
+            expression: false,
+        }
+    }
+
+    fn add(&mut self, cell: &Cell, dialect: Dialect) {
+        let kind = match cell {
+            Cell::Null => return,
+            // Oracle 与达梦把空字符串当作 NULL
+            Cell::Text(s) if s.is_empty() && dialect.has_plsql_blocks() => return,
+            Cell::Number(n) => self.number(n),
+            Cell::Text(s) if DATE.is_match(s) => ValueKind::Date,
+            Cell::Text(s) if DATETIME.is_match(s) => ValueKind::Datetime,
+            Cell::Text(_) => ValueKind::Text,
+            Cell::Raw(expr) => self.expression(expr),
+        };
+        self.values += 1;
+        self.kind = Some(self.kind.map_or(kind, |k| k.merge(kind)));
+        if let Cell::Number(v) | Cell::Text(v) = cell {
+            let chars = v.chars().count();
+            self.min_chars = self.min_chars.min(chars);
+            self.max_chars = self.max_chars.max(chars);
+            self.max_bytes = self.max_bytes.max(v.len());
+        }
+    }
+
+    fn number(&mut self, n: &str) -> ValueKind {
+        let unsigned = n.trim_start_matches('-');
+        if INTEGER.is_match(n) {
+            self.digits = self.digits.max(unsigned.trim_start_matches('0').len().max(1));
+            ValueKind::Integer
+        } else if let Some(caps) = DECIMAL.captures(n) {
+            self.digits = self.digits.max(caps[1].trim_start_matches('0').len().max(1));
+            self.scale = self.scale.max(caps[2].len());
+            ValueKind::Decimal
+        } else {
+            ValueKind::Float
+        }
+    }
+
+    fn expression(&mut self, expr: &str) -> ValueKind {
+        let lower = expr.to_lowercase();
+        if lower == "true" || lower == "false" {
+            ValueKind::Bool
+        } else if lower.starts_with("date ") || lower.starts_with("date'") {
+            ValueKind::Date
+        } else if lower.starts_with("timestamp") || DATETIME_FUNCTIONS.iter().any(|f| lower.contains(f)) {
+            ValueKind::Datetime
+        } else {
+            self.expression = true;
+            ValueKind::Text
+        }
+    }
+
+    /// 按方言生成列类型
+    fn data_type(&self, dialect: Dialect) -> String {
+        let oracle = dialect.has_plsql_blocks();
+        let mysql = dialect == Dialect::Mysql;
+        match self.kind {
+            None => text_type(255, dialect),
+            Some(ValueKind::Bool) => match dialect {
+                Dialect::Mysql => "TINYINT(1)".to_string(),
+                Dialect::Postgres => "BOOLEAN".to_string(),
+                _ => "NUMBER(1)".to_string(),
+            },
+            Some(ValueKind::Integer) if self.digits <= 18 => {
+                let big = self.digits > 9;
+                match dialect {
+                    Dialect::Mysql | Dialect::Postgres if big => "BIGINT".to_string(),
+                    Dialect::Mysql => "INT".to_string(),
+                    Dialect::Postgres => "INTEGER".to_string(),
+                    _ => format!("NUMBER({})", if big { 19 } else { 10 }),
+                }
+            }
+            Some(ValueKind::Integer | ValueKind::Decimal) => {
+                let precision = (self.digits + self.scale).clamp(1, if mysql { 65 } else { 38 });
+                let scale = self.scale.min(precision);
+                let name = match dialect {
+                    Dialect::Mysql => "DECIMAL",
+                    Dialect::Postgres => "NUMERIC",
+                    _ => "NUMBER",
+                };
+                match scale {
+                    0 if oracle => format!("{}({})", name, precision),
+                    _ => format!("{}({}, {})", name, precision, scale),
+                }
+            }
+            Some(ValueKind::Float) => match dialect {
+                Dialect::Postgres => "DOUBLE PRECISION".to_string(),
+                Dialect::Oracle => "BINARY_DOUBLE".to_string(),
+                _ => "DOUBLE".to_string(),
+            },
+            Some(ValueKind::Date) => "DATE".to_string(),
+            Some(ValueKind::Datetime) => match dialect {
+                Dialect::Mysql => "DATETIME".to_string(),
+                Dialect::Oracle => "DATE".to_string(),
+                _ => "TIMESTAMP".to_string(),
+            },
+            Some(ValueKind::Text) => {
+                // Oracle 与达梦默认按字节计算长度
+                let length = if oracle { self.max_bytes } else { self.max_chars };
+                if self.values >= 2 && self.min_chars == self.max_chars && (1..=FIXED_LENGTH).contains(&self.max_chars) && !self.expression {
+                    format!("CHAR({})", length)
+                } else {
+                    text_type(length, dialect)
+                }
+            }
+        }
+    }
+}
+
+/// 按最长长度生成字符串类型，长度向上取整到常用档位
+fn text_type(length: usize, dialect: Dialect) -> String {
+    match LENGTHS.iter().find(|&&l| l >= length) {
+        Some(length) if dialect.has_plsql_blocks() => format!("VARCHAR2({})", length),
+        Some(length) => format!("VARCHAR({})", length),
+        None => match dialect {
+            Dialect::Mysql if length <= 65535 / 4 => "TEXT".to_string(),
+            Dialect::Mysql => "LONGTEXT".to_string(),
+            Dialect::Postgres => "TEXT".to_string(),
+            _ => "CLOB".to_string(),
+        },
+    }
+}
+
+/// 一张表的统计
+struct TableStats {
+    name: String,
+    text: String,
+    columns: Vec<ColumnStats>,
+    /// 每行按 columns 顺序的规范化取值，缺少的列与 NULL 为 None
+    rows: Vec<Vec<Option<String>>>,
+}
+
+impl TableStats {
+    fn column(&mut self, name: &str, text: String) -> usize {
+        match self.columns.iter().position(|c| c.name.eq_ignore_ascii_case(name)) {
+            Some(pos) => pos,
+            None => {
+                self.columns.push(ColumnStats::new(name.to_string(), text));
+                self.columns.len() - 1
+            }
+        }
+    }
+
+    /// 所有行中都有取值的列
+    fn complete(&self, c: usize) -> bool {
+        self.rows.iter().all(|row| row.get(c).is_some_and(|v| v.is_some()))
+    }
+
+    /// 取值互不相同的列组合
+    fn unique(&self, columns: &[usize]) -> bool {
+        let mut seen = HashSet::new();
+        self.rows.iter().all(|row| seen.insert(columns.iter().map(|&c| row.get(c).and_then(|v| v.as_deref())).collect::<Vec<_>>()))
+    }
+
+    fn finish(self, dialect: Dialect) -> InferredTable {
+        let rows = self.rows.len();
+        let complete: Vec<usize> = (0..self.columns.len()).filter(|&c| self.complete(c)).collect();
+        // 只有一行数据时任何列都互不相同，不作为候选键
+        let mut keys: Vec<Vec<usize>> =
+            if rows < 2 { Vec::new() } else { complete.iter().filter(|&&c| self.unique(&[c])).map(|&c| vec![c]).collect() };
+        if keys.is_empty() && rows >= 2 {
+            let pool = &complete[..complete.len().min(PAIR_COLUMNS)];
+            let pair = pool.iter().enumerate().flat_map(|(i, &a)| pool[i + 1..].iter().map(move |&b| vec![a, b])).find(|pair| self.unique(pair));
+            keys.extend(pair);
+        }
+        let primary = keys
+            .iter()
+            .find(|k| k.len() == 1 && self.columns[k[0]].name.eq_ignore_ascii_case("id"))
+            .or_else(|| keys.iter().find(|k| k.len() == 1 && self.columns[k[0]].kind == Some(ValueKind::Integer)))
+            .or(keys.first());
+        let names = |key: &Vec<usize>| key.iter().map(|&c| self.columns[c].name.clone()).collect::<Vec<_>>();
+
+        let mut notes = Vec::new();
+        for column in &self.columns {
+            if column.kind.is_none() {
+                notes.push(format!("列 {} 只有 NULL，类型待定", column.name));
+            } else if column.expression {
+                notes.push(format!("列 {} 含无法识别的表达式，按字符串推断", column.name));
+            }
+        }
+        InferredTable {
+            primary_key: primary.map(names),
+            candidate_keys: keys.iter().map(names).collect(),
+            columns: self
+                .columns
+                .iter()
+                .enumerate()
+                .map(|(c, stats)| InferredColumn {
+                    name: stats.name.clone(),
+                    text: stats.text.clone(),
+                    data_type: stats.data_type(dialect),
+                    nullable: !complete.contains(&c),
+                })
+                .collect(),
+            name: self.name,
+            text: self.text,
+            rows,
+            notes,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Collector {
+    tables: Vec<TableStats>,
+    warnings: Vec<(String, usize, String)>,
+}
+
+impl Collector {
+    fn statement(&mut self, file: &str, statement: &RawStatement, dialect: Dialect) {
+        let tokens = tokenize_with(&statement.text, &dialect.lexer_config());
+        for insert in insert_spans(&tokens, dialect) {
+            let at = |k: usize| statement.line + tokens[k].line - 1;
+            let name = insert.table_name(&tokens);
+            let columns = insert.column_names(&tokens);
+            if columns.is_empty() {
+                self.warnings.push((file.to_string(), at(insert.insert), format!("{}: 缺少列列表，跳过该语句", name)));
+                continue;
+            }
+
+            let t = match self.tables.iter().position(|t| t.name.eq_ignore_ascii_case(&name)) {
+                Some(t) => t,
+                None => {
+                    let text = tokens[insert.table.0..insert.table.1].iter().map(|t| t.text).collect();
+                    self.tables.push(TableStats { name: name.clone(), text, columns: Vec::new(), rows: Vec::new() });
+                    self.tables.len() - 1
+                }
+            };
+            let table = &mut self.tables[t];
+            let texts = insert.columns.iter().flat_map(|c| &c.items).map(|&(a, b)| item_text(&tokens, a, b));
+            let positions: Vec<usize> = columns.iter().zip(texts).map(|(column, text)| table.column(column, text)).collect();
+
+            for row in &insert.rows {
+                if row.items.len() != columns.len() {
+                    self.warnings.push((file.to_string(), at(row.open), format!("{}: 值个数与列数不一致，跳过该行", name)));
+                    continue;
+                }
+                let mut values = vec![None; table.columns.len()];
+                for (&p, &(a, b)) in positions.iter().zip(&row.items) {
+                    let cell = Cell::from_item(&tokens[a..b], dialect);
+                    table.columns[p].add(&cell, dialect);
+                    let empty = matches!(&cell, Cell::Text(s) if s.is_empty()) && dialect.has_plsql_blocks();
+                    if cell != Cell::Null && !empty {
+                        values[p] = Some(cell.normalized());
+                    }
+                }
+                table.rows.push(values);
+            }
+        }
+    }
+
+    fn finish(self, dialect: Dialect) -> InferredSchema {
+        InferredSchema { tables: self.tables.into_iter().map(|t| t.finish(dialect)).collect(), warnings: self.warnings }
+    }
+}
+
+/// 扫描若干 SQL 文件中的 INSERT，按表推断列类型、长度、可空性与候选键；
+/// 多个文件中的同一张表合并推断
+pub fn infer_schema(paths: &[PathBuf], dialect: Dialect) -> Result<InferredSchema, String> {
+    let mut collector = Collector::default();
+    for path in paths {
+        let file = path.display().to_string();
+        let (reader, _) = open_statements(path, dialect)?;
+        for statement in reader {
+            collector.statement(&file, &statement?, dialect);
+        }
+    }
+    Ok(collector.finish(dialect))
+}
+
+/// 由 SQL 文本推断表结构
+pub fn infer_schema_sql(sql: &str, dialect: Dialect) -> InferredSchema {
+    let mut collector = Collector::default();
+    for statement in StatementReader::new(Cursor::new(sql), dialect).flatten() {
+        collector.statement("", &statement, dialect);
+    }
+    collector.finish(dialect)
+}
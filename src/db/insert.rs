@@ -0,0 +1,227 @@
+use super::dialect::Dialect;
+use super::lexer::{matching_paren, next_significant, split_top_level, Token, TokenKind};
+
+/// INSERT 与表名之间可能出现的修饰关键字
+const INSERT_MODIFIERS: &[&str] = &["into", "ignore", "low_priority", "delayed", "high_priority"];
+
+/// 括号列表（列列表或值元组）在 token 序列中的位置
+#[derive(Debug, Clone)]
+pub struct ListSpan {
+    pub open: usize,
+    pub close: usize,
+    /// 每一项的 token 下标范围（不含分隔逗号）
+    pub items: Vec<(usize, usize)>,
+}
+
+/// `INSERT INTO t (cols) VALUES (...), (...)` 的结构，均为 token 下标
+#[derive(Debug, Clone)]
+pub struct InsertSpan {
+    /// INSERT 关键字；INSERT ALL 的子句为该子句的 INTO 关键字
+    pub insert: usize,
+    /// 是否为 Oracle `INSERT ALL` 中的一个 INTO 子句
+    pub all: bool,
+    /// 表名所占的 token 范围，可能带 schema 前缀
+    pub table: (usize, usize),
+    pub columns: Option<ListSpan>,
+    pub values: usize,
+    pub rows: Vec<ListSpan>,
+    /// 最后一个元组之后的 token 下标
+    pub end: usize,
+}
+
+impl InsertSpan {
+    /// 去掉引号后的表名，保留 schema 前缀
+    pub fn table_name(&self, tokens: &[Token]) -> String {
+        qualified_name(&tokens[self.table.0..self.table.1])
+    }
+
+    /// 去掉引号后的列名；没有列列表时为空
+    pub fn column_names(&self, tokens: &[Token]) -> Vec<String> {
+        match &self.columns {
+            Some(cols) => cols.items.iter().map(|&(a, b)| item_ident(tokens, a, b).unwrap_or_else(|| item_text(tokens, a, b))).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// 按名称（不区分大小写）查找列位置
+    pub fn column_index(&self, tokens: &[Token], name: &str) -> Option<usize> {
+        self.column_names(tokens).iter().position(|c| c.eq_ignore_ascii_case(name))
+    }
+
+    /// 主键列在列列表中的位置，缺少列列表或主键列时返回原因
+    pub fn key_positions(&self, tokens: &[Token], keys: &[String]) -> Result<Vec<usize>, String> {
+        if self.columns.is_none() {
+            return Err("缺少列列表，无法确定主键位置".to_string());
+        }
+        keys.iter()
+            .map(|key| self.column_index(tokens, key).ok_or_else(|| format!("列列表中缺少主键列 {}", key)))
+            .collect()
+    }
+}
+
+/// 语句中所有 `INSERT ... VALUES` 子句，INSERT ALL 的每个 INTO 子句各为一项
+pub fn insert_spans(tokens: &[Token], dialect: Dialect) -> Vec<InsertSpan> {
+    let mut spans = Vec::new();
+    let mut i = 0usize;
+    while i < tokens.len() {
+        let inserts = if tokens[i].is_keyword("insert") { parse_inserts(tokens, i, dialect) } else { Vec::new() };
+        match inserts.last() {
+            Some(last) => i = last.end,
+            None => i += 1,
+        }
+        spans.extend(inserts);
+    }
+    spans
+}
+
+/// 解析从 insert 下标开始的 INSERT 语句。`INSERT ALL` 的每个 INTO 子句各返回一项；
+/// 不是 `INSERT ... VALUES` 形式时返回空
+pub fn parse_inserts(tokens: &[Token], insert: usize, dialect: Dialect) -> Vec<InsertSpan> {
+    let Some(k) = next_significant(tokens, insert + 1) else { return Vec::new() };
+    if !(dialect.has_insert_all() && tokens[insert].is_keyword("insert") && tokens[k].is_keyword("all")) {
+        return parse_insert(tokens, insert).into_iter().collect();
+    }
+
+    let mut spans = Vec::new();
+    let mut next = next_significant(tokens, k + 1);
+    while let Some(into) = next.filter(|&i| tokens[i].is_keyword("into")) {
+        let Some(span) = parse_clause(tokens, into, into + 1, true) else { break };
+        next = next_significant(tokens, span.end);
+        spans.push(span);
+    }
+    spans
+}
+
+/// 解析从 insert 下标开始的单表 INSERT 语句，不是 `INSERT ... VALUES` 形式时返回 None
+pub fn parse_insert(tokens: &[Token], insert: usize) -> Option<InsertSpan> {
+    if !tokens.get(insert)?.is_keyword("insert") {
+        return None;
+    }
+    parse_clause(tokens, insert, insert + 1, false)
+}
+
+/// 从 from 开始解析 `[INTO] 表名 [(列)] VALUES (...)[, (...)]`
+fn parse_clause(tokens: &[Token], insert: usize, from: usize, all: bool) -> Option<InsertSpan> {
+    let mut k = next_significant(tokens, from)?;
+    while INSERT_MODIFIERS.iter().any(|m| tokens[k].is_keyword(m)) {
+        k = next_significant(tokens, k + 1)?;
+    }
+
+    // 表名：标识符与 '.' 交替
+    let table_start = k;
+    let mut table_end = k;
+    loop {
+        let t = &tokens[k];
+        if !matches!(t.kind, TokenKind::Word | TokenKind::QuotedIdent) || is_values_keyword(t) {
+            break;
+        }
+        table_end = k + 1;
+        match next_significant(tokens, k + 1) {
+            Some(dot) if tokens[dot].is_punct('.') => k = next_significant(tokens, dot + 1)?,
+            _ => break,
+        }
+    }
+    if table_end == table_start {
+        return None;
+    }
+
+    let mut k = next_significant(tokens, table_end)?;
+    let columns = if tokens[k].is_punct('(') {
+        let close = matching_paren(tokens, k)?;
+        let span = ListSpan { open: k, close, items: split_top_level(tokens, k, close) };
+        k = next_significant(tokens, close + 1)?;
+        Some(span)
+    } else {
+        None
+    };
+
+    if !is_values_keyword(&tokens[k]) {
+        return None;
+    }
+    let values = k;
+    let mut rows = Vec::new();
+    let mut end = values + 1;
+    while let Some(open) = next_significant(tokens, end).filter(|&p| tokens[p].is_punct('(')) {
+        let close = matching_paren(tokens, open)?;
+        rows.push(ListSpan { open, close, items: split_top_level(tokens, open, close) });
+        end = close + 1;
+        match next_significant(tokens, end) {
+            Some(comma) if tokens[comma].is_punct(',') => end = comma + 1,
+            _ => break,
+        }
+    }
+    if rows.is_empty() {
+        return None;
+    }
+    // 末尾逗号不属于元组
+    let end = rows.last().map(|r| r.close + 1).unwrap_or(end);
+
+    Some(InsertSpan { insert, all, table: (table_start, table_end), columns, values, rows, end })
+}
+
+fn is_values_keyword(t: &Token) -> bool {
+    t.is_keyword("values") || t.is_keyword("value")
+}
+
+/// 去掉引号拼接限定名，例如 `` `db`.`t` `` -> db.t
+pub fn qualified_name(tokens: &[Token]) -> String {
+    tokens
+        .iter()
+        .filter(|t| t.is_significant())
+        .map(|t| if t.kind == TokenKind::QuotedIdent { t.ident_name() } else { t.text.to_string() })
+        .collect()
+}
+
+/// 列表项的原文（去掉首尾空白与注释）
+pub fn item_text(tokens: &[Token], start: usize, end: usize) -> String {
+    let Some(first) = next_significant(tokens, start).filter(|&f| f < end) else { return String::new() };
+    let last = (first..end).rev().find(|&k| tokens[k].is_significant()).unwrap_or(first);
+    tokens[first..=last].iter().map(|t| t.text).collect()
+}
+
+/// 列表项若为单个标识符，返回去掉引号后的名称
+pub fn item_ident(tokens: &[Token], start: usize, end: usize) -> Option<String> {
+    let mut significant = tokens[start..end].iter().filter(|t| t.is_significant());
+    match (significant.next(), significant.next()) {
+        (Some(t), None) if matches!(t.kind, TokenKind::Word | TokenKind::QuotedIdent) => Some(t.ident_name()),
+        _ => None,
+    }
+}
+
+/// 标记删除列表中指定位置的项及相邻逗号，保持剩余项的原有格式
+pub fn remove_items(tokens: &[Token], list: &ListSpan, positions: &[usize], removed: &mut [bool]) {
+    let items = &list.items;
+    let keep: Vec<usize> = (0..items.len()).filter(|p| !positions.contains(p)).collect();
+
+    if keep.is_empty() {
+        for flag in &mut removed[list.open + 1..list.close] {
+            *flag = true;
+        }
+        return;
+    }
+
+    for &pos in positions {
+        let Some(&(start, end)) = items.get(pos) else { continue };
+        let first_kept = keep[0];
+        let (from, to) = if pos < first_kept {
+            // 位于所有保留项之前：删除该项、其后的逗号及空白
+            let next_start = items[pos + 1].0;
+            let from = next_significant(tokens, start).unwrap_or(start).min(end);
+            (from, next_significant(tokens, next_start).unwrap_or(next_start))
+        } else {
+            // 其余情况：连同其前的逗号一起删除
+            (items[pos - 1].1, end)
+        };
+        for flag in &mut removed[from..to] {
+            *flag = true;
+        }
+    }
+}
+
+/// 用新值替换列表项 [start, end)：第一个有效 token 承载新值，其余 token 标记删除，保留首尾空白
+pub(crate) fn replace_item(tokens: &[Token], start: usize, end: usize, replacement: String, out: &mut [Option<String>], removed: &mut [bool]) {
+    let first = (start..end).find(|&k| tokens[k].is_significant()).unwrap_or(start);
+    let last = (start..end).rev().find(|&k| tokens[k].is_significant()).map_or(first + 1, |k| k + 1);
+    out[first] = Some(replacement);
+    removed[first + 1..last].iter_mut().for_each(|flag| *flag = true);
+}
@@ -0,0 +1,519 @@
+/// SQL 词法单元类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// 空白（空格、制表符、换行）
+    Whitespace,
+    /// 行注释：`-- ...` 或 MySQL 的 `# ...`
+    LineComment,
+    /// 块注释：`/* ... */`
+    BlockComment,
+    /// 未加引号的关键字或标识符
+    Word,
+    /// 加引号的标识符：`` `name` `` 或 `"name"`
+    QuotedIdent,
+    /// 字符串字面量：`'..'`、`N'..'`、`X'..'`、`$tag$..$tag$` 等
+    String,
+    /// 数字字面量
+    Number,
+    /// 标点：`(` `)` `,` `;` `.`
+    Punct,
+    /// 其他运算符
+    Operator,
+}
+
+/// 词法单元，text 为原文切片，所有 token 依次拼接即为原文
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    /// 在原文中的字节偏移
+    pub offset: usize,
+    /// 起始行号（从 1 开始）
+    pub line: usize,
+}
+
+impl Token<'_> {
+    /// 是否为指定关键字（不区分大小写）
+    pub fn is_keyword(&self, kw: &str) -> bool {
+        self.kind == TokenKind::Word && self.text.eq_ignore_ascii_case(kw)
+    }
+
+    /// 是否为指定标点
+    pub fn is_punct(&self, p: char) -> bool {
+        self.kind == TokenKind::Punct && self.text.len() == 1 && self.text.starts_with(p)
+    }
+
+    /// 空白与注释之外的 token
+    pub fn is_significant(&self) -> bool {
+        !matches!(self.kind, TokenKind::Whitespace | TokenKind::LineComment | TokenKind::BlockComment)
+    }
+
+    /// 标识符名称（去掉引号并还原转义）
+    pub fn ident_name(&self) -> String {
+        match self.kind {
+            TokenKind::QuotedIdent => {
+                let quote = self.text.chars().next().unwrap_or('"');
+                let inner = self.text.get(1..self.text.len().saturating_sub(1)).unwrap_or("");
+                let doubled: String = [quote, quote].iter().collect();
+                inner.replace(&doubled, &quote.to_string())
+            }
+            _ => self.text.to_string(),
+        }
+    }
+}
+
+/// 词法规则开关，不同数据库方言的差异在这里体现
+#[derive(Debug, Clone, Copy)]
+pub struct LexerConfig {
+    /// 字符串内支持反斜杠转义（MySQL）
+    pub backslash_escapes: bool,
+    /// 双引号表示字符串而不是标识符（MySQL 默认模式）
+    pub double_quoted_strings: bool,
+    /// 反引号标识符（MySQL）
+    pub backtick_idents: bool,
+    /// `#` 行注释（MySQL）
+    pub hash_comments: bool,
+    /// `$tag$...$tag$` 字符串（PostgreSQL）
+    pub dollar_quotes: bool,
+    /// `q'[...]'` 字符串（Oracle / 达梦）
+    pub q_quotes: bool,
+}
+
+impl Default for LexerConfig {
+    /// 宽松模式：识别所有引号形式，字符串按 MySQL 规则处理反斜杠转义
+    fn default() -> Self {
+        LexerConfig {
+            backslash_escapes: true,
+            double_quoted_strings: false,
+            backtick_idents: true,
+            hash_comments: true,
+            dollar_quotes: true,
+            q_quotes: true,
+        }
+    }
+}
+
+/// 使用默认规则切分 SQL 文本
+pub fn tokenize(sql: &str) -> Vec<Token<'_>> {
+    tokenize_with(sql, &LexerConfig::default())
+}
+
+/// 按指定规则切分 SQL 文本。未闭合的字符串或注释会一直延续到文本末尾
+pub fn tokenize_with<'a>(sql: &'a str, config: &LexerConfig) -> Vec<Token<'a>> {
+    let bytes = sql.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0usize;
+    let mut line = 1usize;
+
+    while i < bytes.len() {
+        let start = i;
+        let (kind, end) = scan_token(bytes, i, config);
+        i = end.max(start + 1);
+        // 多字节字符不会落在 ASCII 分隔符上，这里只是防御性地对齐字符边界
+        while i < bytes.len() && !sql.is_char_boundary(i) {
+            i += 1;
+        }
+        let text = &sql[start..i];
+        tokens.push(Token { kind, text, offset: start, line });
+        line += text.bytes().filter(|&b| b == b'\n').count();
+    }
+
+    tokens
+}
+
+/// 语句切分的扫描进度。缓冲区末尾的字符串或注释还没有闭合时记录其结束方式与已扫描到的位置，
+/// 读入更多内容后从该位置继续，不必从 token 开头重新扫描
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanState {
+    /// 下次继续扫描的位置
+    pos: usize,
+    /// pos 位于未闭合的 token 内时，该 token 的结束方式
+    open: Option<Closer>,
+}
+
+/// 未闭合 token 的结束方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Closer {
+    /// 引号，支持重复引号转义及可选的反斜杠转义
+    Quote { quote: u8, backslash: bool },
+    /// 块注释的 `*/`
+    CommentEnd,
+    /// 与起始标记相同的 `$tag$`，记录起始标记在缓冲区中的范围
+    Tag { from: usize, len: usize },
+    /// q'<delim>...<delim>' 的结束定界符
+    QClose(u8),
+}
+
+/// 从 state 记录的位置开始查找顶层（不在字符串、注释内）的 `;`，返回分号之后的位置。
+/// 没有找到时更新 state，读入更多内容后再次调用即可继续
+pub fn find_statement_end(sql: &str, state: &mut ScanState, config: &LexerConfig) -> Option<usize> {
+    let bytes = sql.as_bytes();
+    let mut i = resume_open(bytes, state)?;
+    while i < bytes.len() {
+        let start = i;
+        let (kind, end) = scan_token(bytes, i, config);
+        if end >= bytes.len() && kind != TokenKind::Punct {
+            suspend(bytes, start, kind, config, state);
+            return None;
+        }
+        if kind == TokenKind::Punct && bytes[start] == b';' {
+            return Some(start + 1);
+        }
+        i = end.max(start + 1);
+    }
+    *state = ScanState { pos: i, open: None };
+    None
+}
+
+/// 查找 SQL*Plus 风格的块结束标记：单独占一行的 `/`。state 的含义同 [`find_statement_end`]
+pub fn find_block_end(sql: &str, state: &mut ScanState, config: &LexerConfig) -> Option<usize> {
+    let bytes = sql.as_bytes();
+    let mut i = resume_open(bytes, state)?;
+    while i < bytes.len() {
+        let start = i;
+        let (kind, end) = scan_token(bytes, i, config);
+        if end >= bytes.len() && kind != TokenKind::Operator {
+            suspend(bytes, start, kind, config, state);
+            return None;
+        }
+        if kind == TokenKind::Operator && bytes[start] == b'/' {
+            let line_start = bytes[..start].iter().rposition(|&b| b == b'\n').map(|p| p + 1).unwrap_or(0);
+            let alone_before = bytes[line_start..start].iter().all(|b| matches!(b, b' ' | b'\t'));
+            match bytes[start + 1..].iter().position(|&b| b == b'\n') {
+                Some(nl) if alone_before && bytes[start + 1..start + 1 + nl].iter().all(|b| matches!(b, b' ' | b'\t' | b'\r')) => {
+                    return Some(start + 1);
+                }
+                // 行尾还没读到，下次再判断
+                None if alone_before => {
+                    *state = ScanState { pos: start, open: None };
+                    return None;
+                }
+                _ => {}
+            }
+        }
+        i = end.max(start + 1);
+    }
+    *state = ScanState { pos: i, open: None };
+    None
+}
+
+/// 继续扫描上次未闭合的 token，返回其后的位置；仍未闭合时更新 state 并返回 None
+fn resume_open(bytes: &[u8], state: &mut ScanState) -> Option<usize> {
+    let Some(closer) = state.open else { return Some(state.pos) };
+    match continue_token(bytes, closer, state.pos) {
+        Ok(end) => Some(end),
+        Err(pos) => {
+            state.pos = pos;
+            None
+        }
+    }
+}
+
+/// 记录缓冲区末尾未结束的 token：字符串与注释从已扫描处继续，空白从末尾继续，其余 token 下次从头扫描
+fn suspend(bytes: &[u8], start: usize, kind: TokenKind, config: &LexerConfig, state: &mut ScanState) {
+    *state = match open_closer(bytes, start, kind, config) {
+        Some((closer, body)) => match continue_token(bytes, closer, body) {
+            // 恰好在缓冲区末尾闭合
+            Ok(end) => ScanState { pos: end, open: None },
+            Err(pos) => ScanState { pos, open: Some(closer) },
+        },
+        None if kind == TokenKind::Whitespace => ScanState { pos: bytes.len(), open: None },
+        None => ScanState { pos: start, open: None },
+    };
+}
+
+/// 未闭合的字符串或注释的结束方式及内容的起始位置；起始标记本身还不完整时返回 None
+fn open_closer(bytes: &[u8], start: usize, kind: TokenKind, config: &LexerConfig) -> Option<(Closer, usize)> {
+    match (kind, bytes[start]) {
+        (TokenKind::BlockComment, _) => Some((Closer::CommentEnd, start + 2)),
+        (TokenKind::QuotedIdent, quote) => Some((Closer::Quote { quote, backslash: false }, start + 1)),
+        (TokenKind::String, quote @ (b'\'' | b'"')) => Some((Closer::Quote { quote, backslash: config.backslash_escapes }, start + 1)),
+        (TokenKind::String, b'$') => {
+            let len = bytes[start + 1..].iter().position(|&b| b == b'$')? + 2;
+            Some((Closer::Tag { from: start, len }, start + len))
+        }
+        (TokenKind::String, _) => {
+            // 带前缀的字符串：N'..'、E'..'、q'[..]' 等
+            let quote = start + bytes[start..].iter().position(|&b| b == b'\'')?;
+            match bytes[start..quote].to_ascii_lowercase().as_slice() {
+                b"q" | b"nq" if config.q_quotes => {
+                    let open = *bytes.get(quote + 1)?;
+                    let close = match open {
+                        b'[' => b']',
+                        b'{' => b'}',
+                        b'(' => b')',
+                        b'<' => b'>',
+                        other => other,
+                    };
+                    Some((Closer::QClose(close), quote + 2))
+                }
+                b"e" => Some((Closer::Quote { quote: b'\'', backslash: true }, quote + 1)),
+                _ => Some((Closer::Quote { quote: b'\'', backslash: config.backslash_escapes }, quote + 1)),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// 从 pos 开始查找 token 的结束位置。Ok 为 token 之后的位置；
+/// Err 为读入更多内容后可以安全继续扫描的位置（之前的内容中确定没有结束符）
+fn continue_token(bytes: &[u8], closer: Closer, pos: usize) -> Result<usize, usize> {
+    let len = bytes.len();
+    match closer {
+        Closer::Quote { quote, backslash } => {
+            let mut j = pos;
+            while j < len {
+                let b = bytes[j];
+                if backslash && b == b'\\' {
+                    if j + 1 >= len {
+                        return Err(j);
+                    }
+                    j += 2;
+                    continue;
+                }
+                if b == quote {
+                    // 需要下一个字节才能区分结束引号与重复引号
+                    match bytes.get(j + 1) {
+                        None => return Err(j),
+                        Some(&next) if next == quote => j += 2,
+                        Some(_) => return Ok(j + 1),
+                    }
+                    continue;
+                }
+                j += 1;
+            }
+            Err(len)
+        }
+        Closer::CommentEnd => find_seq(bytes, pos, b"*/").map(|p| p + 2).ok_or(len.saturating_sub(1).max(pos)),
+        Closer::Tag { from, len: tag_len } => {
+            let tag = &bytes[from..from + tag_len];
+            find_seq(bytes, pos, tag).map(|p| p + tag_len).ok_or((len + 1).saturating_sub(tag_len).max(pos))
+        }
+        Closer::QClose(close) => {
+            let mut k = pos;
+            while k + 1 < len {
+                if bytes[k] == close && bytes[k + 1] == b'\'' {
+                    return Ok(k + 2);
+                }
+                k += 1;
+            }
+            Err(len.saturating_sub(1).max(pos))
+        }
+    }
+}
+
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'$' || b >= 0x80
+}
+
+fn is_word_start(b: u8) -> bool {
+    b.is_ascii_alphabetic() || b == b'_' || b >= 0x80
+}
+
+/// 从 i 开始识别一个 token，返回类型和结束位置
+fn scan_token(bytes: &[u8], i: usize, config: &LexerConfig) -> (TokenKind, usize) {
+    let b = bytes[i];
+    let next = bytes.get(i + 1).copied();
+
+    match b {
+        b' ' | b'\t' | b'\r' | b'\n' | 0x0b | 0x0c => {
+            let mut j = i;
+            while j < bytes.len() && matches!(bytes[j], b' ' | b'\t' | b'\r' | b'\n' | 0x0b | 0x0c) {
+                j += 1;
+            }
+            (TokenKind::Whitespace, j)
+        }
+        b'-' if next == Some(b'-') => (TokenKind::LineComment, scan_line_end(bytes, i)),
+        b'#' if config.hash_comments => (TokenKind::LineComment, scan_line_end(bytes, i)),
+        b'/' if next == Some(b'*') => {
+            let end = find_seq(bytes, i + 2, b"*/").map(|p| p + 2).unwrap_or(bytes.len());
+            (TokenKind::BlockComment, end)
+        }
+        b'\'' => (TokenKind::String, scan_quoted(bytes, i, b'\'', config.backslash_escapes)),
+        b'"' if config.double_quoted_strings => (TokenKind::String, scan_quoted(bytes, i, b'"', config.backslash_escapes)),
+        b'"' => (TokenKind::QuotedIdent, scan_quoted(bytes, i, b'"', false)),
+        b'`' if config.backtick_idents => (TokenKind::QuotedIdent, scan_quoted(bytes, i, b'`', false)),
+        b'$' if config.dollar_quotes => match scan_dollar_quoted(bytes, i) {
+            Some(end) => (TokenKind::String, end),
+            None => (TokenKind::Operator, i + 1),
+        },
+        b'0'..=b'9' => (TokenKind::Number, scan_number(bytes, i)),
+        b'.' if next.is_some_and(|n| n.is_ascii_digit()) => (TokenKind::Number, scan_number(bytes, i)),
+        b'(' | b')' | b',' | b';' | b'.' => (TokenKind::Punct, i + 1),
+        _ if is_word_start(b) => scan_word_or_prefixed_string(bytes, i, config),
+        _ => {
+            // 常见的双字符运算符合并为一个 token
+            let two = [b, next.unwrap_or(0)];
+            let len = match &two {
+                b"<=" | b">=" | b"<>" | b"!=" | b"||" | b"::" | b":=" | b"=>" => 2,
+                _ => 1,
+            };
+            (TokenKind::Operator, i + len)
+        }
+    }
+}
+
+fn scan_line_end(bytes: &[u8], i: usize) -> usize {
+    let mut j = i;
+    while j < bytes.len() && bytes[j] != b'\n' {
+        j += 1;
+    }
+    j
+}
+
+fn find_seq(bytes: &[u8], from: usize, seq: &[u8]) -> Option<usize> {
+    if from > bytes.len() {
+        return None;
+    }
+    bytes[from..].windows(seq.len()).position(|w| w == seq).map(|p| p + from)
+}
+
+/// 扫描引号包围的内容，支持引号重复转义（`''`）以及可选的反斜杠转义
+fn scan_quoted(bytes: &[u8], i: usize, quote: u8, backslash: bool) -> usize {
+    let mut j = i + 1;
+    while j < bytes.len() {
+        let b = bytes[j];
+        if backslash && b == b'\\' {
+            j += 2;
+            continue;
+        }
+        if b == quote {
+            if bytes.get(j + 1) == Some(&quote) {
+                j += 2;
+                continue;
+            }
+            return j + 1;
+        }
+        j += 1;
+    }
+    bytes.len()
+}
+
+/// 扫描 `$tag$ ... $tag$`，不是合法起始标记时返回 None
+fn scan_dollar_quoted(bytes: &[u8], i: usize) -> Option<usize> {
+    let mut j = i + 1;
+    while j < bytes.len() && (bytes[j].is_ascii_alphanumeric() || bytes[j] == b'_') {
+        j += 1;
+    }
+    if bytes.get(j) != Some(&b'$') || bytes.get(i + 1).is_some_and(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let tag = &bytes[i..=j];
+    let end = find_seq(bytes, j + 1, tag).map(|p| p + tag.len()).unwrap_or(bytes.len());
+    Some(end)
+}
+
+fn scan_number(bytes: &[u8], i: usize) -> usize {
+    let mut j = i;
+    if bytes[j] == b'0' && matches!(bytes.get(j + 1), Some(b'x' | b'X')) {
+        j += 2;
+        while j < bytes.len() && bytes[j].is_ascii_hexdigit() {
+            j += 1;
+        }
+        return j;
+    }
+    while j < bytes.len() && (bytes[j].is_ascii_digit() || bytes[j] == b'.') {
+        j += 1;
+    }
+    if j < bytes.len() && matches!(bytes[j], b'e' | b'E') {
+        let mut k = j + 1;
+        if k < bytes.len() && matches!(bytes[k], b'+' | b'-') {
+            k += 1;
+        }
+        if k < bytes.len() && bytes[k].is_ascii_digit() {
+            j = k;
+            while j < bytes.len() && bytes[j].is_ascii_digit() {
+                j += 1;
+            }
+        }
+    }
+    j
+}
+
+/// 标识符/关键字，或带前缀的字符串：N'..'、E'..'、X'..'、B'..'、_utf8mb4'..'、q'[..]'
+fn scan_word_or_prefixed_string(bytes: &[u8], i: usize, config: &LexerConfig) -> (TokenKind, usize) {
+    let mut j = i;
+    while j < bytes.len() && is_word_byte(bytes[j]) {
+        j += 1;
+    }
+    if bytes.get(j) != Some(&b'\'') {
+        return (TokenKind::Word, j);
+    }
+
+    let word = &bytes[i..j];
+    let lower: Vec<u8> = word.to_ascii_lowercase();
+    match lower.as_slice() {
+        b"q" | b"nq" if config.q_quotes => (TokenKind::String, scan_q_quoted(bytes, j)),
+        b"e" => (TokenKind::String, scan_quoted(bytes, j, b'\'', true)),
+        b"n" | b"x" | b"b" => (TokenKind::String, scan_quoted(bytes, j, b'\'', config.backslash_escapes)),
+        _ if word.first() == Some(&b'_') => (TokenKind::String, scan_quoted(bytes, j, b'\'', config.backslash_escapes)),
+        _ => (TokenKind::Word, j),
+    }
+}
+
+/// Oracle 的 q'<delim>...<delim>'，j 指向开头的单引号
+fn scan_q_quoted(bytes: &[u8], j: usize) -> usize {
+    let Some(&open) = bytes.get(j + 1) else { return bytes.len() };
+    let close = match open {
+        b'[' => b']',
+        b'{' => b'}',
+        b'(' => b')',
+        b'<' => b'>',
+        other => other,
+    };
+    let mut k = j + 2;
+    while k + 1 < bytes.len() {
+        if bytes[k] == close && bytes[k + 1] == b'\'' {
+            return k + 2;
+        }
+        k += 1;
+    }
+    bytes.len()
+}
+
+/// 从 i 开始跳过空白与注释，返回下一个有效 token 的下标
+pub fn next_significant(tokens: &[Token], i: usize) -> Option<usize> {
+    (i..tokens.len()).find(|&k| tokens[k].is_significant())
+}
+
+/// 从 i（含）向前查找上一个有效 token 的下标
+pub fn prev_significant(tokens: &[Token], i: usize) -> Option<usize> {
+    (0..=i.min(tokens.len().checked_sub(1)?)).rev().find(|&k| tokens[k].is_significant())
+}
+
+/// 从 `(` 所在下标出发，找到与之匹配的 `)` 的下标
+pub fn matching_paren(tokens: &[Token], open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (k, t) in tokens.iter().enumerate().skip(open) {
+        if t.is_punct('(') {
+            depth += 1;
+        } else if t.is_punct(')') {
+            depth -= 1;
+            if depth == 0 {
+                return Some(k);
+            }
+        }
+    }
+    None
+}
+
+/// 把括号内（open 与 close 之间）的 token 按顶层逗号切分，返回每一项的下标范围（不含逗号）
+pub fn split_top_level(tokens: &[Token], open: usize, close: usize) -> Vec<(usize, usize)> {
+    let mut items = Vec::new();
+    let mut depth = 0usize;
+    let mut start = open + 1;
+    for (k, t) in tokens.iter().enumerate().take(close).skip(open + 1) {
+        if t.is_punct('(') {
+            depth += 1;
+        } else if t.is_punct(')') {
+            depth = depth.saturating_sub(1);
+        } else if t.is_punct(',') && depth == 0 {
+            items.push((start, k));
+            start = k + 1;
+        }
+    }
+    if start < close || !items.is_empty() {
+        items.push((start, close));
+    }
+    items
+}
@@ -0,0 +1,330 @@
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Cursor};
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use serde_json::{json, Value};
+
+use super::cell::Cell;
+use super::dialect::Dialect;
+use super::encoding::{input_encoding, open_decoded};
+use super::insert::parse_inserts;
+use super::lexer::{next_significant, prev_significant, tokenize_with, Token};
+use super::stream::{RawStatement, StatementReader};
+
+/// 规则级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Severity {
+    Error,
+    Warning,
+    /// 关闭该规则
+    Off,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Off => "off",
+        }
+    }
+}
+
+/// 检查规则
+pub struct Rule {
+    pub id: &'static str,
+    pub default: Severity,
+    pub description: &'static str,
+}
+
+pub const RULES: &[Rule] = &[
+    Rule { id: "missing-commit", default: Severity::Error, description: "最后一条 DML 之后没有 COMMIT" },
+    Rule { id: "column-count", default: Severity::Error, description: "INSERT 元组的值个数与列数不一致" },
+    Rule { id: "duplicate-key", default: Severity::Error, description: "INSERT 中出现重复的主键值" },
+    Rule { id: "delete-without-where", default: Severity::Error, description: "DELETE 没有 WHERE 条件" },
+    Rule { id: "update-without-where", default: Severity::Error, description: "UPDATE 没有 WHERE 条件" },
+    Rule { id: "mixed-encoding", default: Severity::Error, description: "文件中存在非 UTF-8 编码的行" },
+    Rule { id: "insert-without-columns", default: Severity::Warning, description: "INSERT 没有列列表" },
+];
+
+/// 报告格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    Text,
+    Json,
+}
+
+/// 规则级别与主键配置
+#[derive(Debug, Clone)]
+pub struct LintConfig {
+    severities: Vec<(&'static str, Severity)>,
+    /// duplicate-key 规则使用的主键列
+    pub keys: Vec<String>,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        LintConfig { severities: RULES.iter().map(|r| (r.id, r.default)).collect(), keys: vec!["id".to_string()] }
+    }
+}
+
+impl LintConfig {
+    /// 设置规则级别，未知规则返回错误
+    pub fn set(&mut self, rule: &str, severity: Severity) -> Result<(), String> {
+        match self.severities.iter_mut().find(|(id, _)| *id == rule) {
+            Some(entry) => {
+                entry.1 = severity;
+                Ok(())
+            }
+            None => Err(format!(
+                "未知的规则 {}，可选: {}",
+                rule,
+                RULES.iter().map(|r| r.id).collect::<Vec<_>>().join(", ")
+            )),
+        }
+    }
+
+    /// 解析 `规则=级别` 形式的设置
+    pub fn apply(&mut self, spec: &str) -> Result<(), String> {
+        let (rule, level) = spec.split_once('=').ok_or_else(|| format!("规则级别格式应为 规则=级别: {}", spec))?;
+        let severity = Severity::from_str(level.trim(), true).map_err(|_| format!("未知的级别: {}", level))?;
+        self.set(rule.trim(), severity)
+    }
+
+    /// 读取 JSON 配置文件：`{"rules": {"规则": "级别"}, "keys": ["id"]}`
+    pub fn load(&mut self, path: &Path) -> Result<(), String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("读取配置失败: {}", e))?;
+        let value: Value = serde_json::from_str(&content).map_err(|e| format!("解析配置失败: {}", e))?;
+        if let Some(rules) = value.get("rules").and_then(|r| r.as_object()) {
+            for (rule, level) in rules {
+                let level = level.as_str().ok_or_else(|| format!("规则 {} 的级别应为字符串", rule))?;
+                self.apply(&format!("{}={}", rule, level))?;
+            }
+        }
+        if let Some(keys) = value.get("keys").and_then(|k| k.as_array()) {
+            self.keys = keys.iter().filter_map(|k| k.as_str().map(|s| s.to_string())).collect();
+        }
+        Ok(())
+    }
+
+    pub fn severity(&self, rule: &str) -> Severity {
+        self.severities.iter().find(|(id, _)| *id == rule).map(|(_, s)| *s).unwrap_or(Severity::Off)
+    }
+}
+
+/// 一条检查结果
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub line: usize,
+    pub message: String,
+}
+
+/// 单个文件的检查结果
+#[derive(Debug)]
+pub struct FileReport {
+    pub path: PathBuf,
+    pub issues: Vec<Issue>,
+}
+
+impl FileReport {
+    pub fn count(&self, severity: Severity) -> usize {
+        self.issues.iter().filter(|i| i.severity == severity).count()
+    }
+}
+
+/// 逐条语句检查
+struct Linter<'a> {
+    config: &'a LintConfig,
+    dialect: Dialect,
+    issues: Vec<Issue>,
+    /// 最后一条未提交的 DML 所在行
+    pending_dml: Option<usize>,
+    seen_keys: HashSet<(String, Vec<String>)>,
+}
+
+impl<'a> Linter<'a> {
+    fn new(config: &'a LintConfig, dialect: Dialect) -> Self {
+        Linter { config, dialect, issues: Vec::new(), pending_dml: None, seen_keys: HashSet::new() }
+    }
+
+    fn report(&mut self, rule: &'static str, line: usize, message: String) {
+        let severity = self.config.severity(rule);
+        if severity != Severity::Off {
+            self.issues.push(Issue { rule, severity, line, message });
+        }
+    }
+
+    fn statement(&mut self, statement: &RawStatement) {
+        let tokens = tokenize_with(&statement.text, &self.dialect.lexer_config());
+        let Some(first) = next_significant(&tokens, 0) else { return };
+        let line = statement.line + tokens[first].line - 1;
+        let at = |k: usize| statement.line + tokens[k].line - 1;
+
+        if ["insert", "update", "delete", "merge"].iter().any(|kw| tokens[first].is_keyword(kw)) {
+            self.pending_dml = Some(line);
+        }
+        // 只认作为语句开头的 COMMIT，PL/SQL 块内的 COMMIT 同样算作提交
+        if (0..tokens.len()).any(|k| tokens[k].is_keyword("commit") && starts_statement(&tokens, k)) {
+            self.pending_dml = None;
+        }
+
+        for (keyword, rule) in [("delete", "delete-without-where"), ("update", "update-without-where")] {
+            if tokens[first].is_keyword(keyword) && !has_top_level_where(&tokens, first) {
+                self.report(rule, line, format!("{} 语句没有 WHERE 条件", keyword.to_uppercase()));
+            }
+        }
+
+        let mut i = 0usize;
+        while i < tokens.len() {
+            let inserts = if tokens[i].is_keyword("insert") { parse_inserts(&tokens, i, self.dialect) } else { Vec::new() };
+            let Some(last) = inserts.last() else {
+                i += 1;
+                continue;
+            };
+            let insert_line = at(i);
+            i = last.end;
+
+            for insert in &inserts {
+                let table = insert.table_name(&tokens);
+                let Some(columns) = &insert.columns else {
+                    self.report("insert-without-columns", insert_line, format!("{}: INSERT 没有列列表", table));
+                    continue;
+                };
+                let key_positions: Option<Vec<usize>> =
+                    self.config.keys.iter().map(|k| insert.column_index(&tokens, k)).collect();
+
+                for (n, row) in insert.rows.iter().enumerate() {
+                    if row.items.len() != columns.items.len() {
+                        self.report(
+                            "column-count",
+                            at(row.open),
+                            format!("{}: 第 {} 个元组有 {} 个值，列数为 {}", table, n + 1, row.items.len(), columns.items.len()),
+                        );
+                        continue;
+                    }
+                    let Some(positions) = &key_positions else { continue };
+                    // 1 与 '1' 在数据库中视为同一个主键
+                    let key: Vec<String> = positions
+                        .iter()
+                        .map(|&p| Cell::from_item(&tokens[row.items[p].0..row.items[p].1], self.dialect).normalized())
+                        .collect();
+                    if !self.seen_keys.insert((table.to_ascii_lowercase(), key.clone())) {
+                        self.report("duplicate-key", at(row.open), format!("{}: 主键 ({}) 重复", table, key.join(", ")));
+                    }
+                }
+            }
+        }
+    }
+
+    fn finish(mut self) -> Vec<Issue> {
+        if let Some(line) = self.pending_dml {
+            self.report("missing-commit", line, "最后一条 DML 之后没有 COMMIT".to_string());
+        }
+        self.issues.sort_by_key(|i| i.line);
+        self.issues
+    }
+}
+
+/// 第 k 个 token 是否位于语句开头：前面没有有效 token，或紧跟在分号或 BEGIN、THEN 等块关键字之后
+fn starts_statement(tokens: &[Token], k: usize) -> bool {
+    let Some(prev) = k.checked_sub(1).and_then(|p| prev_significant(tokens, p)) else { return true };
+    tokens[prev].is_punct(';') || ["begin", "then", "else", "loop"].iter().any(|kw| tokens[prev].is_keyword(kw))
+}
+
+/// 语句在最外层（不在括号内）是否有 WHERE
+fn has_top_level_where(tokens: &[Token], from: usize) -> bool {
+    let mut depth = 0i32;
+    for t in &tokens[from..] {
+        if t.is_punct('(') {
+            depth += 1;
+        } else if t.is_punct(')') {
+            depth -= 1;
+        } else if depth == 0 && t.is_keyword("where") {
+            return true;
+        }
+    }
+    false
+}
+
+/// 检查 SQL 文本
+pub fn lint_sql(sql: &str, config: &LintConfig, dialect: Dialect) -> Vec<Issue> {
+    let mut linter = Linter::new(config, dialect);
+    for statement in StatementReader::new(Cursor::new(sql), dialect).flatten() {
+        linter.statement(&statement);
+    }
+    linter.finish()
+}
+
+/// 检查 SQL 文件。先逐行检查编码，文件是合法 UTF-8 或整体是 GBK 等同一种编码时
+/// 解码后按语句流式检查；UTF-8 与其他编码的行混在一起时把无法解码的字节替换后再检查
+pub fn lint_file(path: &Path, config: &LintConfig, dialect: Dialect) -> Result<FileReport, String> {
+    let (bad_lines, utf8_lines) = scan_lines(path)?;
+    let mut linter = Linter::new(config, dialect);
+
+    if bad_lines.is_empty() || utf8_lines == 0 {
+        let reader = open_decoded(path, input_encoding(path)?)?;
+        for statement in StatementReader::new(BufReader::new(reader), dialect) {
+            linter.statement(&statement?);
+        }
+    } else {
+        let bytes = fs::read(path).map_err(|e| format!("读取文件失败: {}", e))?;
+        let text = String::from_utf8_lossy(&bytes);
+        for statement in StatementReader::new(Cursor::new(text.as_bytes()), dialect) {
+            linter.statement(&statement?);
+        }
+        let shown: Vec<String> = bad_lines.iter().take(5).map(|l| l.to_string()).collect();
+        let more = if bad_lines.len() > 5 { format!(" 等 {}", bad_lines.len()) } else { String::new() };
+        linter.report("mixed-encoding", bad_lines[0], format!("第 {}{} 行不是 UTF-8 编码", shown.join(", "), more));
+    }
+
+    Ok(FileReport { path: path.to_path_buf(), issues: linter.finish() })
+}
+
+/// 不是合法 UTF-8 的行号，以及含非 ASCII 字符的合法 UTF-8 行数
+fn scan_lines(path: &Path) -> Result<(Vec<usize>, usize), String> {
+    let file = File::open(path).map_err(|e| format!("读取文件失败: {}", e))?;
+    let mut reader = BufReader::new(file);
+    let mut buf = Vec::new();
+    let mut lines = Vec::new();
+    let mut utf8_lines = 0usize;
+    let mut line = 0usize;
+    loop {
+        buf.clear();
+        let n = reader.read_until(b'\n', &mut buf).map_err(|e| format!("读取文件失败: {}", e))?;
+        if n == 0 {
+            break;
+        }
+        line += 1;
+        match std::str::from_utf8(&buf) {
+            Err(_) => lines.push(line),
+            Ok(text) if !text.is_ascii() => utf8_lines += 1,
+            Ok(_) => {}
+        }
+    }
+    Ok((lines, utf8_lines))
+}
+
+/// 机器可读的 JSON 报告
+pub fn report_json(reports: &[FileReport]) -> String {
+    let files: Vec<Value> = reports
+        .iter()
+        .map(|r| {
+            let issues: Vec<Value> = r
+                .issues
+                .iter()
+                .map(|i| json!({ "rule": i.rule, "severity": i.severity.as_str(), "line": i.line, "message": i.message }))
+                .collect();
+            json!({ "path": r.path.display().to_string(), "issues": issues })
+        })
+        .collect();
+    let report = json!({
+        "files": files,
+        "errors": reports.iter().map(|r| r.count(Severity::Error)).sum::<usize>(),
+        "warnings": reports.iter().map(|r| r.count(Severity::Warning)).sum::<usize>(),
+    });
+    serde_json::to_string_pretty(&report).unwrap_or_default()
+}
@@ -0,0 +1,263 @@
+use std::path::Path;
+
+use sha2::{Digest, Sha256};
+
+use super::cell::Cell;
+use super::dialect::Dialect;
+use super::insert::{parse_inserts, replace_item};
+use super::lexer::tokenize_with;
+use super::stream::{rewrite_file, Output};
+
+/// 未指定种子时使用的默认种子
+pub const DEFAULT_SEED: &str = "synapse";
+
+/// 身份证号前 6 位使用的行政区划代码
+const AREA_CODES: &[&str] = &[
+    "110101", "110105", "120101", "310101", "310104", "320102", "330106", "340102", "350102", "370102", "410105",
+    "420106", "430104", "440106", "440305", "500103", "510104", "610113",
+];
+/// 手机号前 3 位
+const PHONE_PREFIXES: &[&str] = &[
+    "130", "131", "132", "133", "135", "136", "137", "138", "139", "150", "151", "152", "155", "156", "157", "158",
+    "159", "166", "177", "180", "181", "185", "186", "187", "188", "189", "199",
+];
+const SURNAMES: &[&str] = &[
+    "王", "李", "张", "刘", "陈", "杨", "黄", "赵", "吴", "周", "徐", "孙", "马", "朱", "胡", "郭", "何", "高", "林",
+    "罗", "郑", "梁", "谢", "宋", "唐", "许", "韩", "冯", "邓", "曹",
+];
+const GIVEN_NAMES: &[&str] = &[
+    "伟", "芳", "娜", "敏", "静", "丽", "强", "磊", "军", "洋", "勇", "艳", "杰", "涛", "明", "超", "秀", "霞", "平",
+    "刚", "桂", "英", "华", "玉", "萍", "红", "娟", "建", "文", "辉", "宁", "欣", "浩", "宇", "晨", "思", "嘉", "雪",
+    "琳", "博",
+];
+
+/// 脱敏方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaskStrategy {
+    /// 种子加值的 SHA-256 十六进制摘要，取前 n 位
+    Hash(usize),
+    /// 保留前 front 个与后 back 个字符，其余替换为 `*`
+    Partial { front: usize, back: usize },
+    /// 保留长度与非数字字符，数字替换为伪随机数字，适用于账号、卡号
+    Digits,
+    /// 校验位正确的 18 位身份证号
+    IdCard,
+    /// 11 位手机号
+    Phone,
+    /// 中文姓名
+    Name,
+    Null,
+}
+
+impl MaskStrategy {
+    /// 解析 `hash[:长度]`、`partial[:前:后]`、`digits`、`idcard`、`phone`、`name`、`null`
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut parts = spec.split(':').map(|p| p.trim());
+        let name = parts.next().unwrap_or_default().to_ascii_lowercase();
+        let args: Vec<usize> = parts
+            .map(|p| p.parse::<usize>().map_err(|_| format!("脱敏参数应为数字: {}", spec)))
+            .collect::<Result<_, _>>()?;
+        let strategy = match (name.as_str(), args.as_slice()) {
+            ("hash", []) => MaskStrategy::Hash(16),
+            ("hash", [n]) if (1..=64).contains(n) => MaskStrategy::Hash(*n),
+            ("partial", []) => MaskStrategy::Partial { front: 3, back: 4 },
+            ("partial", [front, back]) => MaskStrategy::Partial { front: *front, back: *back },
+            ("digits", []) => MaskStrategy::Digits,
+            ("idcard", []) => MaskStrategy::IdCard,
+            ("phone", []) => MaskStrategy::Phone,
+            ("name", []) => MaskStrategy::Name,
+            ("null", []) => MaskStrategy::Null,
+            _ => {
+                return Err(format!(
+                    "无法识别的脱敏方式 {}，可选: hash[:长度]、partial[:前:后]、digits、idcard、phone、name、null",
+                    spec
+                ));
+            }
+        };
+        Ok(strategy)
+    }
+
+    /// 结果是否只含数字，原值为数字字面量时结果也写作数字
+    fn numeric(&self) -> bool {
+        matches!(self, MaskStrategy::Digits | MaskStrategy::Phone)
+    }
+}
+
+/// 一条脱敏规则：`[表名.]列名=方式`
+#[derive(Debug, Clone)]
+pub struct MaskRule {
+    pub table: Option<String>,
+    pub column: String,
+    pub strategy: MaskStrategy,
+}
+
+impl MaskRule {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (target, strategy) = spec.split_once('=').ok_or_else(|| format!("脱敏规则格式应为 列名=方式: {}", spec))?;
+        let target = target.trim();
+        let (table, column) = match target.rsplit_once('.') {
+            Some((table, column)) => (Some(table.to_string()), column.to_string()),
+            None => (None, target.to_string()),
+        };
+        if column.is_empty() {
+            return Err(format!("脱敏规则缺少列名: {}", spec));
+        }
+        Ok(MaskRule { table, column, strategy: MaskStrategy::parse(strategy)? })
+    }
+
+    /// 表名匹配完整名称或去掉 schema 后的名称
+    fn applies_to(&self, table: &str) -> bool {
+        match &self.table {
+            Some(t) => t.eq_ignore_ascii_case(table) || table.rsplit('.').next().is_some_and(|n| t.eq_ignore_ascii_case(n)),
+            None => true,
+        }
+    }
+}
+
+/// 脱敏结果
+#[derive(Debug, Default)]
+pub struct MaskReport {
+    /// 每条规则处理的值个数，与规则顺序一致
+    pub masked: Vec<usize>,
+    /// (行号, 说明) 形式的未处理原因
+    pub warnings: Vec<(usize, String)>,
+}
+
+/// 按规则脱敏 SQL 文件中 INSERT 的列值，按语句流式处理并按 output 写出结果
+pub fn mask_file(sql_path: &Path, output: &Output, rules: &[MaskRule], seed: &str, dialect: Dialect) -> Result<MaskReport, String> {
+    let mut report = MaskReport { masked: vec![0; rules.len()], warnings: Vec::new() };
+    rewrite_file(sql_path, output, dialect, |statement| mask_sql(&statement.text, statement.line, rules, seed, dialect, &mut report))?;
+    Ok(report)
+}
+
+/// 脱敏 SQL 文本中 INSERT 的列值。line 为文本首行在文件中的行号，用于提示
+pub fn mask_sql(sql: &str, line: usize, rules: &[MaskRule], seed: &str, dialect: Dialect, report: &mut MaskReport) -> String {
+    report.masked.resize(rules.len(), 0);
+    let tokens = tokenize_with(sql, &dialect.lexer_config());
+    let mut out: Vec<Option<String>> = vec![None; tokens.len()];
+    let mut removed = vec![false; tokens.len()];
+
+    let mut i = 0usize;
+    while i < tokens.len() {
+        let inserts = if tokens[i].is_keyword("insert") { parse_inserts(&tokens, i, dialect) } else { Vec::new() };
+        let Some(last) = inserts.last() else {
+            i += 1;
+            continue;
+        };
+        let at = |k: usize| line + tokens[k].line - 1;
+        i = last.end;
+
+        for insert in &inserts {
+            let table = insert.table_name(&tokens);
+            let applicable: Vec<usize> = (0..rules.len()).filter(|&r| rules[r].applies_to(&table)).collect();
+            if applicable.is_empty() {
+                continue;
+            }
+            let Some(columns) = &insert.columns else {
+                report.warnings.push((at(insert.insert), format!("{}: 缺少列列表，无法确定脱敏列", table)));
+                continue;
+            };
+            // (列位置, 规则下标)
+            let targets: Vec<(usize, usize)> = applicable
+                .iter()
+                .filter_map(|&r| insert.column_index(&tokens, &rules[r].column).map(|p| (p, r)))
+                .collect();
+
+            for row in &insert.rows {
+                if row.items.len() != columns.items.len() {
+                    report.warnings.push((at(row.open), format!("{}: 值个数与列数不一致，跳过该行", table)));
+                    continue;
+                }
+                for &(pos, r) in &targets {
+                    let Some(&(a, b)) = row.items.get(pos) else { continue };
+                    let value = match Cell::from_item(&tokens[a..b], dialect) {
+                        Cell::Null => continue,
+                        Cell::Raw(expr) => {
+                            report.warnings.push((at(a), format!("{}.{}: 表达式 {} 未脱敏", table, rules[r].column, expr)));
+                            continue;
+                        }
+                        cell => cell,
+                    };
+                    let (Cell::Number(text) | Cell::Text(text)) = &value else { continue };
+                    let strategy = rules[r].strategy;
+                    let replacement = match mask_value(strategy, text, seed) {
+                        None => "NULL".to_string(),
+                        Some(masked) if strategy.numeric() && matches!(value, Cell::Number(_)) => masked,
+                        Some(masked) => dialect.quote_string(&masked),
+                    };
+
+                    replace_item(&tokens, a, b, replacement, &mut out, &mut removed);
+                    report.masked[r] += 1;
+                }
+            }
+        }
+    }
+
+    tokens
+        .iter()
+        .enumerate()
+        .filter(|(k, _)| !removed[*k])
+        .map(|(k, t)| out[k].take().unwrap_or_else(|| t.text.to_string()))
+        .collect()
+}
+
+/// 计算脱敏后的值，None 表示 NULL。结果只取决于种子与原值，不同表中相同的值脱敏结果相同
+pub fn mask_value(strategy: MaskStrategy, value: &str, seed: &str) -> Option<String> {
+    let digest = Sha256::new().chain_update(seed).chain_update([0u8]).chain_update(value).finalize();
+    let mut bytes = digest.iter().copied().cycle();
+    let mut next = |n: usize| (bytes.next().unwrap_or(0) as usize * 256 + bytes.next().unwrap_or(0) as usize) % n;
+
+    let masked = match strategy {
+        MaskStrategy::Null => return None,
+        MaskStrategy::Hash(len) => digest.iter().map(|b| format!("{:02x}", b)).collect::<String>()[..len].to_string(),
+        MaskStrategy::Partial { front, back } => {
+            let chars: Vec<char> = value.chars().collect();
+            // 太短时只保留第一个字符
+            let (front, back) = if front + back < chars.len() { (front, back) } else { (1.min(chars.len()), 0) };
+            chars
+                .iter()
+                .enumerate()
+                .map(|(k, &c)| if k < front || k >= chars.len() - back { c } else { '*' })
+                .collect()
+        }
+        MaskStrategy::Digits => {
+            let first_digit = value.find(|c: char| c.is_ascii_digit());
+            value
+                .char_indices()
+                .map(|(k, c)| match c {
+                    // 第一个数字不为 0，避免数字字面量出现前导零
+                    '0'..='9' if Some(k) == first_digit => char::from(b'1' + next(9) as u8),
+                    '0'..='9' => char::from(b'0' + next(10) as u8),
+                    _ => c,
+                })
+                .collect()
+        }
+        MaskStrategy::Phone => {
+            let mut phone = PHONE_PREFIXES[next(PHONE_PREFIXES.len())].to_string();
+            (0..8).for_each(|_| phone.push(char::from(b'0' + next(10) as u8)));
+            phone
+        }
+        MaskStrategy::IdCard => {
+            let mut id = AREA_CODES[next(AREA_CODES.len())].to_string();
+            id.push_str(&format!("{}{:02}{:02}", 1960 + next(46), 1 + next(12), 1 + next(28)));
+            id.push_str(&format!("{:03}", 1 + next(999)));
+            id.push(id_card_check_digit(&id));
+            id
+        }
+        MaskStrategy::Name => {
+            let given = value.chars().count().saturating_sub(1).clamp(1, 2);
+            let mut name = SURNAMES[next(SURNAMES.len())].to_string();
+            (0..given).for_each(|_| name.push_str(GIVEN_NAMES[next(GIVEN_NAMES.len())]));
+            name
+        }
+    };
+    Some(masked)
+}
+
+/// GB 11643 身份证校验码
+fn id_card_check_digit(first17: &str) -> char {
+    const WEIGHTS: [u32; 17] = [7, 9, 10, 5, 8, 4, 2, 1, 6, 3, 7, 9, 10, 5, 8, 4, 2];
+    const CHECK: &[u8] = b"10X98765432";
+    let sum: u32 = first17.chars().zip(WEIGHTS).map(|(c, w)| c.to_digit(10).unwrap_or(0) * w).sum();
+    char::from(CHECK[(sum % 11) as usize])
+}
@@ -1,3 +1,54 @@
+pub mod lexer;
+pub mod dialect;
+pub mod encoding;
+pub mod insert;
+pub mod stream;
+pub mod dropcol;
 pub mod rmid;
+pub mod convert;
+pub mod upsert;
+pub mod cell;
+pub mod csv;
+pub mod export;
+pub mod import;
+pub mod rollback;
+pub mod lint;
+pub mod check;
+pub mod rename;
+pub mod dedup;
+pub mod mask;
+pub mod diff;
+pub mod ddl;
+pub mod schemadiff;
+pub mod split;
+pub mod rebatch;
+pub mod fmt;
+pub mod infer;
+pub mod remap;
+pub mod batch;
 
-pub use rmid::rmid_file;
\ No newline at end of file
+pub use lexer::{tokenize, tokenize_with, LexerConfig, Token, TokenKind};
+pub use dialect::Dialect;
+pub use encoding::{recode_file, FileEncoding};
+pub use stream::{StatementReader, RawStatement, open_statements, rewrite_file, Output};
+pub use dropcol::{dropcol_file, DropColumnsReport, TableDropStat};
+pub use rmid::rmid_file;
+pub use convert::{convert_file, ConvertOptions, ConvertReport, MultiRowMode};
+pub use upsert::{upsert_file, UpsertReport};
+pub use cell::{Cell, ColumnType};
+pub use export::{export_file, DataFormat, ExportReport, ExportedTable};
+pub use import::{import_file, parse_column_types, ImportOptions};
+pub use rollback::{rollback_file, RollbackReport};
+pub use lint::{lint_file, report_json, FileReport, LintConfig, ReportFormat, Severity, RULES};
+pub use check::{normalize_statement, CheckFailure, CheckReport, Checker, FailureKind, Normalized};
+pub use rename::{parse_mapping, rename_file, RenameReport, Renames};
+pub use dedup::{dedup_file, DedupReport, Duplicate, KeepPolicy};
+pub use mask::{mask_file, MaskReport, MaskRule, MaskStrategy};
+pub use diff::{diff_files, ColumnChange, DataDiff, DataRow, ModifiedRow, TableDiff};
+pub use ddl::{parse_schema, parse_schema_file, ColumnDef, IndexDef, PrimaryKey, Schema, TableDef};
+pub use schemadiff::{schema_diff, Migration, MigrationStep};
+pub use split::{extract_file, merge_files, split_file, statement_table, ExtractOptions, ExtractReport, MergeReport, SplitReport};
+pub use rebatch::{parse_size, rebatch_file, RebatchOptions, RebatchReport};
+pub use fmt::{check_file, fmt_file, format_sql, FmtOptions, KeywordCase};
+pub use remap::{plan_remap, remap_file, ForeignKey, IdMap, RemapOptions, RemapReport};
+pub use infer::{infer_schema, infer_schema_sql, InferredColumn, InferredSchema, InferredTable};
@@ -0,0 +1,215 @@
+use std::path::Path;
+
+use super::ddl::format_tokens;
+use super::dialect::Dialect;
+use super::insert::insert_spans;
+use super::lexer::{next_significant, tokenize_with, Token, TokenKind};
+use super::stream::{rewrite_file_buffered, Output, RawStatement};
+
+/// 重新分批的选项
+#[derive(Debug, Clone, Copy)]
+pub struct RebatchOptions {
+    /// 每条 INSERT 最多的行数
+    pub rows: Option<usize>,
+    /// 每条 INSERT 最多的字节数，单行超过限制时单独成为一条语句
+    pub max_bytes: Option<usize>,
+    /// 每输出 N 条 INSERT 插入一次 COMMIT
+    pub commit_every: Option<usize>,
+}
+
+/// 重新分批的结果
+#[derive(Debug, Default)]
+pub struct RebatchReport {
+    /// 读取的 INSERT 语句数
+    pub read: usize,
+    /// 输出的 INSERT 语句数
+    pub written: usize,
+    pub rows: usize,
+    /// 插入的 COMMIT 数
+    pub commits: usize,
+    /// 单行即超过字节限制的行数
+    pub oversized: usize,
+    /// 带有 ON DUPLICATE KEY、RETURNING 等子句而保持原样的语句数
+    pub skipped: usize,
+}
+
+/// 解析 `500`、`64K`、`1M`、`1G` 形式的字节数
+pub fn parse_size(spec: &str) -> Result<usize, String> {
+    let spec = spec.trim();
+    let upper = spec.to_ascii_uppercase();
+    let digits = upper.trim_end_matches(['B', 'I']);
+    let (number, unit) = match digits.char_indices().last() {
+        Some((k, c)) if c.is_ascii_alphabetic() => (&digits[..k], c),
+        _ => (digits, ' '),
+    };
+    let multiplier = match unit {
+        ' ' => 1,
+        'K' => 1 << 10,
+        'M' => 1 << 20,
+        'G' => 1 << 30,
+        _ => return Err(format!("无法识别的大小: {}", spec)),
+    };
+    match number.trim().parse::<usize>().ok().filter(|&n| n > 0).and_then(|n| n.checked_mul(multiplier)) {
+        Some(size) => Ok(size),
+        None => Err(format!("无法识别的大小: {}", spec)),
+    }
+}
+
+/// 把连续的、写入同一张表且列相同的 INSERT 合并后按行数与字节数重新分批，
+/// 按语句流式处理并按 output 写出结果。
+/// Oracle 与达梦没有多行 VALUES，多行的批次写作 INSERT ALL
+pub fn rebatch_file(sql_path: &Path, output: &Output, options: &RebatchOptions, dialect: Dialect) -> Result<RebatchReport, String> {
+    let mut batcher = Batcher { options, dialect, report: RebatchReport::default(), run: None, since_commit: 0 };
+    rewrite_file_buffered(sql_path, output, dialect, |statement| match statement {
+        Some(statement) => batcher.statement(statement),
+        None => batcher.finish(),
+    })?;
+    Ok(batcher.report)
+}
+
+/// 同一目标的连续数据行
+struct Run {
+    /// 比较用的语句开头：INSERT 修饰词、表名与列列表
+    key: String,
+    /// 单条语句的开头，例如 `INSERT INTO t (a, b) VALUES`
+    head: String,
+    /// INSERT ALL 中的子句开头，例如 `t (a, b) VALUES`
+    target: String,
+    /// 尚未输出的行
+    rows: Vec<String>,
+    bytes: usize,
+    /// 第一条语句前的空白与注释，尚未输出时保存在这里
+    prefix: Option<String>,
+}
+
+struct Batcher<'a> {
+    options: &'a RebatchOptions,
+    dialect: Dialect,
+    report: RebatchReport,
+    run: Option<Run>,
+    /// 上次 COMMIT 之后输出的 INSERT 数
+    since_commit: usize,
+}
+
+impl Batcher<'_> {
+    fn statement(&mut self, statement: &RawStatement) -> String {
+        let tokens = tokenize_with(&statement.text, &self.dialect.lexer_config());
+        let Some(first) = next_significant(&tokens, 0) else {
+            return self.flush() + &statement.text;
+        };
+        if tokens[first].is_keyword("commit") {
+            self.since_commit = 0;
+        }
+        let rows = if tokens[first].is_keyword("insert") { self.rows(&tokens) } else { None };
+        let Some(rows) = rows else {
+            return self.flush() + &statement.text;
+        };
+        self.report.read += 1;
+
+        // 与当前批次目标相同且前面没有注释时直接并入，语句前的空白不再需要；
+        // 否则先输出当前批次，语句前的空白与注释留给新的批次
+        let commented = tokens[..first].iter().any(|t| matches!(t.kind, TokenKind::LineComment | TokenKind::BlockComment));
+        let continues = !commented && self.run.as_ref().zip(rows.first()).is_some_and(|(run, row)| run.key == row.0);
+        let mut out = if continues { String::new() } else { self.flush() };
+        let mut prefix = (!continues).then(|| tokens[..first].iter().map(|t| t.text).collect::<String>());
+
+        let oracle_like = self.oracle_like();
+        for (key, head, target, row) in rows {
+            if self.run.as_ref().is_some_and(|r| r.key != key) {
+                out += &self.flush();
+            }
+            let run = self.run.get_or_insert_with(|| Run { key, head, target, rows: Vec::new(), bytes: 0, prefix: prefix.take() });
+            // INSERT ALL 的每一行都要重复 INTO 子句
+            let size = if oracle_like { run.target.len() + row.len() + 9 } else { row.len() + 4 };
+            let full_rows = self.options.rows.is_some_and(|n| run.rows.len() >= n);
+            let full_bytes = self.options.max_bytes.is_some_and(|n| !run.rows.is_empty() && run.head.len() + run.bytes + size > n);
+            if full_rows || full_bytes {
+                out += &self.emit();
+            }
+            if let Some(run) = self.run.as_mut() {
+                if run.rows.is_empty() && self.options.max_bytes.is_some_and(|n| run.head.len() + size > n) {
+                    self.report.oversized += 1;
+                }
+                run.bytes += size;
+                run.rows.push(row);
+                self.report.rows += 1;
+            }
+        }
+        out
+    }
+
+    /// 语句中的数据行：(目标, 单条语句开头, INSERT ALL 子句开头, 行原文)。
+    /// 不是 `INSERT ... VALUES` 形式或 VALUES 之后还有其他子句时返回 None
+    fn rows(&mut self, tokens: &[Token]) -> Option<Vec<(String, String, String, String)>> {
+        let spans = insert_spans(tokens, self.dialect);
+        let last = spans.last()?;
+        let text = |a: usize, b: usize| -> String { tokens[a..b].iter().map(|t| t.text).collect() };
+        // INSERT ALL 以 SELECT ... FROM DUAL 结尾，其他语句在 VALUES 之后不能再有子句
+        let rest = next_significant(tokens, last.end).filter(|&k| !tokens[k].is_punct(';'));
+        if rest.is_some_and(|k| !(last.all && tokens[k].is_keyword("select"))) {
+            self.report.skipped += 1;
+            return None;
+        }
+
+        let mut rows = Vec::new();
+        for span in &spans {
+            let target = text(span.table.0, span.values + 1);
+            let head = if span.all { format!("INSERT INTO {}", target) } else { text(span.insert, span.values + 1) };
+            let key = format_tokens(&tokenize_with(&head, &self.dialect.lexer_config()), true);
+            for row in &span.rows {
+                rows.push((key.clone(), head.clone(), target.clone(), text(row.open, row.close + 1)));
+            }
+        }
+        Some(rows)
+    }
+
+    fn oracle_like(&self) -> bool {
+        matches!(self.dialect, Dialect::Oracle | Dialect::Dm)
+    }
+
+    /// 输出当前批次并结束
+    fn flush(&mut self) -> String {
+        let out = self.emit();
+        self.run = None;
+        out
+    }
+
+    /// 把当前批次中尚未输出的行写为一条语句
+    fn emit(&mut self) -> String {
+        let oracle_like = self.oracle_like();
+        let Some(run) = self.run.as_mut().filter(|r| !r.rows.is_empty()) else { return String::new() };
+        let rows = std::mem::take(&mut run.rows);
+        run.bytes = 0;
+
+        let statement = match rows.as_slice() {
+            [row] => format!("{} {};", run.head, row),
+            _ if oracle_like => {
+                let clauses: Vec<String> = rows.iter().map(|r| format!("  INTO {} {}", run.target, r)).collect();
+                format!("INSERT ALL\n{}\nSELECT 1 FROM DUAL;", clauses.join("\n"))
+            }
+            _ => format!("{}\n  {};", run.head, rows.join(",\n  ")),
+        };
+        // 第一条语句沿用原语句前的空白与注释，之后的语句各自另起一行
+        let mut out = match run.prefix.take() {
+            Some(prefix) => prefix + &statement,
+            None => format!("\n{}", statement),
+        };
+        self.report.written += 1;
+        self.since_commit += 1;
+        if self.options.commit_every.is_some_and(|n| self.since_commit >= n) {
+            out.push_str("\nCOMMIT;");
+            self.report.commits += 1;
+            self.since_commit = 0;
+        }
+        out
+    }
+
+    fn finish(&mut self) -> String {
+        let mut out = self.flush();
+        if self.options.commit_every.is_some() && self.since_commit > 0 {
+            out.push_str(if out.is_empty() { "COMMIT;\n" } else { "\nCOMMIT;\n" });
+            self.report.commits += 1;
+        }
+        out
+    }
+}
@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use super::cell::Cell;
+use super::dialect::Dialect;
+use super::insert::{insert_spans, replace_item};
+use super::lexer::tokenize_with;
+use super::stream::{open_statements, rewrite_file, Output, RawStatement, StatementReader};
+
+/// 外键：`表.列=被引用表.列`
+#[derive(Debug, Clone)]
+pub struct ForeignKey {
+    pub table: String,
+    pub column: String,
+    pub target: String,
+    pub target_column: String,
+}
+
+impl ForeignKey {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let split = |side: &str| {
+            side.trim()
+                .rsplit_once('.')
+                .filter(|(table, column)| !table.is_empty() && !column.is_empty())
+                .map(|(table, column)| (table.to_string(), column.to_string()))
+        };
+        let parsed = spec.split_once('=').and_then(|(from, to)| Some((split(from)?, split(to)?)));
+        let Some(((table, column), (target, target_column))) = parsed else {
+            return Err(format!("外键格式应为 表.列=被引用表.列: {}", spec));
+        };
+        Ok(ForeignKey { table, column, target, target_column })
+    }
+}
+
+/// 重新编号的参数
+#[derive(Debug, Clone)]
+pub struct RemapOptions {
+    /// 要重新编号主键的表
+    pub tables: Vec<String>,
+    /// 主键列
+    pub key: String,
+    /// 第一个新主键值
+    pub start: i64,
+    pub foreign_keys: Vec<ForeignKey>,
+}
+
+/// 一张表的新旧主键对照
+#[derive(Debug, Clone)]
+pub struct TableIds {
+    pub table: String,
+    /// 旧主键值（规范化后）到新主键值
+    pub ids: HashMap<String, i64>,
+}
+
+impl TableIds {
+    /// 新主键值的范围
+    pub fn range(&self) -> Option<(i64, i64)> {
+        Some((*self.ids.values().min()?, *self.ids.values().max()?))
+    }
+}
+
+/// 扫描所有文件后得到的新旧主键对照
+#[derive(Debug, Clone)]
+pub struct IdMap {
+    pub tables: Vec<TableIds>,
+    key: String,
+    /// (外键, 被引用表在 tables 中的位置)
+    foreign_keys: Vec<(ForeignKey, usize)>,
+    /// (文件, 行号, 说明) 形式的提示
+    pub warnings: Vec<(String, usize, String)>,
+}
+
+/// 改写结果
+#[derive(Debug, Default)]
+pub struct RemapReport {
+    /// 改写的主键值个数
+    pub keys: usize,
+    /// 改写的外键值个数
+    pub references: usize,
+    /// (行号, 说明) 形式的未处理原因
+    pub warnings: Vec<(usize, String)>,
+}
+
+/// 表名相同；其中一方不带 schema 时只比较去掉 schema 后的名称
+fn same_table(a: &str, b: &str) -> bool {
+    let bare = |name: &str| name.rsplit('.').next().unwrap_or(name).to_string();
+    a.eq_ignore_ascii_case(b) || ((!a.contains('.') || !b.contains('.')) && bare(a).eq_ignore_ascii_case(&bare(b)))
+}
+
+impl IdMap {
+    fn new(options: &RemapOptions) -> Result<Self, String> {
+        let tables: Vec<TableIds> = options.tables.iter().map(|t| TableIds { table: t.clone(), ids: HashMap::new() }).collect();
+        let foreign_keys = options
+            .foreign_keys
+            .iter()
+            .map(|fk| {
+                let target = tables
+                    .iter()
+                    .position(|t| same_table(&t.table, &fk.target))
+                    .ok_or_else(|| format!("外键 {}.{} 引用的表 {} 不在 --table 中", fk.table, fk.column, fk.target))?;
+                if !fk.target_column.eq_ignore_ascii_case(&options.key) {
+                    return Err(format!("外键 {}.{} 引用的列 {} 不是主键列 {}", fk.table, fk.column, fk.target_column, options.key));
+                }
+                Ok((fk.clone(), target))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(IdMap { tables, key: options.key.clone(), foreign_keys, warnings: Vec::new() })
+    }
+
+    /// 第一遍：按出现顺序为每个旧主键值分配新值。next 为各表下一个可用的新值，用尽时为 None
+    fn scan(&mut self, file: &str, statement: &RawStatement, next: &mut [Option<i64>], dialect: Dialect) -> Result<(), String> {
+        let tokens = tokenize_with(&statement.text, &dialect.lexer_config());
+        for insert in insert_spans(&tokens, dialect) {
+            let name = insert.table_name(&tokens);
+            let Some(t) = self.tables.iter().position(|t| same_table(&t.table, &name)) else { continue };
+            let at = |k: usize| statement.line + tokens[k].line - 1;
+            let Ok(positions) = insert.key_positions(&tokens, std::slice::from_ref(&self.key)) else {
+                self.warnings.push((file.to_string(), at(insert.insert), format!("{}: 缺少主键列 {}，跳过该语句", name, self.key)));
+                continue;
+            };
+            let columns = insert.column_names(&tokens).len();
+            // 值个数与列数不一致的行不分配新值，改写时提示并跳过
+            for row in insert.rows.iter().filter(|row| row.items.len() == columns) {
+                let Some(&(a, b)) = row.items.get(positions[0]) else { continue };
+                let value = match Cell::from_item(&tokens[a..b], dialect) {
+                    cell @ (Cell::Number(_) | Cell::Text(_)) => cell.normalized(),
+                    cell => {
+                        self.warnings.push((file.to_string(), at(a), format!("{}: 主键值 {} 无法重新编号", name, cell.to_csv())));
+                        continue;
+                    }
+                };
+                let ids = &mut self.tables[t].ids;
+                if ids.contains_key(&value) {
+                    self.warnings.push((file.to_string(), at(a), format!("{}: 主键 {} 重复出现，使用同一个新值", name, value)));
+                    continue;
+                }
+                let Some(id) = next[t] else {
+                    return Err(format!("{}: 新主键值超出 i64 范围，请调小 --start", self.tables[t].table));
+                };
+                ids.insert(value, id);
+                next[t] = id.checked_add(1);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 扫描所有文件中的 INSERT，为指定表的主键分配从 start 开始的连续新值
+pub fn plan_remap(paths: &[PathBuf], options: &RemapOptions, dialect: Dialect) -> Result<IdMap, String> {
+    let mut map = IdMap::new(options)?;
+    let mut next = vec![Some(options.start); map.tables.len()];
+    for path in paths {
+        let file = path.display().to_string();
+        let (reader, _) = open_statements(path, dialect)?;
+        for statement in reader {
+            map.scan(&file, &statement?, &mut next, dialect)?;
+        }
+    }
+    Ok(map)
+}
+
+/// 由 SQL 文本生成新旧主键对照
+pub fn plan_remap_sql(sql: &str, options: &RemapOptions, dialect: Dialect) -> Result<IdMap, String> {
+    let mut map = IdMap::new(options)?;
+    let mut next = vec![Some(options.start); map.tables.len()];
+    for statement in StatementReader::new(Cursor::new(sql), dialect).flatten() {
+        map.scan("", &statement, &mut next, dialect)?;
+    }
+    Ok(map)
+}
+
+/// 按对照改写 SQL 文件中的主键与外键值，按语句流式处理并按 output 写出结果
+pub fn remap_file(sql_path: &Path, output: &Output, map: &IdMap, dialect: Dialect) -> Result<RemapReport, String> {
+    let mut report = RemapReport::default();
+    rewrite_file(sql_path, output, dialect, |statement| remap_sql(&statement.text, statement.line, map, dialect, &mut report))?;
+    Ok(report)
+}
+
+/// 改写 SQL 文本中 INSERT 的主键与外键值。line 为文本首行在文件中的行号，用于提示
+pub fn remap_sql(sql: &str, line: usize, map: &IdMap, dialect: Dialect, report: &mut RemapReport) -> String {
+    let tokens = tokenize_with(sql, &dialect.lexer_config());
+    let mut out: Vec<Option<String>> = vec![None; tokens.len()];
+    let mut removed = vec![false; tokens.len()];
+    let at = |k: usize| line + tokens[k].line - 1;
+
+    for insert in insert_spans(&tokens, dialect) {
+        let name = insert.table_name(&tokens);
+        // (列位置, 对照表位置, 外键列名；主键为 None)
+        let mut targets: Vec<(usize, usize, Option<&str>)> = Vec::new();
+        if let Some(t) = map.tables.iter().position(|t| same_table(&t.table, &name)) {
+            targets.extend(insert.column_index(&tokens, &map.key).map(|p| (p, t, None)));
+        }
+        for (fk, t) in map.foreign_keys.iter().filter(|(fk, _)| same_table(&fk.table, &name)) {
+            match insert.column_index(&tokens, &fk.column) {
+                Some(p) => targets.push((p, *t, Some(fk.column.as_str()))),
+                None if insert.columns.is_none() => {
+                    report.warnings.push((at(insert.insert), format!("{}: 缺少列列表，无法确定外键列 {}", name, fk.column)));
+                }
+                None => {}
+            }
+        }
+
+        let columns = insert.column_names(&tokens).len();
+        for row in &insert.rows {
+            if !targets.is_empty() && row.items.len() != columns {
+                report.warnings.push((at(row.open), format!("{}: 值个数与列数不一致，跳过该行", name)));
+                continue;
+            }
+            for &(pos, t, reference) in &targets {
+                let Some(&(a, b)) = row.items.get(pos) else { continue };
+                let cell = Cell::from_item(&tokens[a..b], dialect);
+                let new = match &cell {
+                    Cell::Null => continue,
+                    Cell::Number(_) | Cell::Text(_) => map.tables[t].ids.get(&cell.normalized()),
+                    Cell::Raw(_) => None,
+                };
+                let Some(&new) = new else {
+                    if let Some(column) = reference {
+                        report.warnings.push((
+                            at(a),
+                            format!("{}.{}: 值 {} 在 {} 中不存在，保持原值", name, column, cell.to_csv(), map.tables[t].table),
+                        ));
+                    }
+                    continue;
+                };
+                let replacement = match cell {
+                    Cell::Text(_) => dialect.quote_string(&new.to_string()),
+                    _ => new.to_string(),
+                };
+                replace_item(&tokens, a, b, replacement, &mut out, &mut removed);
+                if reference.is_some() {
+                    report.references += 1;
+                } else {
+                    report.keys += 1;
+                }
+            }
+        }
+    }
+
+    tokens
+        .iter()
+        .enumerate()
+        .filter(|(k, _)| !removed[*k])
+        .map(|(k, t)| out[k].take().unwrap_or_else(|| t.text.to_string()))
+        .collect()
+}
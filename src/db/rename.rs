@@ -0,0 +1,146 @@
+use std::path::Path;
+
+use super::dialect::Dialect;
+use super::lexer::{next_significant, prev_significant, tokenize_with, Token, TokenKind};
+use super::stream::{rewrite_file, Output};
+
+/// 其后紧跟表名的关键字
+const TABLE_KEYWORDS: &[&str] = &["into", "from", "update", "join", "table", "tables", "references", "truncate", "exists"];
+/// 表名之后不是别名的关键字
+const NOT_ALIAS: &[&str] = &[
+    "where", "join", "inner", "left", "right", "full", "cross", "natural", "straight_join", "on", "using", "group",
+    "order", "limit", "set", "values", "value", "union", "having", "write", "read", "cascade", "restrict", "for", "as",
+    "partition", "select", "default",
+];
+
+/// 表名与 schema 的替换规则
+#[derive(Debug, Clone, Default)]
+pub struct Renames {
+    pub tables: Vec<(String, String)>,
+    pub schemas: Vec<(String, String)>,
+}
+
+/// 每条替换规则的替换次数，与规则顺序一致
+#[derive(Debug, Default)]
+pub struct RenameReport {
+    pub tables: Vec<usize>,
+    pub schemas: Vec<usize>,
+}
+
+impl RenameReport {
+    pub fn total(&self) -> usize {
+        self.tables.iter().chain(&self.schemas).sum()
+    }
+}
+
+/// 解析 `旧名=新名`
+pub fn parse_mapping(spec: &str) -> Result<(String, String), String> {
+    match spec.split_once('=') {
+        Some((old, new)) if !old.trim().is_empty() && !new.trim().is_empty() => Ok((old.trim().to_string(), new.trim().to_string())),
+        _ => Err(format!("替换规则格式应为 旧名=新名: {}", spec)),
+    }
+}
+
+/// 替换 SQL 文件中的表名与 schema 前缀，按语句流式处理并按 output 写出结果
+pub fn rename_file(sql_path: &Path, output: &Output, renames: &Renames, dialect: Dialect) -> Result<RenameReport, String> {
+    let mut report = RenameReport { tables: vec![0; renames.tables.len()], schemas: vec![0; renames.schemas.len()] };
+    rewrite_file(sql_path, output, dialect, |statement| rename_sql(&statement.text, renames, dialect, &mut report))?;
+    Ok(report)
+}
+
+/// 替换 SQL 文本中的表名与 schema 前缀。只改写表名位置（INTO、FROM、JOIN、TABLE 等之后）
+/// 以及表达式中的限定名，字符串与注释保持不变
+pub fn rename_sql(sql: &str, renames: &Renames, dialect: Dialect, report: &mut RenameReport) -> String {
+    report.tables.resize(renames.tables.len(), 0);
+    report.schemas.resize(renames.schemas.len(), 0);
+
+    let tokens = tokenize_with(sql, &dialect.lexer_config());
+    let mut out: Vec<Option<String>> = vec![None; tokens.len()];
+    let mut listed = vec![false; tokens.len()];
+    let first_word = next_significant(&tokens, 0).map(|k| tokens[k].text.to_ascii_lowercase()).unwrap_or_default();
+    let create_index = first_word == "create"
+        && tokens.iter().filter(|t| t.kind == TokenKind::Word).take(4).any(|t| t.is_keyword("index"));
+
+    let mut k = 0;
+    while k < tokens.len() {
+        let prev = if k == 0 { None } else { prev_significant(&tokens, k - 1) };
+        if !is_ident(&tokens[k]) || prev.is_some_and(|p| tokens[p].is_punct('.')) {
+            k += 1;
+            continue;
+        }
+        let chain = chain_at(&tokens, k);
+        let table_position = listed[k]
+            || prev.is_some_and(|p| {
+                let t = &tokens[p];
+                // ON DUPLICATE KEY UPDATE 与 ON UPDATE CASCADE 之后是列名或动作
+                let before = if p == 0 { None } else { prev_significant(&tokens, p - 1) };
+                let clause_update = t.is_keyword("update") && before.is_some_and(|b| tokens[b].is_keyword("key") || tokens[b].is_keyword("on"));
+                (TABLE_KEYWORDS.iter().any(|kw| t.is_keyword(kw)) && !clause_update)
+                    || (t.is_keyword("on") && create_index)
+                    || (t.is_keyword("using") && matches!(first_word.as_str(), "merge" | "delete"))
+            });
+        let n = chain.len();
+        let (table_index, schema_index) = if table_position {
+            (Some(n - 1), (n - 1).checked_sub(1))
+        } else {
+            (n.checked_sub(2), n.checked_sub(3))
+        };
+
+        if let Some(i) = table_index {
+            replace(&tokens[chain[i]], &mut out[chain[i]], &renames.tables, &mut report.tables, dialect);
+        }
+        if let Some(i) = schema_index {
+            replace(&tokens[chain[i]], &mut out[chain[i]], &renames.schemas, &mut report.schemas, dialect);
+        }
+
+        let last = chain[n - 1];
+        if table_position {
+            // FROM a x, b y 与 DROP TABLE a, b：逗号后的下一个名称同样是表名
+            let mut p = next_significant(&tokens, last + 1);
+            if p.is_some_and(|q| tokens[q].is_keyword("as")) {
+                p = p.and_then(|q| next_significant(&tokens, q + 1));
+            }
+            if p.is_some_and(|q| is_ident(&tokens[q]) && !NOT_ALIAS.iter().any(|kw| tokens[q].is_keyword(kw))) {
+                p = p.and_then(|q| next_significant(&tokens, q + 1));
+            }
+            if let Some(comma) = p.filter(|&q| tokens[q].is_punct(','))
+                && let Some(next) = next_significant(&tokens, comma + 1)
+            {
+                listed[next] = true;
+            }
+        }
+        k = last + 1;
+    }
+
+    tokens.iter().zip(out).map(|(t, o)| o.unwrap_or_else(|| t.text.to_string())).collect()
+}
+
+fn is_ident(token: &Token) -> bool {
+    matches!(token.kind, TokenKind::Word | TokenKind::QuotedIdent)
+}
+
+/// 从 k 开始的 `a.b.c` 限定名中各个标识符的下标
+fn chain_at(tokens: &[Token], k: usize) -> Vec<usize> {
+    let mut chain = vec![k];
+    let mut last = k;
+    while let Some(dot) = next_significant(tokens, last + 1).filter(|&d| tokens[d].is_punct('.')) {
+        match next_significant(tokens, dot + 1).filter(|&i| is_ident(&tokens[i])) {
+            Some(i) => {
+                chain.push(i);
+                last = i;
+            }
+            None => break,
+        }
+    }
+    chain
+}
+
+/// 名称匹配某条规则时按原有的引号风格写入新名称
+fn replace(token: &Token, out: &mut Option<String>, rules: &[(String, String)], counts: &mut [usize], dialect: Dialect) {
+    let name = token.ident_name();
+    if let Some(pos) = rules.iter().position(|(old, _)| old.eq_ignore_ascii_case(&name)) {
+        let new = &rules[pos].1;
+        *out = Some(if token.kind == TokenKind::QuotedIdent { dialect.quote_ident(new) } else { new.clone() });
+        counts[pos] += 1;
+    }
+}
@@ -0,0 +1,117 @@
+use std::path::Path;
+
+use super::dialect::Dialect;
+use super::encoding::{input_encoding, output_encoding};
+use super::insert::{item_text, parse_inserts, InsertSpan};
+use super::lexer::{next_significant, tokenize_with, Token};
+use super::stream::{open_statements_as, write_generated, Output};
+use crate::utils::progress::Progress;
+
+/// 生成回滚脚本的结果
+#[derive(Debug, Default)]
+pub struct RollbackReport {
+    /// 生成的 DELETE 语句数
+    pub deletes: usize,
+    /// (行号, 说明) 形式的未处理原因
+    pub warnings: Vec<(usize, String)>,
+}
+
+/// 读取 INSERT 脚本，按主键生成逆序的 DELETE 回滚脚本，按 output 写入新文件或输出到标准输出
+pub fn rollback_file(sql_path: &Path, output: &Output, keys: &[String], dialect: Dialect) -> Result<RollbackReport, String> {
+    let encoding = input_encoding(sql_path)?;
+    let (mut reader, total) = open_statements_as(sql_path, dialect, encoding)?;
+    let mut report = RollbackReport::default();
+    let mut deletes = Vec::new();
+    let mut progress = Progress::new("处理中", total);
+
+    while let Some(statement) = reader.next() {
+        let statement = statement?;
+        deletes.extend(rollback_sql(&statement.text, statement.line, keys, dialect, &mut report));
+        progress.update(reader.bytes_read());
+    }
+    progress.finish();
+
+    let name = sql_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    write_generated(&[sql_path], output, output_encoding(encoding), |writer| {
+        writeln!(writer, "-- 回滚脚本：根据 {} 生成，按插入的逆序删除", name).map_err(|e| format!("写入内容失败: {}", e))?;
+        for delete in deletes.iter().rev() {
+            writeln!(writer, "{}", delete).map_err(|e| format!("写入内容失败: {}", e))?;
+        }
+        Ok(())
+    })?;
+    Ok(report)
+}
+
+/// 为 SQL 文本中的每个 INSERT 元组生成一条 DELETE，按出现顺序返回。
+/// line 为文本首行在文件中的行号，用于提示
+pub fn rollback_sql(sql: &str, line: usize, keys: &[String], dialect: Dialect, report: &mut RollbackReport) -> Vec<String> {
+    let tokens = tokenize_with(sql, &dialect.lexer_config());
+    let mut deletes = Vec::new();
+
+    if let Some(first) = next_significant(&tokens, 0)
+        && ["update", "delete", "merge", "replace"].iter().any(|kw| tokens[first].is_keyword(kw))
+    {
+        let at = line + tokens[first].line - 1;
+        report.warnings.push((at, format!("{} 语句无法生成回滚语句", tokens[first].text.to_uppercase())));
+    }
+
+    let mut i = 0usize;
+    while i < tokens.len() {
+        let inserts = if tokens[i].is_keyword("insert") { parse_inserts(&tokens, i, dialect) } else { Vec::new() };
+        let Some(last) = inserts.last() else {
+            i += 1;
+            continue;
+        };
+        let at = line + tokens[i].line - 1;
+        i = last.end;
+
+        for insert in &inserts {
+            match key_positions(&tokens, insert, keys) {
+                Ok(positions) => deletes.extend(delete_rows(&tokens, insert, &positions, at, report)),
+                Err(reason) => report.warnings.push((at, format!("{}: {}", insert.table_name(&tokens), reason))),
+            }
+        }
+    }
+    report.deletes += deletes.len();
+    deletes
+}
+
+/// 主键列的原文及其在列列表中的位置
+fn key_positions(tokens: &[Token], insert: &InsertSpan, keys: &[String]) -> Result<Vec<(String, usize)>, String> {
+    let Some(columns) = &insert.columns else {
+        return Err("缺少列列表，无法确定主键位置".to_string());
+    };
+    keys.iter()
+        .map(|key| {
+            let pos = insert.column_index(tokens, key).ok_or_else(|| format!("列列表中缺少主键列 {}", key))?;
+            let (a, b) = columns.items[pos];
+            Ok((item_text(tokens, a, b), pos))
+        })
+        .collect()
+}
+
+fn delete_rows(tokens: &[Token], insert: &InsertSpan, keys: &[(String, usize)], at: usize, report: &mut RollbackReport) -> Vec<String> {
+    let table: String = tokens[insert.table.0..insert.table.1].iter().map(|t| t.text).collect();
+    let width = insert.columns.as_ref().map(|c| c.items.len()).unwrap_or(0);
+    let mut deletes = Vec::new();
+    for row in &insert.rows {
+        if row.items.len() != width {
+            report.warnings.push((at, format!("{}: 值个数与列数不一致，跳过该行", insert.table_name(tokens))));
+            continue;
+        }
+        let conditions: Vec<String> = keys
+            .iter()
+            .map(|(column, pos)| {
+                let (a, b) = row.items[*pos];
+                let value = item_text(tokens, a, b);
+                if value.eq_ignore_ascii_case("null") {
+                    format!("{} IS NULL", column)
+                } else {
+                    format!("{} = {}", column, value)
+                }
+            })
+            .collect();
+        deletes.push(format!("DELETE FROM {} WHERE {};", table, conditions.join(" AND ")));
+    }
+    deletes
+}
@@ -0,0 +1,380 @@
+use super::ddl::{ident_key, same_columns, ColumnDef, IndexDef, PrimaryKey, Schema, TableDef};
+use super::dialect::Dialect;
+
+/// 整数类型的宽度顺序
+const INTEGER_TYPES: &[&[&str]] = &[
+    &["TINYINT"],
+    &["SMALLINT", "INT2"],
+    &["MEDIUMINT"],
+    &["INT", "INTEGER", "INT4"],
+    &["BIGINT", "INT8"],
+];
+/// 带长度的字符串类型
+const STRING_TYPES: &[&str] =
+    &["CHAR", "VARCHAR", "VARCHAR2", "NCHAR", "NVARCHAR", "NVARCHAR2", "CHARACTER", "CHARACTER VARYING"];
+/// 大文本类型的容量顺序
+const TEXT_TYPES: &[&[&str]] = &[&["TINYTEXT"], &["TEXT", "CLOB", "NCLOB"], &["MEDIUMTEXT"], &["LONGTEXT"]];
+
+/// 迁移脚本中的一条语句
+#[derive(Debug, Clone)]
+pub struct MigrationStep {
+    pub table: String,
+    pub sql: String,
+    /// 会丢失数据的原因，例如删除表、删除列、缩小类型
+    pub destructive: Option<String>,
+    /// 可能执行失败的原因，例如新增 NOT NULL 列
+    pub note: Option<String>,
+}
+
+/// 由旧表结构变为新表结构的迁移语句，按执行顺序排列
+#[derive(Debug, Default)]
+pub struct Migration {
+    pub steps: Vec<MigrationStep>,
+}
+
+impl Migration {
+    pub fn destructive(&self) -> impl Iterator<Item = &MigrationStep> {
+        self.steps.iter().filter(|s| s.destructive.is_some())
+    }
+
+    /// 生成脚本。allow_destructive 为假时会丢失数据的语句以注释形式输出
+    pub fn render(&self, header: &str, allow_destructive: bool) -> String {
+        let mut out = format!("-- {}\n", header);
+        let mut table = "";
+        for step in &self.steps {
+            if step.table != table {
+                out.push_str(&format!("\n-- 表 {}\n", step.table));
+                table = &step.table;
+            }
+            if let Some(note) = &step.note {
+                out.push_str(&format!("-- [注意] {}\n", note));
+            }
+            match &step.destructive {
+                Some(reason) if !allow_destructive => {
+                    out.push_str(&format!("-- [危险] {}，确认后去掉注释执行\n", reason));
+                    for line in step.sql.lines() {
+                        out.push_str(&format!("-- {}\n", line));
+                    }
+                }
+                Some(reason) => out.push_str(&format!("-- [危险] {}\n{}\n", reason, step.sql)),
+                None => out.push_str(&format!("{}\n", step.sql)),
+            }
+        }
+        out
+    }
+}
+
+/// 比较两份表结构，生成迁移语句。顺序：新建表，删除旧索引与主键，新增与修改列，
+/// 新建主键与索引，修改注释，删除列，最后删除表
+pub fn schema_diff(old: &Schema, new: &Schema, dialect: Dialect) -> Migration {
+    let mut migration = Migration::default();
+    let mut builder = Builder { dialect, steps: &mut migration.steps };
+
+    for table in new.tables.iter().filter(|t| old.table(&t.name).is_none()) {
+        builder.create_table(table);
+    }
+    for table in &new.tables {
+        if let Some(before) = old.table(&table.name) {
+            builder.alter_table(before, table);
+        }
+    }
+    for table in new.tables.iter().filter_map(|t| old.table(&t.name).map(|before| (before, t))) {
+        builder.drop_columns(table.0, table.1);
+    }
+    for table in old.tables.iter().rev().filter(|t| new.table(&t.name).is_none()) {
+        builder.push(table, format!("DROP TABLE {};", table.text), Some(format!("删除表 {}，数据将丢失", table.name)), None);
+    }
+    migration
+}
+
+struct Builder<'a> {
+    dialect: Dialect,
+    steps: &'a mut Vec<MigrationStep>,
+}
+
+impl Builder<'_> {
+    fn push(&mut self, table: &TableDef, sql: String, destructive: Option<String>, note: Option<String>) {
+        self.steps.push(MigrationStep { table: table.name.clone(), sql, destructive, note });
+    }
+
+    fn mysql(&self) -> bool {
+        self.dialect == Dialect::Mysql
+    }
+
+    fn create_table(&mut self, table: &TableDef) {
+        self.push(table, format!("{};", table.ddl), None, None);
+        if let Some(pk) = table.primary_key.as_ref().filter(|pk| pk.standalone) {
+            self.add_primary_key(table, pk);
+        }
+        for index in table.indexes.iter().filter(|i| i.standalone) {
+            self.create_index(table, index);
+        }
+        // MySQL 的注释写在 CREATE TABLE 中
+        if !self.mysql() {
+            if table.comment.is_some() {
+                self.table_comment(table);
+            }
+            for column in table.columns.iter().filter(|c| c.comment.is_some()) {
+                self.column_comment(table, column);
+            }
+        }
+    }
+
+    fn alter_table(&mut self, old: &TableDef, new: &TableDef) {
+        // 先删除变化了的索引与主键，避免与列的修改冲突
+        for index in &old.indexes {
+            if !new.indexes.iter().any(|i| i.same_index(index) && i.same_definition(index)) {
+                self.drop_index(old, index);
+            }
+        }
+        let pk_changed = match (&old.primary_key, &new.primary_key) {
+            (Some(a), Some(b)) => !same_columns(&a.columns, &b.columns),
+            (None, None) => false,
+            _ => true,
+        };
+        if let Some(pk) = old.primary_key.as_ref().filter(|_| pk_changed) {
+            self.drop_primary_key(old, pk);
+        }
+
+        for (n, column) in new.columns.iter().enumerate() {
+            match old.column(&column.name) {
+                None => self.add_column(new, column, n.checked_sub(1).map(|p| &new.columns[p])),
+                Some(before) => self.modify_column(new, before, column),
+            }
+        }
+
+        if let Some(pk) = new.primary_key.as_ref().filter(|_| pk_changed) {
+            self.add_primary_key(new, pk);
+        }
+        for index in &new.indexes {
+            if !old.indexes.iter().any(|i| i.same_index(index) && i.same_definition(index)) {
+                self.create_index(new, index);
+            }
+        }
+        if old.comment != new.comment {
+            self.table_comment(new);
+        }
+    }
+
+    fn drop_columns(&mut self, old: &TableDef, new: &TableDef) {
+        for column in old.columns.iter().filter(|c| new.column(&c.name).is_none()) {
+            let sql = format!("ALTER TABLE {} DROP COLUMN {};", old.text, column.text);
+            self.push(old, sql, Some(format!("删除列 {}，数据将丢失", column.name)), None);
+        }
+    }
+
+    /// 新增列时的完整定义
+    fn column_definition(&self, column: &ColumnDef) -> String {
+        let mut def = format!("{} {}", column.text, column.data_type);
+        let default = column.default.as_ref().map(|d| format!(" DEFAULT {}", d)).unwrap_or_default();
+        let not_null = if column.nullable { "" } else { " NOT NULL" };
+        if self.mysql() {
+            def.push_str(not_null);
+            def.push_str(&default);
+        } else {
+            def.push_str(&default);
+            def.push_str(not_null);
+        }
+        if !column.extra.is_empty() {
+            def.push(' ');
+            def.push_str(&column.extra);
+        }
+        if let Some(comment) = column.comment.as_ref().filter(|_| self.mysql()) {
+            def.push_str(&format!(" COMMENT {}", self.dialect.quote_string(comment)));
+        }
+        def
+    }
+
+    fn add_column(&mut self, table: &TableDef, column: &ColumnDef, after: Option<&ColumnDef>) {
+        let def = self.column_definition(column);
+        let sql = match self.dialect {
+            Dialect::Mysql => {
+                let position = after.map_or(" FIRST".to_string(), |a| format!(" AFTER {}", a.text));
+                format!("ALTER TABLE {} ADD COLUMN {}{};", table.text, def, position)
+            }
+            Dialect::Oracle | Dialect::Dm => format!("ALTER TABLE {} ADD ({});", table.text, def),
+            Dialect::Postgres => format!("ALTER TABLE {} ADD COLUMN {};", table.text, def),
+        };
+        let note = (!column.nullable && column.default.is_none())
+            .then(|| format!("新增的 NOT NULL 列 {} 没有默认值，表中已有数据时会执行失败", column.name));
+        self.push(table, sql, None, note);
+        if !self.mysql() && column.comment.is_some() {
+            self.column_comment(table, column);
+        }
+    }
+
+    fn modify_column(&mut self, table: &TableDef, old: &ColumnDef, new: &ColumnDef) {
+        let type_changed = old.data_type != new.data_type;
+        let null_changed = old.nullable != new.nullable;
+        let default_changed = old.default != new.default;
+        let extra_changed = old.extra != new.extra;
+        let comment_changed = old.comment != new.comment;
+
+        let destructive = (type_changed && !widens(&old.data_type, &new.data_type))
+            .then(|| format!("列 {} 的类型由 {} 改为 {}，可能截断或无法转换已有数据", new.name, old.data_type, new.data_type));
+        let note = (null_changed && !new.nullable).then(|| format!("列 {} 改为 NOT NULL，已有空值时会执行失败", new.name));
+        let t = &table.text;
+        let c = &new.text;
+
+        match self.dialect {
+            Dialect::Mysql => {
+                // MySQL 的 MODIFY 需要完整的列定义，注释与其他属性一并写出
+                if type_changed || null_changed || default_changed || extra_changed || comment_changed {
+                    let sql = format!("ALTER TABLE {} MODIFY COLUMN {};", t, self.column_definition(new));
+                    self.push(table, sql, destructive, note);
+                }
+            }
+            Dialect::Oracle | Dialect::Dm => {
+                // 只写出变化的部分：对已经是 NOT NULL 的列再次声明 NOT NULL 会报错
+                let mut parts = Vec::new();
+                if type_changed {
+                    parts.push(new.data_type.clone());
+                }
+                if default_changed {
+                    parts.push(format!("DEFAULT {}", new.default.as_deref().unwrap_or("NULL")));
+                }
+                if null_changed {
+                    parts.push(if new.nullable { "NULL" } else { "NOT NULL" }.to_string());
+                }
+                if !parts.is_empty() {
+                    self.push(table, format!("ALTER TABLE {} MODIFY ({} {});", t, c, parts.join(" ")), destructive, note);
+                }
+            }
+            Dialect::Postgres => {
+                if type_changed {
+                    let sql = format!("ALTER TABLE {} ALTER COLUMN {} TYPE {};", t, c, new.data_type);
+                    self.push(table, sql, destructive, None);
+                }
+                if default_changed {
+                    let sql = match &new.default {
+                        Some(d) => format!("ALTER TABLE {} ALTER COLUMN {} SET DEFAULT {};", t, c, d),
+                        None => format!("ALTER TABLE {} ALTER COLUMN {} DROP DEFAULT;", t, c),
+                    };
+                    self.push(table, sql, None, None);
+                }
+                if null_changed {
+                    let action = if new.nullable { "DROP NOT NULL" } else { "SET NOT NULL" };
+                    self.push(table, format!("ALTER TABLE {} ALTER COLUMN {} {};", t, c, action), None, note);
+                }
+            }
+        }
+        if !self.mysql() && comment_changed {
+            self.column_comment(table, new);
+        }
+    }
+
+    fn drop_primary_key(&mut self, table: &TableDef, pk: &PrimaryKey) {
+        let sql = match (self.dialect, &pk.name) {
+            (Dialect::Postgres, Some(name)) => format!("ALTER TABLE {} DROP CONSTRAINT {};", table.text, name),
+            (Dialect::Postgres, None) => {
+                let short = table.name.rsplit('.').next().unwrap_or(&table.name);
+                format!("ALTER TABLE {} DROP CONSTRAINT {}_pkey;", table.text, short)
+            }
+            _ => format!("ALTER TABLE {} DROP PRIMARY KEY;", table.text),
+        };
+        self.push(table, sql, None, None);
+    }
+
+    fn add_primary_key(&mut self, table: &TableDef, pk: &PrimaryKey) {
+        let constraint = pk.name.as_ref().filter(|_| !self.mysql()).map(|n| format!("CONSTRAINT {} ", n)).unwrap_or_default();
+        let sql = format!("ALTER TABLE {} ADD {}PRIMARY KEY ({});", table.text, constraint, pk.columns.join(", "));
+        let note = Some("主键变更，已有数据中存在重复值时会执行失败".to_string());
+        self.push(table, sql, None, note);
+    }
+
+    fn drop_index(&mut self, table: &TableDef, index: &IndexDef) {
+        let Some(name) = &index.name else {
+            let note = format!("未命名的索引 ({}) 需要手工删除", index.columns.join(", "));
+            self.push(table, format!("-- ALTER TABLE {} DROP INDEX ?;", table.text), None, Some(note));
+            return;
+        };
+        let sql = match self.dialect {
+            Dialect::Mysql => format!("ALTER TABLE {} DROP INDEX {};", table.text, name),
+            _ if index.constraint => format!("ALTER TABLE {} DROP CONSTRAINT {};", table.text, name),
+            _ => format!("DROP INDEX {};", name),
+        };
+        self.push(table, sql, None, None);
+    }
+
+    fn create_index(&mut self, table: &TableDef, index: &IndexDef) {
+        let short = table.name.rsplit('.').next().unwrap_or(&table.name);
+        let name = index.name.clone().unwrap_or_else(|| {
+            let columns: Vec<String> = index.columns.iter().map(|c| ident_key(c).replace(|ch: char| !ch.is_alphanumeric(), "_")).collect();
+            format!("{}_{}_{}", if index.unique { "uk" } else { "idx" }, short.to_ascii_lowercase(), columns.join("_"))
+        });
+        let unique = if index.unique { "UNIQUE " } else { "" };
+        let sql = match self.dialect {
+            _ if index.constraint && index.unique => {
+                format!("ALTER TABLE {} ADD CONSTRAINT {} UNIQUE ({});", table.text, name, index.columns.join(", "))
+            }
+            Dialect::Mysql => format!("ALTER TABLE {} ADD {}INDEX {} ({});", table.text, unique, name, index.columns.join(", ")),
+            _ => format!("CREATE {}INDEX {} ON {} ({});", unique, name, table.text, index.columns.join(", ")),
+        };
+        let note = index.unique.then(|| "唯一索引，已有数据中存在重复值时会执行失败".to_string());
+        self.push(table, sql, None, note);
+    }
+
+    fn table_comment(&mut self, table: &TableDef) {
+        let comment = self.comment_literal(&table.comment);
+        let sql = if self.mysql() {
+            format!("ALTER TABLE {} COMMENT = {};", table.text, comment)
+        } else {
+            format!("COMMENT ON TABLE {} IS {};", table.text, comment)
+        };
+        self.push(table, sql, None, None);
+    }
+
+    fn column_comment(&mut self, table: &TableDef, column: &ColumnDef) {
+        let sql = format!("COMMENT ON COLUMN {}.{} IS {};", table.text, column.text, self.comment_literal(&column.comment));
+        self.push(table, sql, None, None);
+    }
+
+    fn comment_literal(&self, comment: &Option<String>) -> String {
+        match comment {
+            Some(c) => self.dialect.quote_string(c),
+            None if self.dialect == Dialect::Postgres => "NULL".to_string(),
+            None => "''".to_string(),
+        }
+    }
+}
+
+/// 类型变化是否只会扩大取值范围
+fn widens(old: &str, new: &str) -> bool {
+    let (old_base, old_args) = split_type(old);
+    let (new_base, new_args) = split_type(new);
+    let rank = |groups: &[&[&str]], base: &str| groups.iter().position(|g| g.contains(&base));
+
+    if old_base == new_base {
+        return match (old_args.as_slice(), new_args.as_slice()) {
+            ([a], [b]) => b >= a,
+            // DECIMAL(p, s)：小数位与整数位都不能减少
+            ([p, s], [q, t]) => t >= s && q - t >= p - s,
+            (a, b) => a == b || b.is_empty(),
+        };
+    }
+    if let (Some(a), Some(b)) = (rank(INTEGER_TYPES, &old_base), rank(INTEGER_TYPES, &new_base)) {
+        return b >= a;
+    }
+    if let (Some(a), Some(b)) = (rank(TEXT_TYPES, &old_base), rank(TEXT_TYPES, &new_base)) {
+        return b >= a;
+    }
+    if STRING_TYPES.contains(&old_base.as_str()) {
+        if rank(TEXT_TYPES, &new_base).is_some_and(|r| r > 0) {
+            return true;
+        }
+        if STRING_TYPES.contains(&new_base.as_str()) {
+            return matches!((old_args.as_slice(), new_args.as_slice()), ([a], [b]) if b >= a);
+        }
+    }
+    false
+}
+
+/// 拆分类型名与括号中的数字参数，例如 `DECIMAL(10,2)` -> ("DECIMAL", [10, 2])
+fn split_type(data_type: &str) -> (String, Vec<i64>) {
+    let (base, rest) = match data_type.split_once('(') {
+        Some((base, rest)) => (base, rest.split(')').next().unwrap_or_default()),
+        None => (data_type, ""),
+    };
+    let args = rest.split(',').filter_map(|a| a.split_whitespace().next()?.parse().ok()).collect();
+    (base.trim().to_string(), args)
+}
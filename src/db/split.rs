@@ -0,0 +1,581 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection};
+
+use super::cell::Cell;
+use super::ddl::{leading_words, name_at, name_parts, parse_schema, skip_words};
+use super::dialect::Dialect;
+use super::encoding::{create_encoded, input_encoding, output_encoding, EncodedWriter};
+use super::insert::{insert_spans, remove_items, ListSpan};
+use super::lexer::{next_significant, tokenize_with, Token, TokenKind};
+use super::stream::{open_statements, open_statements_as};
+
+/// 会话设置语句的起始关键字
+const SESSION_STATEMENTS: &[&str] = &["set", "use"];
+/// 表名之前可能出现的修饰词
+const TABLE_MODIFIERS: &[&str] =
+    &["if", "not", "exists", "only", "ignore", "low_priority", "quick", "into", "from", "table", "tables"];
+/// 不属于任何表的语句写入的文件
+const OTHERS_FILE: &str = "_others";
+
+/// 语句在脚本中的角色
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Role {
+    /// SET、USE 等会话设置，以及 MySQL 条件注释
+    Session,
+    /// 操作某张表的语句
+    Table(String),
+    /// COMMIT、UNLOCK TABLES 与只有注释的片段，跟随前一条语句
+    Attached,
+    /// 其他语句，例如视图、序列、存储过程
+    Other,
+}
+
+fn classify(tokens: &[Token], dialect: Dialect) -> Role {
+    if next_significant(tokens, 0).filter(|&k| !tokens[k].is_punct(';')).is_none() {
+        return if is_conditional(tokens) { Role::Session } else { Role::Attached };
+    }
+    let words = leading_words(tokens, 2);
+    let word = |n: usize| words.get(n).map(|(_, w)| w.as_str()).unwrap_or("");
+    match (word(0), word(1)) {
+        ("commit" | "rollback" | "unlock", _) => Role::Attached,
+        ("alter", "session") | ("start", "transaction") => Role::Session,
+        ("begin", _) if tokens.iter().filter(|t| t.is_significant()).count() <= 2 => Role::Session,
+        (w, _) if SESSION_STATEMENTS.contains(&w) => Role::Session,
+        _ => statement_table(tokens, dialect).map_or(Role::Other, Role::Table),
+    }
+}
+
+/// 只含 MySQL 条件注释 `/*!40101 SET ... */` 的语句
+fn is_conditional(tokens: &[Token]) -> bool {
+    tokens.iter().any(|t| t.kind == TokenKind::BlockComment && t.text.starts_with("/*!"))
+}
+
+/// 语句操作的表（去掉引号的限定名）：INSERT、REPLACE、UPDATE、DELETE、TRUNCATE、LOCK TABLES、
+/// CREATE/ALTER/DROP TABLE、CREATE INDEX 与 COMMENT ON。其他语句返回 None
+pub fn statement_table(tokens: &[Token], dialect: Dialect) -> Option<String> {
+    let words = leading_words(tokens, 8);
+    let (first, head) = words.first()?;
+    let second = words.get(1).map(|(k, w)| (*k, w.as_str()));
+    let name = |k: usize| skip_words(tokens, k, TABLE_MODIFIERS).and_then(|p| name_at(tokens, p)).map(|(name, _, _)| name);
+
+    match (head.as_str(), second) {
+        ("insert", _) => insert_spans(tokens, dialect).first().map(|i| i.table_name(tokens)).or_else(|| name(first + 1)),
+        ("replace" | "update" | "delete" | "truncate" | "lock", _) => name(first + 1),
+        ("alter" | "drop", Some((k, "table"))) => name(k + 1),
+        ("create", _) => {
+            let (k, kind) = created_object(&words)?;
+            match kind.as_str() {
+                "table" => name(k + 1),
+                "index" => name((k + 1..tokens.len()).find(|&p| tokens[p].is_keyword("on"))? + 1),
+                _ => None,
+            }
+        }
+        ("comment", Some((k, "on"))) => {
+            let kind = next_significant(tokens, k + 1)?;
+            let parts = name_parts(tokens, next_significant(tokens, kind + 1)?);
+            let parts = if tokens[kind].is_keyword("table") {
+                &parts[..]
+            } else if tokens[kind].is_keyword("column") {
+                &parts[..parts.len().saturating_sub(1)]
+            } else {
+                return None;
+            };
+            (!parts.is_empty()).then(|| parts.iter().map(|&p| tokens[p].ident_name()).collect::<Vec<_>>().join("."))
+        }
+        _ => None,
+    }
+}
+
+/// CREATE 语句创建的对象类型关键字及其下标
+fn created_object(words: &[(usize, String)]) -> Option<&(usize, String)> {
+    if words.first()?.1 != "create" {
+        return None;
+    }
+    words[1..].iter().find(|(_, w)| {
+        !matches!(w.as_str(), "or" | "replace" | "global" | "local" | "temporary" | "temp" | "unlogged" | "unique")
+    })
+}
+
+/// 表名匹配完整名称或去掉 schema 后的名称
+fn table_matches(patterns: &[String], table: &str) -> bool {
+    let short = table.rsplit('.').next().unwrap_or(table);
+    patterns.iter().any(|p| p.eq_ignore_ascii_case(table) || p.eq_ignore_ascii_case(short))
+}
+
+/// 输出用的语句原文：去掉开头的空行与结尾的空白。原文以空行开头且不是文件的第一段时保留一个空行
+fn tidy(text: &str, first: bool) -> String {
+    let start = text.find(|c: char| !c.is_whitespace()).unwrap_or(text.len());
+    let line_start = text[..start].rfind('\n').map_or(0, |p| p + 1);
+    let body = text[line_start..].trim_end();
+    if body.is_empty() {
+        return String::new();
+    }
+    let blank = !first && text[..line_start].matches('\n').count() >= 2;
+    format!("{}{}\n", if blank { "\n" } else { "" }, body)
+}
+
+fn write_text<W: Write>(out: &mut W, text: &str) -> Result<(), String> {
+    out.write_all(text.as_bytes()).map_err(|e| format!("写入文件失败: {}", e))
+}
+
+/// 拆分结果
+#[derive(Debug, Default)]
+pub struct SplitReport {
+    /// (表名, 文件, 语句数)，按表首次出现的顺序
+    pub files: Vec<(String, PathBuf, usize)>,
+    /// 写入每个文件开头与结尾的会话设置语句数
+    pub shared: usize,
+    /// 不属于任何表、写入 `_others.sql` 的语句数
+    pub others: usize,
+}
+
+/// 拆分时的一个输出文件
+struct Output {
+    table: Option<String>,
+    path: PathBuf,
+    writer: EncodedWriter<BufWriter<File>>,
+    statements: usize,
+    empty: bool,
+}
+
+impl Output {
+    fn write(&mut self, text: &str) -> Result<(), String> {
+        let text = tidy(text, self.empty);
+        self.empty &= text.is_empty();
+        write_text(&mut self.writer, &text)
+    }
+}
+
+/// 按表拆分 SQL 文件，每张表一个文件。开头与结尾的会话设置语句（SET、USE 等）写入每个文件，
+/// 两条表语句之间的会话设置归入其后的表，COMMIT 与 UNLOCK TABLES 归入其前的表，
+/// 其余语句写入 `_others.sql`。拆分出的文件与原文件编码相同
+pub fn split_file(sql_path: &Path, out_dir: &Path, dialect: Dialect) -> Result<SplitReport, String> {
+    fs::create_dir_all(out_dir).map_err(|e| format!("创建目录失败: {}", e))?;
+    let encoding = input_encoding(sql_path)?;
+    let (reader, _) = open_statements_as(sql_path, dialect, encoding)?;
+    let mut header: Vec<String> = Vec::new();
+    let mut pending: Vec<String> = Vec::new();
+    let mut outputs: Vec<Output> = Vec::new();
+    // 小写表名 -> 输出文件下标，None 对应 _others.sql
+    let mut index: HashMap<Option<String>, usize> = HashMap::new();
+    let mut used: HashSet<String> = HashSet::new();
+    let mut current: Option<usize> = None;
+
+    for statement in reader {
+        let statement = statement?;
+        let tokens = tokenize_with(&statement.text, &dialect.lexer_config());
+        let table = match classify(&tokens, dialect) {
+            Role::Session if current.is_none() => {
+                header.push(statement.text);
+                continue;
+            }
+            Role::Session => {
+                pending.push(statement.text);
+                continue;
+            }
+            Role::Attached => match current {
+                Some(o) => {
+                    outputs[o].write(&statement.text)?;
+                    continue;
+                }
+                None => {
+                    header.push(statement.text);
+                    continue;
+                }
+            },
+            Role::Table(name) => Some(name),
+            Role::Other => None,
+        };
+
+        let key = table.as_ref().map(|t| t.to_ascii_lowercase());
+        let o = match index.get(&key) {
+            Some(&o) => o,
+            None => {
+                let path = out_dir.join(file_name(table.as_deref().unwrap_or(OTHERS_FILE), &mut used));
+                let writer = create_encoded(&path, output_encoding(encoding))?;
+                let mut output = Output { table, path, writer, statements: 0, empty: true };
+                for text in &header {
+                    output.write(text)?;
+                }
+                outputs.push(output);
+                index.insert(key, outputs.len() - 1);
+                outputs.len() - 1
+            }
+        };
+        for text in pending.drain(..) {
+            outputs[o].write(&text)?;
+        }
+        outputs[o].write(&statement.text)?;
+        outputs[o].statements += 1;
+        current = Some(o);
+    }
+
+    let mut report = SplitReport { shared: header.len() + pending.len(), ..Default::default() };
+    for mut output in outputs {
+        for text in &pending {
+            output.write(text)?;
+        }
+        output.writer.flush().map_err(|e| format!("写入文件失败: {}", e))?;
+        match output.table {
+            Some(table) => report.files.push((table, output.path, output.statements)),
+            None => report.others = output.statements,
+        }
+    }
+    Ok(report)
+}
+
+/// 由表名生成文件名，与已有文件名重复（不区分大小写）时加序号
+fn file_name(table: &str, used: &mut HashSet<String>) -> String {
+    let base: String = table.chars().map(|c| if c.is_alphanumeric() || matches!(c, '_' | '-' | '.') { c } else { '_' }).collect();
+    let mut name = format!("{}.sql", base);
+    let mut n = 2;
+    while !used.insert(name.to_lowercase()) {
+        name = format!("{}_{}.sql", base, n);
+        n += 1;
+    }
+    name
+}
+
+/// 抽取条件
+#[derive(Debug, Clone, Default)]
+pub struct ExtractOptions {
+    /// 保留的表，为空时保留所有表
+    pub tables: Vec<String>,
+    /// 过滤 INSERT 行的 SQL 条件，例如 `status = 'A'`
+    pub filter: Option<String>,
+}
+
+/// 抽取结果
+#[derive(Debug, Default)]
+pub struct ExtractReport {
+    /// 输出的语句数（不含会话设置语句）
+    pub statements: usize,
+    /// 保留的行数
+    pub rows: usize,
+    /// 不满足条件而删除的行数
+    pub removed: usize,
+    /// (行号, 说明) 形式的未处理原因
+    pub warnings: Vec<(usize, String)>,
+}
+
+/// 按表与条件抽取语句与数据行。会话设置语句全部保留；指定条件时逐行求值，
+/// 只保留满足条件的 INSERT 行，其余语句不受条件影响
+pub fn extract_file<W: Write>(sql_path: &Path, options: &ExtractOptions, dialect: Dialect, mut out: W) -> Result<ExtractReport, String> {
+    let (reader, _) = open_statements(sql_path, dialect)?;
+    let mut filter = options.filter.as_deref().map(RowFilter::new).transpose()?;
+    let mut report = ExtractReport::default();
+    let mut keep = true;
+    let mut first = true;
+
+    for statement in reader {
+        let statement = statement?;
+        let tokens = tokenize_with(&statement.text, &dialect.lexer_config());
+        let text = match classify(&tokens, dialect) {
+            Role::Session => Some(statement.text.clone()),
+            Role::Attached => keep.then(|| statement.text.clone()),
+            Role::Other => {
+                keep = false;
+                None
+            }
+            Role::Table(table) => {
+                if let Some(filter) = filter.as_mut() {
+                    filter.learn(&statement.text, dialect);
+                }
+                keep = options.tables.is_empty() || table_matches(&options.tables, &table);
+                let text = match (keep, filter.as_mut()) {
+                    (true, Some(filter)) => filter.apply(&tokens, statement.line, dialect, &mut report),
+                    (true, None) => Some(statement.text.clone()),
+                    (false, _) => None,
+                };
+                report.statements += text.is_some() as usize;
+                text
+            }
+        };
+        if let Some(text) = text {
+            let text = tidy(&text, first);
+            first &= text.is_empty();
+            write_text(&mut out, &text)?;
+        }
+    }
+    out.flush().map_err(|e| format!("写入文件失败: {}", e))?;
+    Ok(report)
+}
+
+/// 在内存 SQLite 中对 INSERT 的每一行求值过滤条件
+struct RowFilter {
+    conn: Connection,
+    condition: String,
+    /// 由 CREATE TABLE 得到的列名，用于没有列列表的 INSERT
+    columns: HashMap<String, Vec<String>>,
+    /// 条件无法求值的表，只提示一次
+    failed: HashSet<String>,
+}
+
+impl RowFilter {
+    fn new(condition: &str) -> Result<Self, String> {
+        let conn = Connection::open_in_memory().map_err(|e| format!("创建 SQLite 数据库失败: {}", e))?;
+        Ok(RowFilter { conn, condition: condition.to_string(), columns: HashMap::new(), failed: HashSet::new() })
+    }
+
+    /// 记录建表语句中的列
+    fn learn(&mut self, sql: &str, dialect: Dialect) {
+        for table in parse_schema(sql, dialect).tables {
+            let names = table.columns.iter().map(|c| c.name.clone()).collect();
+            self.columns.insert(table.name.to_ascii_lowercase(), names);
+        }
+    }
+
+    /// 删除不满足条件的行，所有行都被删除时返回 None
+    fn apply(&mut self, tokens: &[Token], line: usize, dialect: Dialect, report: &mut ExtractReport) -> Option<String> {
+        let inserts = insert_spans(tokens, dialect);
+        let mut removed = vec![false; tokens.len()];
+        let mut emptied = 0usize;
+        let at = |k: usize| line + tokens[k].line - 1;
+
+        for insert in &inserts {
+            let table = insert.table_name(tokens);
+            let columns = match &insert.columns {
+                Some(_) => insert.column_names(tokens),
+                None => match self.columns.get(&table.to_ascii_lowercase()) {
+                    Some(columns) => columns.clone(),
+                    None => {
+                        report.warnings.push((at(insert.insert), format!("{}: 缺少列列表且未找到建表语句，保留所有行", table)));
+                        report.rows += insert.rows.len();
+                        continue;
+                    }
+                },
+            };
+
+            let mut dropped = Vec::new();
+            for (n, row) in insert.rows.iter().enumerate() {
+                if row.items.len() != columns.len() {
+                    report.warnings.push((at(row.open), format!("{}: 值个数与列数不一致，保留该行", table)));
+                    continue;
+                }
+                let values = row.items.iter().map(|&(a, b)| sqlite_value(Cell::from_item(&tokens[a..b], dialect)));
+                match self.matches(&columns, values) {
+                    Ok(true) => {}
+                    Ok(false) => dropped.push(n),
+                    Err(e) => {
+                        if self.failed.insert(table.to_ascii_lowercase()) {
+                            report.warnings.push((at(row.open), format!("{}: 过滤条件无法求值，保留该表的行: {}", table, e)));
+                        }
+                        dropped.clear();
+                        break;
+                    }
+                }
+            }
+            report.rows += insert.rows.len() - dropped.len();
+            report.removed += dropped.len();
+
+            if dropped.len() == insert.rows.len() {
+                // INSERT ALL 中删除整个 INTO 子句
+                if insert.all {
+                    removed[insert.insert..insert.end].iter_mut().for_each(|r| *r = true);
+                }
+                emptied += 1;
+            } else if !dropped.is_empty() {
+                let rows = ListSpan {
+                    open: insert.values,
+                    close: insert.end,
+                    items: insert.rows.iter().map(|r| (r.open, r.close + 1)).collect(),
+                };
+                remove_items(tokens, &rows, &dropped, &mut removed);
+            }
+        }
+
+        if !inserts.is_empty() && emptied == inserts.len() {
+            return None;
+        }
+        Some(tokens.iter().zip(removed).filter(|(_, r)| !r).map(|(t, _)| t.text).collect())
+    }
+
+    fn matches(&self, columns: &[String], values: impl Iterator<Item = Value>) -> Result<bool, String> {
+        let names: Vec<String> = columns.iter().map(|c| format!("\"{}\"", c.replace('"', "\"\""))).collect();
+        let params: Vec<String> = (1..=columns.len()).map(|n| format!("?{}", n)).collect();
+        let sql = format!(
+            "WITH r({}) AS (VALUES ({})) SELECT count(*) FROM r WHERE {}",
+            names.join(", "),
+            params.join(", "),
+            self.condition
+        );
+        // 语法错误只提示原因，不带生成的查询
+        let message = |e: rusqlite::Error| match e {
+            rusqlite::Error::SqlInputError { msg, .. } => msg,
+            e => e.to_string(),
+        };
+        let mut statement = self.conn.prepare_cached(&sql).map_err(message)?;
+        let count: i64 = statement.query_row(params_from_iter(values), |r| r.get(0)).map_err(message)?;
+        Ok(count > 0)
+    }
+}
+
+/// 单元格转为 SQLite 的值，表达式按原文作为文本比较
+fn sqlite_value(cell: Cell) -> Value {
+    match cell {
+        Cell::Null => Value::Null,
+        Cell::Number(n) => n.parse().map(Value::Integer).or_else(|_| n.parse().map(Value::Real)).unwrap_or(Value::Text(n)),
+        Cell::Text(text) | Cell::Raw(text) => Value::Text(text),
+    }
+}
+
+/// 合并结果
+#[derive(Debug, Default)]
+pub struct MergeReport {
+    /// 合并后的文件顺序
+    pub order: Vec<PathBuf>,
+    /// 输出的语句数
+    pub statements: usize,
+    /// 去掉的重复会话设置语句数
+    pub duplicates: usize,
+    pub warnings: Vec<String>,
+}
+
+/// 合并前对一个文件的扫描结果
+struct Script {
+    path: PathBuf,
+    /// 创建的表（小写）
+    created: Vec<String>,
+    /// 依赖的表（小写）：写入、修改或外键引用但未在本文件中创建的表
+    required: Vec<String>,
+    /// 开头与结尾的会话设置语句：(去重用的文本, 原文)
+    header: Vec<(String, String)>,
+    trailer: Vec<(String, String)>,
+    /// 正文语句的下标范围
+    body: (usize, usize),
+}
+
+/// 按依赖顺序合并多个 SQL 文件：创建被依赖表的文件在前，其余保持给定顺序。
+/// 各文件开头的会话设置语句去重后写在最前，结尾的写在最后
+pub fn merge_files<W: Write>(paths: &[PathBuf], dialect: Dialect, mut out: W) -> Result<MergeReport, String> {
+    let scripts = paths.iter().map(|p| scan_script(p, dialect)).collect::<Result<Vec<_>, _>>()?;
+    let mut report = MergeReport::default();
+    let order = dependency_order(&scripts, &mut report.warnings);
+    let mut first = true;
+    let mut emit = |out: &mut W, text: &str| -> Result<(), String> {
+        let text = tidy(text, first);
+        first &= text.is_empty();
+        write_text(out, &text)
+    };
+
+    let mut seen = HashSet::new();
+    for &n in &order {
+        for (key, text) in &scripts[n].header {
+            if seen.insert(key.clone()) {
+                emit(&mut out, text)?;
+            } else if !key.is_empty() {
+                report.duplicates += 1;
+            }
+        }
+    }
+    for &n in &order {
+        let script = &scripts[n];
+        report.order.push(script.path.clone());
+        if script.body.0 >= script.body.1 {
+            continue;
+        }
+        emit(&mut out, &format!("\n\n-- 来自 {}", script.path.display()))?;
+        let (reader, _) = open_statements(&script.path, dialect)?;
+        for statement in reader.take(script.body.1).skip(script.body.0) {
+            emit(&mut out, &statement?.text)?;
+            report.statements += 1;
+        }
+    }
+    let mut seen = HashSet::new();
+    for &n in &order {
+        for (key, text) in &scripts[n].trailer {
+            if seen.insert(key.clone()) {
+                emit(&mut out, text)?;
+            } else if !key.is_empty() {
+                report.duplicates += 1;
+            }
+        }
+    }
+    out.flush().map_err(|e| format!("写入文件失败: {}", e))?;
+    Ok(report)
+}
+
+fn scan_script(path: &Path, dialect: Dialect) -> Result<Script, String> {
+    let (reader, _) = open_statements(path, dialect).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut script =
+        Script { path: path.to_path_buf(), created: Vec::new(), required: Vec::new(), header: Vec::new(), trailer: Vec::new(), body: (0, 0) };
+    // 每条语句：(是否为会话设置, 是否只有空白与注释, 去重用的文本, 原文)
+    let mut statements = Vec::new();
+
+    for statement in reader {
+        let statement = statement?;
+        let tokens = tokenize_with(&statement.text, &dialect.lexer_config());
+        let role = classify(&tokens, dialect);
+        if let Role::Table(table) = &role {
+            let table = table.to_ascii_lowercase();
+            if created_object(&leading_words(&tokens, 6)).is_some_and(|(_, w)| w == "table") {
+                script.created.push(table);
+            } else {
+                script.required.push(table);
+            }
+        }
+        for k in (0..tokens.len()).filter(|&k| tokens[k].is_keyword("references")) {
+            if let Some((name, _, _)) = next_significant(&tokens, k + 1).and_then(|p| name_at(&tokens, p)) {
+                script.required.push(name.to_ascii_lowercase());
+            }
+        }
+        let blank = role == Role::Attached && next_significant(&tokens, 0).is_none();
+        statements.push((role == Role::Session, blank, statement_key(&tokens), statement.text));
+    }
+
+    let substantive = |s: &(bool, bool, String, String)| !s.0 && !s.1;
+    let start = statements.iter().position(substantive).unwrap_or(statements.len());
+    let end = statements.iter().rposition(substantive).map_or(start, |k| k + 1);
+    script.body = (start, end);
+    script.required.retain(|t| !script.created.contains(t));
+    for (k, (_, _, key, text)) in statements.into_iter().enumerate() {
+        if k < start {
+            script.header.push((key, text));
+        } else if k >= end {
+            script.trailer.push((key, text));
+        }
+    }
+    Ok(script)
+}
+
+/// 去重用的语句文本：忽略空白与行注释，保留 MySQL 条件注释
+fn statement_key(tokens: &[Token]) -> String {
+    tokens
+        .iter()
+        .filter(|t| !matches!(t.kind, TokenKind::Whitespace | TokenKind::LineComment))
+        .map(|t| t.text.trim())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 依赖排序：每次取给定顺序中第一个依赖都已输出的文件；存在循环依赖时按给定顺序继续
+fn dependency_order(scripts: &[Script], warnings: &mut Vec<String>) -> Vec<usize> {
+    let creator = |table: &String| scripts.iter().position(|s| s.created.contains(table));
+    let deps: Vec<Vec<usize>> = scripts
+        .iter()
+        .enumerate()
+        .map(|(n, s)| s.required.iter().filter_map(creator).filter(|&c| c != n).collect())
+        .collect();
+
+    let mut done = vec![false; scripts.len()];
+    let mut order = Vec::new();
+    while order.len() < scripts.len() {
+        let ready = (0..scripts.len()).find(|&n| !done[n] && deps[n].iter().all(|&d| done[d]));
+        let n = match ready {
+            Some(n) => n,
+            None => {
+                let n = (0..scripts.len()).find(|&n| !done[n]).unwrap_or_default();
+                warnings.push(format!("{} 与其他文件存在循环依赖，按给定顺序合并", scripts[n].path.display()));
+                n
+            }
+        };
+        done[n] = true;
+        order.push(n);
+    }
+    order
+}
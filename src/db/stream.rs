@@ -0,0 +1,299 @@
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use super::dialect::Dialect;
+use super::encoding::{input_encoding, output_encoding, stdout_encoding, DecodeReader, EncodedWriter, FileEncoding};
+use super::lexer::{find_block_end, find_statement_end, next_significant, tokenize_with, ScanState};
+use crate::utils::progress::Progress;
+
+/// 从输入中切分出的一条语句，包含其前面的空白与注释
+#[derive(Debug, Clone)]
+pub struct RawStatement {
+    pub text: String,
+    /// 语句文本首字符所在行号（从 1 开始）
+    pub line: usize,
+}
+
+/// 逐条读取 SQL 语句，内存占用只取决于最长的单条语句。
+/// 所有语句依次拼接即为原始输入；Oracle / 达梦的 PL/SQL 块读到单独一行的 `/` 为止
+pub struct StatementReader<R> {
+    reader: R,
+    dialect: Dialect,
+    buf: String,
+    /// 当前语句是 PL/SQL 块
+    block: bool,
+    /// buf 中的扫描进度，读入新的一行后从这里继续
+    scan: ScanState,
+    line: usize,
+    bytes_read: u64,
+    eof: bool,
+}
+
+impl<R: BufRead> StatementReader<R> {
+    pub fn new(reader: R, dialect: Dialect) -> Self {
+        StatementReader {
+            reader,
+            dialect,
+            buf: String::new(),
+            block: false,
+            scan: ScanState::default(),
+            line: 1,
+            bytes_read: 0,
+            eof: false,
+        }
+    }
+
+    /// 已从输入读取的字节数
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    fn take(&mut self, end: usize) -> RawStatement {
+        let rest = self.buf.split_off(end);
+        let text = std::mem::replace(&mut self.buf, rest);
+        let line = self.line;
+        self.line += text.bytes().filter(|&b| b == b'\n').count();
+        self.scan = ScanState::default();
+        self.block = false;
+        RawStatement { text, line }
+    }
+}
+
+impl<R: BufRead> Iterator for StatementReader<R> {
+    type Item = Result<RawStatement, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let config = self.dialect.lexer_config();
+            let end = if self.block {
+                find_block_end(&self.buf, &mut self.scan, &config)
+            } else {
+                find_statement_end(&self.buf, &mut self.scan, &config)
+            };
+            if let Some(end) = end {
+                if !self.block && self.dialect.has_plsql_blocks() && is_plsql_block(&self.buf[..end], self.dialect) {
+                    // 块内的分号不结束语句，改为查找 `/`
+                    self.block = true;
+                    self.scan = ScanState::default();
+                    continue;
+                }
+                return Some(Ok(self.take(end)));
+            }
+
+            if self.eof {
+                if self.buf.is_empty() {
+                    return None;
+                }
+                let len = self.buf.len();
+                return Some(Ok(self.take(len)));
+            }
+
+            match self.reader.read_line(&mut self.buf) {
+                Ok(0) => self.eof = true,
+                Ok(n) => self.bytes_read += n as u64,
+                Err(e) => {
+                    self.eof = true;
+                    self.buf.clear();
+                    return Some(Err(format!("读取文件失败: {}", e)));
+                }
+            }
+        }
+    }
+}
+
+/// 语句是否以 PL/SQL 块开头：BEGIN、DECLARE 或 CREATE [OR REPLACE] PROCEDURE/FUNCTION/PACKAGE/TRIGGER/TYPE
+pub(crate) fn is_plsql_block(text: &str, dialect: Dialect) -> bool {
+    // 只需要开头的几个关键字
+    let mut cut = text.len().min(1024);
+    while !text.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    let tokens = tokenize_with(&text[..cut], &dialect.lexer_config());
+    let mut words = Vec::new();
+    let mut k = 0;
+    while words.len() < 6 {
+        let Some(i) = next_significant(&tokens, k) else { break };
+        words.push(tokens[i].text.to_ascii_lowercase());
+        k = i + 1;
+    }
+
+    match words.first().map(|w| w.as_str()) {
+        Some("begin") | Some("declare") => true,
+        Some("create") => words[1..]
+            .iter()
+            .map(|w| w.as_str())
+            .find(|w| !matches!(*w, "or" | "replace" | "editionable" | "noneditionable" | "editioning"))
+            .is_some_and(|w| matches!(w, "procedure" | "function" | "package" | "trigger" | "type")),
+        _ => false,
+    }
+}
+
+/// 按编码解码后逐条读取语句的文件读取器
+pub type FileStatements = StatementReader<BufReader<DecodeReader<File>>>;
+
+/// 打开 SQL 文件并按语句逐条读取，编码由 `--encoding` 指定或按内容识别
+pub fn open_statements(sql_path: &Path, dialect: Dialect) -> Result<(FileStatements, u64), String> {
+    open_statements_as(sql_path, dialect, input_encoding(sql_path)?)
+}
+
+/// 按指定编码打开 SQL 文件并按语句逐条读取
+pub fn open_statements_as(sql_path: &Path, dialect: Dialect, encoding: FileEncoding) -> Result<(FileStatements, u64), String> {
+    let file = File::open(sql_path).map_err(|e| format!("读取文件失败: {}", e))?;
+    let total = file.metadata().map(|m| m.len()).unwrap_or(0);
+    Ok((StatementReader::new(BufReader::new(DecodeReader::new(file, encoding)), dialect), total))
+}
+
+/// 改写结果的去向
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Output {
+    /// 原地改写；backup 为备份文件名后缀，None 时不备份
+    InPlace { backup: Option<String> },
+    /// 写入另一个文件，原文件不变
+    File(PathBuf),
+    /// 写到标准输出，原文件不变
+    Stdout,
+}
+
+impl Default for Output {
+    /// 原地改写，不生成备份
+    fn default() -> Self {
+        Output::InPlace { backup: None }
+    }
+}
+
+impl Output {
+    /// 是否覆盖原文件
+    pub fn in_place(&self) -> bool {
+        matches!(self, Output::InPlace { .. })
+    }
+
+    /// 备份文件路径
+    pub fn backup_path(&self, sql_path: &Path) -> Option<PathBuf> {
+        match self {
+            Output::InPlace { backup: Some(suffix) } => {
+                Some(sql_path.with_file_name(format!("{}{}", sql_path.file_name()?.to_string_lossy(), suffix)))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// 同目录下的临时文件路径
+fn temp_path(sql_path: &Path) -> PathBuf {
+    let name = sql_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    sql_path.with_file_name(format!(".{}.{}.tmp", name, std::process::id()))
+}
+
+/// 逐条语句改写 SQL 文件，结果按 output 写回原文件、写入新文件或输出到标准输出。
+/// 写入文件时先写同目录临时文件，完成后原子替换；原地改写时可先生成备份。
+/// 文件按原文件的编码（或 `--output-encoding`）写入
+pub fn rewrite_file<F>(sql_path: &Path, output: &Output, dialect: Dialect, mut transform: F) -> Result<(), String>
+where
+    F: FnMut(&RawStatement) -> String,
+{
+    rewrite_file_buffered(sql_path, output, dialect, |statement| statement.map(&mut transform).unwrap_or_default())
+}
+
+/// 与 [`rewrite_file`] 相同，但读完所有语句后再以 None 调用一次 transform，
+/// 便于先缓存若干条语句再一起输出
+pub fn rewrite_file_buffered<F>(sql_path: &Path, output: &Output, dialect: Dialect, mut transform: F) -> Result<(), String>
+where
+    F: FnMut(Option<&RawStatement>) -> String,
+{
+    let encoding = input_encoding(sql_path)?;
+    let (mut reader, total) = open_statements_as(sql_path, dialect, encoding)?;
+    let Some(target) = output_target(sql_path, output)? else {
+        let mut writer = EncodedWriter::new(io::stdout().lock(), stdout_encoding());
+        return pump(&mut reader, total, &mut transform, &mut writer);
+    };
+    write_atomically(sql_path, target, output, |file| {
+        let mut writer = EncodedWriter::new(BufWriter::new(file), output_encoding(encoding));
+        pump(&mut reader, total, &mut transform, &mut writer)
+    })
+}
+
+/// 写出由输入文件生成的新内容（回滚脚本、导入脚本等），不修改输入文件。
+/// 写入文件时先写临时文件再原子替换，按 encoding 编码；输出到标准输出时按 `--output-encoding` 或 UTF-8
+pub fn write_generated<F>(sources: &[&Path], output: &Output, encoding: FileEncoding, write: F) -> Result<(), String>
+where
+    F: FnOnce(&mut dyn Write) -> Result<(), String>,
+{
+    let target = match output {
+        Output::InPlace { .. } => return Err("该命令生成新文件，不能原地修改".to_string()),
+        Output::Stdout => {
+            let mut writer = EncodedWriter::new(io::stdout().lock(), stdout_encoding());
+            write(&mut writer)?;
+            return writer.flush().map_err(|e| format!("写入内容失败: {}", e));
+        }
+        Output::File(target) => target,
+    };
+    for source in sources {
+        output_target(source, output)?;
+    }
+    let source = sources.first().copied().unwrap_or(target);
+    write_atomically(source, target, output, |file| {
+        let mut writer = EncodedWriter::new(BufWriter::new(file), encoding);
+        write(&mut writer)?;
+        writer.flush().map_err(|e| format!("写入内容失败: {}", e))
+    })
+}
+
+/// 结果要写入的文件，输出到标准输出时为 None
+pub(crate) fn output_target<'a>(sql_path: &'a Path, output: &'a Output) -> Result<Option<&'a Path>, String> {
+    match output {
+        Output::Stdout => Ok(None),
+        Output::File(path) => {
+            if let (Ok(a), Ok(b)) = (fs::canonicalize(sql_path), fs::canonicalize(path))
+                && a == b
+            {
+                return Err(format!("输出文件不能与输入文件相同: {}", path.display()));
+            }
+            Ok(Some(path.as_path()))
+        }
+        Output::InPlace { .. } => Ok(Some(sql_path)),
+    }
+}
+
+/// 按需备份原文件后，由 write 写入同目录临时文件，完成后原子替换 target
+pub(crate) fn write_atomically<F>(sql_path: &Path, target: &Path, output: &Output, write: F) -> Result<(), String>
+where
+    F: FnOnce(File) -> Result<(), String>,
+{
+    if let Some(bak_path) = output.backup_path(sql_path) {
+        fs::copy(sql_path, &bak_path).map_err(|e| format!("写入备份失败: {}", e))?;
+    }
+
+    let tmp_path = temp_path(target);
+    let result = (|| {
+        let file = File::create(&tmp_path).map_err(|e| format!("创建临时文件失败: {}", e))?;
+        write(file)?;
+        if let Ok(meta) = fs::metadata(sql_path) {
+            let _ = fs::set_permissions(&tmp_path, meta.permissions());
+        }
+        fs::rename(&tmp_path, target).map_err(|e| format!("替换原文件失败: {}", e))
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+/// 把每条语句的改写结果依次写入 writer
+fn pump<R, W, F>(reader: &mut StatementReader<R>, total: u64, transform: &mut F, writer: &mut W) -> Result<(), String>
+where
+    R: BufRead,
+    W: Write,
+    F: FnMut(Option<&RawStatement>) -> String,
+{
+    let mut progress = Progress::new("处理中", total);
+    while let Some(statement) = reader.next() {
+        let statement = statement?;
+        writer.write_all(transform(Some(&statement)).as_bytes()).map_err(|e| format!("写入内容失败: {}", e))?;
+        progress.update(reader.bytes_read());
+    }
+    writer.write_all(transform(None).as_bytes()).map_err(|e| format!("写入内容失败: {}", e))?;
+    progress.finish();
+    writer.flush().map_err(|e| format!("写入内容失败: {}", e))
+}
@@ -0,0 +1,286 @@
+use std::path::Path;
+
+use super::cell::Cell;
+use super::dialect::Dialect;
+use super::insert::{item_text, parse_inserts, remove_items, InsertSpan, ListSpan};
+use super::lexer::{next_significant, prev_significant, tokenize_with, Token};
+use super::stream::{rewrite_file, Output};
+
+/// 改写为 upsert 的处理结果
+#[derive(Debug, Default)]
+pub struct UpsertReport {
+    /// 改写的 INSERT 语句数
+    pub statements: usize,
+    /// 涉及的数据行数
+    pub rows: usize,
+    /// (行号, 说明) 形式的未改写原因
+    pub warnings: Vec<(usize, String)>,
+}
+
+/// 处理 SQL 文件：把 INSERT 改写为按主键幂等的 upsert 语句。
+/// 按语句流式处理并按 output 写出结果。
+pub fn upsert_file(sql_path: &Path, output: &Output, keys: &[String], dialect: Dialect) -> Result<UpsertReport, String> {
+    let mut report = UpsertReport::default();
+    rewrite_file(sql_path, output, dialect, |statement| upsert_sql(&statement.text, statement.line, keys, dialect, &mut report))?;
+    Ok(report)
+}
+
+/// 改写 SQL 文本中的 INSERT：MySQL 追加 ON DUPLICATE KEY UPDATE，PostgreSQL 追加 ON CONFLICT，
+/// Oracle / 达梦改写为 MERGE INTO。line 为文本首行在文件中的行号，用于提示
+pub fn upsert_sql(sql: &str, line: usize, keys: &[String], dialect: Dialect, report: &mut UpsertReport) -> String {
+    let tokens = tokenize_with(sql, &dialect.lexer_config());
+    let mut out: Vec<Option<String>> = vec![None; tokens.len()];
+    let mut removed = vec![false; tokens.len()];
+
+    let mut i = 0usize;
+    while i < tokens.len() {
+        let inserts = if tokens[i].is_keyword("insert") { parse_inserts(&tokens, i, dialect) } else { Vec::new() };
+        let Some(last) = inserts.last() else {
+            i += 1;
+            continue;
+        };
+        let start = i;
+        i = last.end;
+
+        let at = line + tokens[start].line - 1;
+        let mut ok = true;
+        for insert in &inserts {
+            if let Err(reason) = check_insert(&tokens, insert, keys) {
+                report.warnings.push((at, format!("{}: {}", insert.table_name(&tokens), reason)));
+                ok = false;
+            }
+        }
+        if !ok {
+            continue;
+        }
+
+        // MySQL 依次处理重复行；ON CONFLICT 与 MERGE 在同一语句中遇到重复主键会报错，只保留最后一行
+        let kept: Vec<Vec<usize>> = inserts
+            .iter()
+            .map(|insert| {
+                if dialect == Dialect::Mysql {
+                    (0..insert.rows.len()).collect()
+                } else {
+                    last_rows_by_key(&tokens, insert, keys, dialect)
+                }
+            })
+            .collect();
+        let rows: usize = kept.iter().map(|k| k.len()).sum();
+        match dialect {
+            Dialect::Mysql | Dialect::Postgres => {
+                let insert = &inserts[0];
+                if next_significant(&tokens, insert.end).is_some_and(|k| tokens[k].is_keyword("on")) {
+                    report.warnings.push((at, format!("{}: 语句已包含冲突处理子句", insert.table_name(&tokens))));
+                    continue;
+                }
+                if tokens.get(next_significant(&tokens, start + 1).unwrap_or(start)).is_some_and(|t| t.is_keyword("ignore")) {
+                    report.warnings.push((at, format!("{}: INSERT IGNORE 无需改写", insert.table_name(&tokens))));
+                    continue;
+                }
+                // 追加在最后一个元组之后，RETURNING 等子句之前
+                let clause = if dialect == Dialect::Mysql {
+                    on_duplicate_key(&tokens, insert, keys)
+                } else {
+                    on_conflict(&tokens, insert, keys)
+                };
+                let dropped: Vec<usize> = (0..insert.rows.len()).filter(|n| !kept[0].contains(n)).collect();
+                if !dropped.is_empty() {
+                    let rows = ListSpan {
+                        open: insert.values,
+                        close: insert.end,
+                        items: insert.rows.iter().map(|r| (r.open, r.close + 1)).collect(),
+                    };
+                    remove_items(&tokens, &rows, &dropped, &mut removed);
+                }
+                let close = insert.end - 1;
+                out[close] = Some(format!("{}\n{}", tokens[close].text, clause));
+            }
+            Dialect::Oracle | Dialect::Dm => {
+                let mut merges = Vec::new();
+                for (insert, kept) in inserts.iter().zip(&kept) {
+                    let (merge, untyped) = merge_into(&tokens, insert, kept, keys, dialect);
+                    if !untyped.is_empty() {
+                        let table = insert.table_name(&tokens);
+                        report.warnings.push((at, format!("{}: 无法确定列 {} 的类型，首行的 NULL 可能导致 UNION ALL 类型不一致", table, untyped.join(", "))));
+                    }
+                    merges.push(merge);
+                }
+                // INSERT ALL 连同结尾的 SELECT ... FROM DUAL 一起替换
+                let stop = if inserts[0].all {
+                    let stmt_end = (last.end..tokens.len()).find(|&p| tokens[p].is_punct(';')).unwrap_or(tokens.len());
+                    prev_significant(&tokens, stmt_end - 1).filter(|&p| p >= last.end).map(|p| p + 1).unwrap_or(last.end)
+                } else {
+                    last.end
+                };
+                for o in &mut out[start..stop] {
+                    *o = Some(String::new());
+                }
+                out[start] = Some(merges.join(";\n"));
+                i = stop;
+            }
+        }
+        for (insert, kept) in inserts.iter().zip(&kept) {
+            if kept.len() < insert.rows.len() {
+                let dropped = insert.rows.len() - kept.len();
+                report.warnings.push((at, format!("{}: 同一语句中有 {} 行主键重复，只保留最后一行", insert.table_name(&tokens), dropped)));
+            }
+        }
+        report.statements += 1;
+        report.rows += rows;
+    }
+
+    tokens
+        .iter()
+        .zip(out)
+        .zip(removed)
+        .filter(|(_, removed)| !removed)
+        .map(|((t, o), _)| o.unwrap_or_else(|| t.text.to_string()))
+        .collect()
+}
+
+/// 主键相同的行只保留最后一行，返回保留行的下标
+fn last_rows_by_key(tokens: &[Token], insert: &InsertSpan, keys: &[String], dialect: Dialect) -> Vec<usize> {
+    let Ok(positions) = insert.key_positions(tokens, keys) else { return (0..insert.rows.len()).collect() };
+    let row_keys: Vec<Vec<String>> = insert
+        .rows
+        .iter()
+        .map(|row| positions.iter().map(|&p| Cell::from_item(&tokens[row.items[p].0..row.items[p].1], dialect).normalized()).collect())
+        .collect();
+    (0..row_keys.len()).filter(|&n| !row_keys[n + 1..].contains(&row_keys[n])).collect()
+}
+
+/// 检查语句能否改写：需要列列表、包含全部主键列，且每个元组的值个数与列数一致
+fn check_insert(tokens: &[Token], insert: &InsertSpan, keys: &[String]) -> Result<(), String> {
+    let Some(columns) = &insert.columns else {
+        return Err("缺少列列表，无法确定主键位置".to_string());
+    };
+    let missing: Vec<&str> = keys
+        .iter()
+        .filter(|k| insert.column_index(tokens, k).is_none())
+        .map(|k| k.as_str())
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("列列表中缺少主键列 {}", missing.join(", ")));
+    }
+    if insert.rows.iter().any(|r| r.items.len() != columns.items.len()) {
+        return Err("值个数与列数不一致".to_string());
+    }
+    Ok(())
+}
+
+/// 列的原文（保留引号）及是否为主键列
+fn columns(tokens: &[Token], insert: &InsertSpan, keys: &[String]) -> Vec<(String, bool)> {
+    let names = insert.column_names(tokens);
+    let list = insert.columns.as_ref().map(|c| c.items.as_slice()).unwrap_or(&[]);
+    list.iter()
+        .zip(names)
+        .map(|(&(a, b), name)| (item_text(tokens, a, b), keys.iter().any(|k| k.eq_ignore_ascii_case(&name))))
+        .collect()
+}
+
+fn on_duplicate_key(tokens: &[Token], insert: &InsertSpan, keys: &[String]) -> String {
+    let cols = columns(tokens, insert, keys);
+    let updates: Vec<String> = cols.iter().filter(|(_, key)| !key).map(|(c, _)| format!("{c} = VALUES({c})")).collect();
+    if updates.is_empty() {
+        // 只有主键列时保持原值，仅用于忽略重复
+        let key = &cols.iter().find(|(_, key)| *key).unwrap().0;
+        return format!("ON DUPLICATE KEY UPDATE {key} = {key}");
+    }
+    format!("ON DUPLICATE KEY UPDATE {}", updates.join(", "))
+}
+
+fn on_conflict(tokens: &[Token], insert: &InsertSpan, keys: &[String]) -> String {
+    let cols = columns(tokens, insert, keys);
+    let key_list: Vec<&str> = cols.iter().filter(|(_, key)| *key).map(|(c, _)| c.as_str()).collect();
+    let updates: Vec<String> = cols.iter().filter(|(_, key)| !key).map(|(c, _)| format!("{c} = EXCLUDED.{c}")).collect();
+    if updates.is_empty() {
+        return format!("ON CONFLICT ({}) DO NOTHING", key_list.join(", "));
+    }
+    format!("ON CONFLICT ({}) DO UPDATE SET {}", key_list.join(", "), updates.join(", "))
+}
+
+/// 生成 MERGE INTO，源数据为 rows 中各行的 UNION ALL；同时返回无法确定类型的列
+fn merge_into(tokens: &[Token], insert: &InsertSpan, rows: &[usize], keys: &[String], dialect: Dialect) -> (String, Vec<String>) {
+    let cols = columns(tokens, insert, keys);
+    let table: String = tokens[insert.table.0..insert.table.1].iter().map(|t| t.text).collect();
+    let cells = |n: usize| -> Vec<Cell> { insert.rows[n].items.iter().map(|&(a, b)| Cell::from_item(&tokens[a..b], dialect)).collect() };
+    let cells: Vec<Vec<Cell>> = rows.iter().map(|&n| cells(n)).collect();
+
+    // UNION ALL 的列类型取自第一个分支，其中的 NULL 按其他行的值转换类型
+    let mut untyped = Vec::new();
+    let first_values: Vec<Option<String>> = (0..cols.len())
+        .map(|p| {
+            if cells[0][p] != Cell::Null {
+                return None;
+            }
+            let sample = cells.iter().map(|row| &row[p]).find(|c| **c != Cell::Null)?;
+            let ty = null_type(sample);
+            if ty.is_none() {
+                untyped.push(cols[p].0.clone());
+            }
+            ty.map(|ty| format!("CAST(NULL AS {})", ty))
+        })
+        .collect();
+
+    let selects: Vec<String> = rows
+        .iter()
+        .enumerate()
+        .map(|(r, &n)| {
+            let values: Vec<String> = insert.rows[n]
+                .items
+                .iter()
+                .zip(&cols)
+                .enumerate()
+                .map(|(p, (&(a, b), (c, _)))| {
+                    let value = match &first_values[p] {
+                        Some(cast) if r == 0 => cast.clone(),
+                        _ => item_text(tokens, a, b),
+                    };
+                    format!("{} AS {}", value, c)
+                })
+                .collect();
+            format!("SELECT {} FROM DUAL", values.join(", "))
+        })
+        .collect();
+
+    let on: Vec<String> = cols.iter().filter(|(_, key)| *key).map(|(c, _)| format!("dst.{c} = src.{c}")).collect();
+    let updates: Vec<String> = cols.iter().filter(|(_, key)| !key).map(|(c, _)| format!("dst.{c} = src.{c}")).collect();
+    let names: Vec<&str> = cols.iter().map(|(c, _)| c.as_str()).collect();
+    let values: Vec<String> = names.iter().map(|c| format!("src.{c}")).collect();
+
+    let mut sql = format!(
+        "MERGE INTO {} dst\nUSING ({}) src\nON ({})\n",
+        table,
+        selects.join("\n  UNION ALL "),
+        on.join(" AND ")
+    );
+    if !updates.is_empty() {
+        sql.push_str(&format!("WHEN MATCHED THEN UPDATE SET {}\n", updates.join(", ")));
+    }
+    sql.push_str(&format!("WHEN NOT MATCHED THEN INSERT ({}) VALUES ({})", names.join(", "), values.join(", ")));
+    (sql, untyped)
+}
+
+/// 与值同类型的 Oracle 类型名，用于给 NULL 标注类型；无法判断时返回 None
+fn null_type(cell: &Cell) -> Option<&'static str> {
+    match cell {
+        Cell::Null => None,
+        Cell::Number(_) => Some("NUMBER"),
+        Cell::Text(_) => Some("VARCHAR2(4000)"),
+        Cell::Raw(expr) => {
+            let upper = expr.to_ascii_uppercase();
+            let starts = |prefixes: &[&str]| prefixes.iter().any(|p| upper.starts_with(p));
+            if starts(&["TO_TIMESTAMP", "TIMESTAMP '", "TIMESTAMP'", "SYSTIMESTAMP", "CURRENT_TIMESTAMP"]) {
+                Some("TIMESTAMP")
+            } else if starts(&["TO_DATE", "DATE '", "DATE'", "SYSDATE", "CURRENT_DATE"]) {
+                Some("DATE")
+            } else if starts(&["TO_NUMBER"]) {
+                Some("NUMBER")
+            } else if starts(&["TO_CHAR"]) {
+                Some("VARCHAR2(4000)")
+            } else {
+                None
+            }
+        }
+    }
+}
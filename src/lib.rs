@@ -6,7 +6,7 @@ pub mod utils;
 pub mod db;
 
 // 重新导出主要的公共 API
-pub use cli::{Cli, Commands, RepoCommands, WorkspaceCommands, DbCommands};
+pub use cli::{Cli, Commands, RepoCommands, WorkspaceCommands, WorkspaceArgs, DbCommands, OutputArgs};
 pub use version::replace;
 pub use utils::{command, parallel};
 pub use db::rmid;
\ No newline at end of file
@@ -1,20 +1,41 @@
 use clap::Parser;
+use regex::Regex;
 use std::env;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use synapse_cli::{
     Cli, Commands, RepoCommands, WorkspaceCommands, DbCommands,
     repo::{find_git_repos, get_repos_info_parallel, print_repos_table, 
           pull_all_repos_parallel, fetch_all_repos_parallel, 
           switch_all_repos_parallel, clean_all_repos_parallel,
-          gen_clone_commands, save_script},
-    workspace::{create_workspace, init_workspace, list_workspaces, switch_workspace},
-    version::version_replace,
-    db::rmid_file,
+          gen_clone_commands, save_script,
+          ChangelogOptions, parse_range, collect_commits_parallel, render_changelog, save_changelog},
+    workspace::{create_workspace, init_workspace, list_workspaces, switch_workspace, show_workspace, update_workspace, WorkspaceUpdate},
+    version::{version_replace, DepGraph, plan_bump, print_plan, apply_plan},
+    utils::{confirm, InputFile},
+    db::{rmid_file, dropcol_file, convert_file, ConvertOptions, upsert_file, export_file, import_file, parse_column_types, ImportOptions, rollback_file,
+        lint_file, report_json, LintConfig, ReportFormat, Severity, Checker, FailureKind,
+        parse_mapping, rename_file, Renames, dedup_file, KeepPolicy, mask_file, MaskRule,
+        diff_files, parse_schema_file, schema_diff, split_file, extract_file, ExtractOptions, merge_files,
+        parse_size, rebatch_file, RebatchOptions, recode_file, FileEncoding, check_file, fmt_file, FmtOptions, Output, infer_schema,
+        plan_remap, remap_file, ForeignKey, RemapOptions,
+        batch::{expand_files, run_batch, run_batch_as, run_batch_with, single_output, Outcome, OutputKind},
+        stream::write_generated,
+        encoding::{input_encoding, output_encoding, set_overrides, write_encoded}},
 };
 
 fn main() {
     let cli = Cli::parse();
+    if let Commands::Db { encoding, output_encoding, .. } = &cli.command {
+        let parse = |label: &Option<String>| label.as_deref().map(FileEncoding::parse).transpose();
+        match (parse(encoding), parse(output_encoding)) {
+            (Ok(input), Ok(output)) => set_overrides(input, output),
+            (Err(e), _) | (_, Err(e)) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
 
     match cli.command {
         Commands::Repo { command } => match command {
@@ -78,8 +99,41 @@ fn main() {
                     }
                 }
             }
-            RepoCommands::Updateversion { old_version, new_version } => {
+            RepoCommands::Updateversion { old_version, new_version, module, cascade, yes, dry_run } => {
                 let cwd = env::current_dir().expect("无法获取当前目录");
+                if let Some(module) = module {
+                    let repos = find_git_repos(&cwd);
+                    if repos.is_empty() {
+                        println!("当前目录未发现 Git 仓库");
+                        return;
+                    }
+
+                    let graph = DepGraph::build(&repos);
+                    let plan = graph
+                        .find(&module)
+                        .and_then(|root| plan_bump(&graph, root, &old_version, &new_version, cascade));
+                    let plan = match plan {
+                        Ok(plan) => plan,
+                        Err(e) => {
+                            eprintln!("生成升级计划失败: {}", e);
+                            std::process::exit(1);
+                        }
+                    };
+
+                    print_plan(&graph, &plan);
+                    if dry_run || (!yes && !confirm("是否执行以上升级计划？")) {
+                        return;
+                    }
+                    match apply_plan(&graph, &plan) {
+                        Ok(count) => println!("版本升级完成，共修改 {} 个清单文件", count),
+                        Err(e) => {
+                            eprintln!("版本升级失败: {}", e);
+                            std::process::exit(1);
+                        }
+                    }
+                    return;
+                }
+
                 println!("正在替换版本号: {} -> {}", old_version, new_version);
                 
                 match version_replace(&old_version, &new_version, &cwd) {
@@ -91,16 +145,62 @@ fn main() {
                     }
                 }
             }
+            RepoCommands::Changelog { range, group_pattern, issue_pattern, issue_url, output } => {
+                let cwd = env::current_dir().expect("无法获取当前目录");
+                let repos = find_git_repos(&cwd);
+                if repos.is_empty() {
+                    println!("当前目录未发现 Git 仓库");
+                    return;
+                }
+
+                let (from, to) = match parse_range(&range) {
+                    Ok(r) => r,
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        std::process::exit(1);
+                    }
+                };
+                let compile = |pattern: &str| {
+                    Regex::new(pattern).unwrap_or_else(|e| {
+                        eprintln!("无效的正则 '{}': {}", pattern, e);
+                        std::process::exit(1);
+                    })
+                };
+                let options = ChangelogOptions {
+                    group_pattern: group_pattern.as_deref().map(compile),
+                    issue_pattern: compile(&issue_pattern),
+                    issue_url,
+                };
+
+                let results = collect_commits_parallel(repos, &from, &to);
+                for repo in &results {
+                    if let Err(e) = &repo.commits {
+                        eprintln!("[WARN] {} 跳过: {}", repo.name, e);
+                    }
+                }
+                let markdown = render_changelog(&format!("{}..{}", from, to), &results, &options);
+
+                match output {
+                    Some(path) => match save_changelog(Path::new(&path), &markdown) {
+                        Ok(()) => println!("变更日志已保存到: {}", path),
+                        Err(e) => {
+                            eprintln!("保存变更日志失败: {}", e);
+                            std::process::exit(1);
+                        }
+                    },
+                    None => print!("{}", markdown),
+                }
+            }
         },
         Commands::Workspace { command } => match command {
-            WorkspaceCommands::New => {
-                if let Err(e) = create_workspace() {
+            WorkspaceCommands::New { info } => {
+                if let Err(e) = create_workspace(info.name, info.description, info.branch) {
                     eprintln!("错误: {}", e);
                     std::process::exit(1);
                 }
             }
-            WorkspaceCommands::Init => {
-                if let Err(e) = init_workspace() {
+            WorkspaceCommands::Init { info } => {
+                if let Err(e) = init_workspace(info.name, info.description, info.branch) {
                     eprintln!("错误: {}", e);
                     std::process::exit(1);
                 }
@@ -117,25 +217,620 @@ fn main() {
                     std::process::exit(1);
                 }
             }
+            WorkspaceCommands::Show { workspace_name } => {
+                if let Err(e) = show_workspace(&workspace_name) {
+                    eprintln!("错误: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            WorkspaceCommands::Set { workspace_name, path, description, branch, groups, remotes, env } => {
+                let update = WorkspaceUpdate {
+                    path: path.map(PathBuf::from),
+                    description,
+                    default_branch: branch,
+                    groups,
+                    remotes,
+                    env,
+                };
+                if let Err(e) = update_workspace(&workspace_name, &update) {
+                    eprintln!("错误: {}", e);
+                    std::process::exit(1);
+                }
+            }
         },
-        Commands::Db { command } => match command {
-            DbCommands::Rmid { sql_file_path } => {
-                let path = Path::new(&sql_file_path);
-                if !path.exists() {
-                    eprintln!("文件不存在: {}", path.display());
+        Commands::Db { command, .. } => match command {
+            DbCommands::Rmid { paths, output, dialect } => {
+                or_exit(run_batch(&paths, &output, move |path, output| {
+                    let report = rmid_file(path, output, dialect)?;
+                    let lines = report.warnings.iter().map(|(line, message)| format!("[WARN] 第 {} 行: {}", line, message)).collect();
+                    Ok(Outcome::done(lines, format!("共修改 {} 处", report.changed_count())))
+                }));
+            }
+            DbCommands::Dropcol { paths, output, column, dialect } => {
+                or_exit(run_batch(&paths, &output, move |path, output| {
+                    let report = dropcol_file(path, output, &column, dialect)?;
+                    let mut lines = Vec::new();
+                    if report.tables.is_empty() {
+                        lines.push(format!("未找到包含列 {} 的 INSERT 语句", column.join(", ")));
+                    } else {
+                        let table_width = report.tables.iter().map(|t| t.table.len()).max().unwrap_or(5).max(5);
+                        lines.push(format!("{:<table_width$} {:>10} {:>10}", "TABLE", "STATEMENTS", "ROWS", table_width = table_width));
+                        lines.push("-".repeat(table_width + 22));
+                        for stat in &report.tables {
+                            lines.push(format!("{:<table_width$} {:>10} {:>10}", stat.table, stat.statements, stat.rows, table_width = table_width));
+                        }
+                    }
+                    lines.extend(report.warnings.iter().map(|(line, message)| format!("[WARN] 第 {} 行: {}", line, message)));
+                    Ok(Outcome::done(lines, format!("共修改 {} 处", report.changed_count())))
+                }));
+            }
+            DbCommands::Convert { paths, output, from, to, multi_row, keep_date_strings } => {
+                let options = ConvertOptions { from, to, multi_row, date_literals: !keep_date_strings };
+                or_exit(run_batch(&paths, &output, move |path, output| {
+                    let report = convert_file(path, output, &options)?;
+                    let lines = report.warnings.iter().map(|(line, message)| format!("[WARN] 第 {} 行: {}", line, message)).collect();
+                    Ok(Outcome::done(lines, format!("共改写 {} 条语句，{} 处需人工检查", report.statements, report.warnings.len())))
+                }));
+            }
+            DbCommands::Upsert { paths, output, key, dialect } => {
+                or_exit(run_batch(&paths, &output, move |path, output| {
+                    let report = upsert_file(path, output, &key, dialect)?;
+                    let lines = report.warnings.iter().map(|(line, message)| format!("[WARN] 第 {} 行: {}", line, message)).collect();
+                    Ok(Outcome::done(
+                        lines,
+                        format!("共改写 {} 条语句（{} 行数据），跳过 {} 条", report.statements, report.rows, report.warnings.len()),
+                    ))
+                }));
+            }
+            DbCommands::Export { paths, output, format, dialect } => {
+                let kind = OutputKind::Directory(|path: &Path| path.with_extension(""));
+                or_exit(run_batch_as(&paths, &["sql".to_string()], &output, kind, move |path, output| {
+                    let Output::File(out_dir) = output else { return Err("导出结果只能写入目录".to_string()) };
+                    let report = export_file(path, out_dir, format, dialect)?;
+                    let mut lines: Vec<String> = report.warnings.iter().map(|(line, message)| format!("[WARN] 第 {} 行: {}", line, message)).collect();
+                    for table in &report.tables {
+                        lines.push(format!("[OK] {} ({} 行) -> {}", table.table, table.rows, table.path.display()));
+                    }
+                    Ok(Outcome::done(lines, format!("共导出 {} 张表", report.tables.len())))
+                }));
+            }
+            DbCommands::Import { paths, output, table, types, dialect } => {
+                let types = or_exit(parse_column_types(&types));
+                let extensions = ["csv".to_string(), "json".to_string()];
+                let kind = OutputKind::File(Some(|path: &Path| path.with_extension("sql")));
+                or_exit(run_batch_as(&paths, &extensions, &output, kind, move |path, output| {
+                    let table = table.clone().unwrap_or_else(|| path.file_stem().unwrap_or_default().to_string_lossy().to_string());
+                    let rows = import_file(path, output, &ImportOptions { table, types: types.clone(), dialect })?;
+                    Ok(Outcome::done(Vec::new(), format!("共生成 {} 条 INSERT", rows)))
+                }));
+            }
+            DbCommands::Rollback { paths, output, key, dialect } => {
+                let kind = OutputKind::File(Some(|path: &Path| {
+                    path.with_file_name(format!("{}.rollback.sql", path.file_stem().unwrap_or_default().to_string_lossy()))
+                }));
+                or_exit(run_batch_as(&paths, &["sql".to_string()], &output, kind, move |path, output| {
+                    let report = rollback_file(path, output, &key, dialect)?;
+                    let lines = report.warnings.iter().map(|(line, message)| format!("[WARN] 第 {} 行: {}", line, message)).collect();
+                    Ok(Outcome::done(lines, format!("已生成 {} 条 DELETE", report.deletes)))
+                }));
+            }
+            DbCommands::Lint { files, disable, severity, config, key, format, dialect } => {
+                let mut lint_config = LintConfig::default();
+                let configured = config
+                    .map(|c| lint_config.load(Path::new(&c)))
+                    .unwrap_or(Ok(()))
+                    .and_then(|_| severity.iter().try_for_each(|s| lint_config.apply(s)))
+                    .and_then(|_| disable.iter().try_for_each(|rule| lint_config.set(rule, Severity::Off)));
+                if let Err(e) = configured {
+                    eprintln!("{}", e);
                     std::process::exit(1);
                 }
-                match rmid_file(path) {
-                    Ok(n) => {
-                        println!("✅ 处理完成，共修改 {} 处（已生成备份：{}.bak）", n, path.file_name().unwrap().to_string_lossy());
+                if !key.is_empty() {
+                    lint_config.keys = key;
+                }
+
+                let mut reports = Vec::new();
+                for file in expand_or_exit(&files) {
+                    match lint_file(&file.path, &lint_config, dialect) {
+                        Ok(report) => reports.push(report),
+                        Err(e) => {
+                            eprintln!("检查失败 {}: {}", file.path.display(), e);
+                            std::process::exit(1);
+                        }
+                    }
+                }
+
+                let errors: usize = reports.iter().map(|r| r.count(Severity::Error)).sum();
+                let warnings: usize = reports.iter().map(|r| r.count(Severity::Warning)).sum();
+                match format {
+                    ReportFormat::Json => println!("{}", report_json(&reports)),
+                    ReportFormat::Text => {
+                        for report in &reports {
+                            for issue in &report.issues {
+                                println!(
+                                    "{}:{}: [{}] {}: {}",
+                                    report.path.display(),
+                                    issue.line,
+                                    issue.severity.as_str(),
+                                    issue.rule,
+                                    issue.message
+                                );
+                            }
+                        }
+                        println!("检查了 {} 个文件：{} 个错误，{} 个警告", reports.len(), errors, warnings);
                     }
+                }
+                if errors > 0 {
+                    std::process::exit(1);
+                }
+            }
+            DbCommands::Check { files, schema, dialect } => {
+                let mut checker = match Checker::new(dialect) {
+                    Ok(checker) => checker,
                     Err(e) => {
-                        eprintln!("处理失败: {}", e);
+                        eprintln!("{}", e);
                         std::process::exit(1);
                     }
+                };
+                for file in schema.iter().map(PathBuf::from).chain(expand_or_exit(&files).into_iter().map(|f| f.path)) {
+                    if let Err(e) = checker.run_file(&file) {
+                        eprintln!("执行失败 {}: {}", file.display(), e);
+                        std::process::exit(1);
+                    }
+                }
+
+                let report = checker.finish();
+                for failure in &report.failures {
+                    let kind = match failure.kind {
+                        FailureKind::Constraint => "约束冲突",
+                        FailureKind::Error => "执行失败",
+                    };
+                    println!("{}:{}: [{}] {}", failure.file, failure.line, kind, failure.message);
+                    println!("    {}", failure.snippet);
+                }
+                println!(
+                    "执行 {} 条语句，跳过 {} 条；{} 条执行失败，{} 条约束冲突",
+                    report.executed,
+                    report.skipped,
+                    report.count(FailureKind::Error),
+                    report.count(FailureKind::Constraint)
+                );
+                if !report.failures.is_empty() {
+                    std::process::exit(1);
                 }
             }
+            DbCommands::Dedup { paths, output, key, keep, sort, dialect } => {
+                or_exit(run_batch(&paths, &output, move |path, output| {
+                    let report = dedup_file(path, output, &key, keep, sort, dialect)?;
+                    let mut lines: Vec<String> = report.warnings.iter().map(|(line, message)| format!("[WARN] 第 {} 行: {}", line, message)).collect();
+                    for dup in &report.duplicates {
+                        let rows: Vec<String> = dup.lines.iter().map(|l| l.to_string()).collect();
+                        let (level, note) = match (dup.conflict, keep) {
+                            (true, KeepPolicy::Fail) => ("ERROR", "值不一致".to_string()),
+                            (true, _) => ("WARN", format!("值不一致，保留第 {} 行", dup.kept)),
+                            (false, _) => ("INFO", format!("值相同，保留第 {} 行", dup.kept)),
+                        };
+                        lines.push(format!("[{}] {}: 主键 ({}) 在第 {} 行重复，{}", level, dup.table, dup.key.join(", "), rows.join(", "), note));
+                    }
+                    if !report.applied {
+                        if report.conflicts() > 0 && keep == KeepPolicy::Fail {
+                            return Ok(Outcome::failed(lines, format!("存在 {} 个值不一致的重复主键，未修改文件", report.conflicts())));
+                        }
+                        return Ok(Outcome::unchanged(lines, format!("共 {} 行，未发现重复主键", report.rows)));
+                    }
+                    Ok(Outcome::done(
+                        lines,
+                        format!("共 {} 行，删除重复行 {} 行{}", report.rows, report.removed, if sort { "，已按主键排序" } else { "" }),
+                    ))
+                }));
+            }
+            DbCommands::Mask { paths, output, rule, seed, dialect } => {
+                let rules = match rule.iter().map(|r| MaskRule::parse(r)).collect::<Result<Vec<_>, _>>() {
+                    Ok(rules) => rules,
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        std::process::exit(1);
+                    }
+                };
+                or_exit(run_batch(&paths, &output, move |path, output| {
+                    let report = mask_file(path, output, &rules, &seed, dialect)?;
+                    let mut lines: Vec<String> = report.warnings.iter().map(|(line, message)| format!("[WARN] 第 {} 行: {}", line, message)).collect();
+                    for (spec, count) in rule.iter().zip(&report.masked) {
+                        lines.push(format!("[OK] {}: {} 个值", spec, count));
+                    }
+                    if let Some(backup) = output.backup_path(path) {
+                        lines.push(format!("[WARN] 备份文件 {} 中仍是原始数据", backup.display()));
+                    }
+                    Ok(Outcome::done(lines, format!("共脱敏 {} 个值", report.masked.iter().sum::<usize>())))
+                }));
+            }
+            DbCommands::Diff { old_file, new_file, key, format, script, dialect } => {
+                let (old_path, new_path) = (Path::new(&old_file), Path::new(&new_file));
+                for path in [old_path, new_path] {
+                    if !path.exists() {
+                        eprintln!("文件不存在: {}", path.display());
+                        std::process::exit(1);
+                    }
+                }
+                let diff = match diff_files(old_path, new_path, &key, dialect) {
+                    Ok(diff) => diff,
+                    Err(e) => {
+                        eprintln!("比较失败: {}", e);
+                        std::process::exit(1);
+                    }
+                };
+
+                for (file, line, message) in &diff.warnings {
+                    eprintln!("[WARN] {} 第 {} 行: {}", file, line, message);
+                }
+                let (added, removed, modified) = diff.counts();
+                match format {
+                    ReportFormat::Json => println!("{}", diff.to_json()),
+                    ReportFormat::Text => {
+                        for table in &diff.tables {
+                            println!(
+                                "{}: 新增 {} 行，删除 {} 行，修改 {} 行",
+                                table.table,
+                                table.added.len(),
+                                table.removed.len(),
+                                table.modified.len()
+                            );
+                            for row in &table.added {
+                                println!("  + {}（新文件第 {} 行）", diff.key_label(row), row.line);
+                            }
+                            for row in &table.removed {
+                                println!("  - {}（旧文件第 {} 行）", diff.key_label(row), row.line);
+                            }
+                            for row in &table.modified {
+                                println!("  ~ {}（第 {} 行 -> 第 {} 行）", diff.key_label(&row.new), row.old.line, row.new.line);
+                                for change in &row.changes {
+                                    let show = |v: &Option<String>| v.clone().unwrap_or_else(|| "(无此列)".to_string());
+                                    println!("      {}: {} -> {}", change.column, show(&change.old), show(&change.new));
+                                }
+                            }
+                        }
+                        if diff.is_empty() {
+                            println!("✅ 两份数据一致");
+                        } else {
+                            println!("共新增 {} 行，删除 {} 行，修改 {} 行", added, removed, modified);
+                        }
+                    }
+                }
+
+                if let Some(script) = script {
+                    let header = format!("由 {} 变更为 {} 的数据脚本", old_file, new_file);
+                    let encoding = input_encoding(new_path).map(output_encoding);
+                    if let Err(e) = encoding.and_then(|encoding| write_encoded(Path::new(&script), &diff.script(&header), encoding)) {
+                        eprintln!("写入脚本失败: {}", e);
+                        std::process::exit(1);
+                    }
+                    eprintln!("✅ 已生成变更脚本 -> {}", script);
+                }
+            }
+            DbCommands::Schemadiff { old_file, new_file, output, allow_destructive, dialect } => {
+                let output = or_exit(single_output(&output));
+                let (old_path, new_path) = (Path::new(&old_file), Path::new(&new_file));
+                for path in [old_path, new_path] {
+                    if !path.exists() {
+                        eprintln!("文件不存在: {}", path.display());
+                        std::process::exit(1);
+                    }
+                }
+                let (old, new) = match (parse_schema_file(old_path, dialect), parse_schema_file(new_path, dialect)) {
+                    (Ok(old), Ok(new)) => (old, new),
+                    (Err(e), _) | (_, Err(e)) => {
+                        eprintln!("解析失败: {}", e);
+                        std::process::exit(1);
+                    }
+                };
+                for (file, schema) in [(&old_file, &old), (&new_file, &new)] {
+                    for (line, message) in &schema.warnings {
+                        eprintln!("[WARN] {} 第 {} 行: {}", file, line, message);
+                    }
+                }
+
+                let migration = schema_diff(&old, &new, dialect);
+                if migration.steps.is_empty() {
+                    eprintln!("✅ 表结构一致，无需迁移");
+                    return;
+                }
+                let header = format!("由 {} 变更为 {} 的表结构迁移脚本", old_file, new_file);
+                let script = migration.render(&header, allow_destructive);
+                let written = input_encoding(new_path).and_then(|encoding| {
+                    write_generated(&[old_path, new_path], &output, output_encoding(encoding), |out| {
+                        out.write_all(script.as_bytes()).map_err(|e| e.to_string())
+                    })
+                });
+                if let Err(e) = written {
+                    eprintln!("写入脚本失败: {}", e);
+                    std::process::exit(1);
+                }
+
+                let destructive = migration.destructive().count();
+                eprintln!("[OK] 共 {} 条迁移语句{}", migration.steps.len(), target_note(&output));
+                if destructive > 0 {
+                    if allow_destructive {
+                        eprintln!("[WARN] 其中 {} 条会丢失数据，请确认后执行", destructive);
+                    } else {
+                        eprintln!("[WARN] 其中 {} 条会丢失数据，已注释；确认后使用 --allow-destructive 输出", destructive);
+                    }
+                }
+            }
+            DbCommands::Split { paths, output, dialect } => {
+                let kind = OutputKind::Directory(|path: &Path| path.with_extension(""));
+                or_exit(run_batch_as(&paths, &["sql".to_string()], &output, kind, move |path, output| {
+                    let Output::File(out_dir) = output else { return Err("拆分结果只能写入目录".to_string()) };
+                    let report = split_file(path, out_dir, dialect)?;
+                    let mut lines = Vec::new();
+                    for (table, file, statements) in &report.files {
+                        lines.push(format!("{} -> {}（{} 条语句）", table, file.display(), statements));
+                    }
+                    if report.others > 0 {
+                        lines.push(format!("其他语句 {} 条 -> {}", report.others, out_dir.join("_others.sql").display()));
+                    }
+                    Ok(Outcome::done(
+                        lines,
+                        format!("已拆分为 {} 个表文件，每个文件包含 {} 条公共会话设置语句", report.files.len(), report.shared),
+                    ))
+                }));
+            }
+            DbCommands::Extract { paths, output, table, filter, dialect } => {
+                if table.is_empty() && filter.is_none() {
+                    eprintln!("至少需要指定 --table 或 --where");
+                    std::process::exit(1);
+                }
+                let options = ExtractOptions { tables: table, filter };
+                or_exit(run_batch_as(&paths, &["sql".to_string()], &output, OutputKind::File(None), move |path, output| {
+                    let mut report = None;
+                    write_generated(&[path], output, output_encoding(input_encoding(path)?), |out| {
+                        report = Some(extract_file(path, &options, dialect, out)?);
+                        Ok(())
+                    })?;
+                    let report = report.unwrap_or_default();
+                    let lines = report.warnings.iter().map(|(line, message)| format!("[WARN] 第 {} 行: {}", line, message)).collect();
+                    let rows = if options.filter.is_some() {
+                        format!("，保留 {} 行，按条件删除 {} 行", report.rows, report.removed)
+                    } else {
+                        String::new()
+                    };
+                    Ok(Outcome::done(lines, format!("抽取 {} 条语句{}", report.statements, rows)))
+                }));
+            }
+            DbCommands::Merge { paths, output, dialect } => {
+                let output = or_exit(single_output(&output));
+                let paths: Vec<PathBuf> = expand_or_exit(&paths).into_iter().map(|f| f.path).collect();
+                let inputs: Vec<&Path> = paths.iter().map(|p| p.as_path()).collect();
+                let mut report = None;
+                let result = input_encoding(&paths[0]).and_then(|encoding| {
+                    write_generated(&inputs, &output, output_encoding(encoding), |out| {
+                        report = Some(merge_files(&paths, dialect, out)?);
+                        Ok(())
+                    })
+                });
+                match result.map(|_| report.unwrap_or_default()) {
+                    Ok(report) => {
+                        for warning in &report.warnings {
+                            eprintln!("[WARN] {}", warning);
+                        }
+                        let order: Vec<String> = report.order.iter().map(|p| p.display().to_string()).collect();
+                        eprintln!("合并顺序: {}", order.join(" -> "));
+                        eprintln!(
+                            "[OK] 合并 {} 个文件，共 {} 条语句，去掉 {} 条重复的会话设置语句{}",
+                            report.order.len(),
+                            report.statements,
+                            report.duplicates,
+                            target_note(&output)
+                        );
+                    }
+                    Err(e) => {
+                        eprintln!("合并失败: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            DbCommands::Rebatch { paths, output, rows, max_bytes, commit_every, dialect } => {
+                if rows.is_none() && max_bytes.is_none() {
+                    eprintln!("至少需要指定 --rows 或 --max-bytes");
+                    std::process::exit(1);
+                }
+                if rows == Some(0) || commit_every == Some(0) {
+                    eprintln!("--rows 与 --commit-every 必须大于 0");
+                    std::process::exit(1);
+                }
+                let max_bytes = match max_bytes.as_deref().map(parse_size).transpose() {
+                    Ok(max_bytes) => max_bytes,
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        std::process::exit(1);
+                    }
+                };
+                let options = RebatchOptions { rows, max_bytes, commit_every };
+                or_exit(run_batch(&paths, &output, move |path, output| {
+                    let report = rebatch_file(path, output, &options, dialect)?;
+                    let mut lines = Vec::new();
+                    if report.oversized > 0 {
+                        lines.push(format!("[WARN] {} 行单独超过字节限制，已各自单独成为一条语句", report.oversized));
+                    }
+                    if report.skipped > 0 {
+                        lines.push(format!("[WARN] {} 条 INSERT 带有 VALUES 之后的子句，保持原样", report.skipped));
+                    }
+                    if report.commits > 0 {
+                        lines.push(format!("[OK] 插入 {} 个 COMMIT", report.commits));
+                    }
+                    Ok(Outcome::done(lines, format!("{} 行数据由 {} 条 INSERT 重新分为 {} 条", report.rows, report.read, report.written)))
+                }));
+            }
+            DbCommands::Recode { paths, output, to, ext } => {
+                let to = match FileEncoding::parse(&to) {
+                    Ok(to) => to,
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        std::process::exit(1);
+                    }
+                };
+                or_exit(run_batch_with(&paths, &ext, &output, move |path, output| match recode_file(path, output, to)? {
+                    Some(from) => Ok(Outcome::done(Vec::new(), format!("{} -> {}", from.name(), to.name()))),
+                    None if output.in_place() => Ok(Outcome::unchanged(Vec::new(), format!("已是 {}", to.name()))),
+                    None => Ok(Outcome::done(Vec::new(), format!("已是 {}，原样输出", to.name()))),
+                }));
+            }
+            DbCommands::Fmt { paths, output, check, keyword_case, indent, dialect } => {
+                let options = FmtOptions { keyword_case, indent };
+                if !check {
+                    or_exit(run_batch(&paths, &output, move |path, output| {
+                        if fmt_file(path, output, &options, dialect)? {
+                            Ok(Outcome::done(Vec::new(), "已格式化".to_string()))
+                        } else {
+                            Ok(Outcome::unchanged(Vec::new(), "已是格式化结果，无需改动".to_string()))
+                        }
+                    }));
+                    return;
+                }
+
+                let files = expand_or_exit(&paths);
+                let mut changed = 0;
+                for file in &files {
+                    match check_file(&file.path, &options, dialect) {
+                        Ok(Some(line)) => {
+                            println!("{}:{}: 格式与 db fmt 的结果不一致", file.path.display(), line);
+                            changed += 1;
+                        }
+                        Ok(None) => {}
+                        Err(e) => {
+                            eprintln!("格式检查失败 {}: {}", file.path.display(), e);
+                            std::process::exit(1);
+                        }
+                    }
+                }
+                if changed > 0 {
+                    println!("{} 个文件未格式化，可运行 synapse db fmt 修正", changed);
+                    std::process::exit(1);
+                }
+                println!("✅ {} 个文件均已格式化", files.len());
+            }
+            DbCommands::InferSchema { paths, output, dialect } => {
+                let output = or_exit(single_output(&output));
+                let files: Vec<PathBuf> = expand_or_exit(&paths).into_iter().map(|f| f.path).collect();
+                let schema = match infer_schema(&files, dialect) {
+                    Ok(schema) => schema,
+                    Err(e) => {
+                        eprintln!("推断失败: {}", e);
+                        std::process::exit(1);
+                    }
+                };
+                for (file, line, message) in &schema.warnings {
+                    eprintln!("[WARN] {} 第 {} 行: {}", file, line, message);
+                }
+                if schema.tables.is_empty() {
+                    eprintln!("未找到带列列表的 INSERT 语句");
+                    std::process::exit(1);
+                }
+
+                let header = format!("由 {} 中的数据推断的表结构，请核对类型、长度与主键后使用", paths.join(" "));
+                let script = schema.render(&header, dialect);
+                let inputs: Vec<&Path> = files.iter().map(|p| p.as_path()).collect();
+                let written = input_encoding(&files[0]).and_then(|encoding| {
+                    write_generated(&inputs, &output, output_encoding(encoding), |out| {
+                        out.write_all(script.as_bytes()).map_err(|e| e.to_string())
+                    })
+                });
+                if let Err(e) = written {
+                    eprintln!("写入脚本失败: {}", e);
+                    std::process::exit(1);
+                }
+                let rows: usize = schema.tables.iter().map(|t| t.rows).sum();
+                eprintln!(
+                    "[OK] 由 {} 行数据推断出 {} 张表{}",
+                    rows,
+                    schema.tables.len(),
+                    target_note(&output)
+                );
+            }
+            DbCommands::RemapId { paths, output, table, key, start, fk, dialect } => {
+                let foreign_keys = match fk.iter().map(|f| ForeignKey::parse(f)).collect::<Result<Vec<_>, _>>() {
+                    Ok(foreign_keys) => foreign_keys,
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        std::process::exit(1);
+                    }
+                };
+                let options = RemapOptions { tables: table, key, start, foreign_keys };
+                let files: Vec<PathBuf> = expand_or_exit(&paths).into_iter().map(|f| f.path).collect();
+                let map = match plan_remap(&files, &options, dialect) {
+                    Ok(map) => map,
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        std::process::exit(1);
+                    }
+                };
+                let say = |line: String| if output.stdout { eprintln!("{}", line) } else { println!("{}", line) };
+                for (file, line, message) in &map.warnings {
+                    say(format!("[WARN] {} 第 {} 行: {}", file, line, message));
+                }
+                for table in &map.tables {
+                    match table.range() {
+                        Some((first, last)) => say(format!("[OK] {}: {} 个主键 -> {}..{}", table.table, table.ids.len(), first, last)),
+                        None => say(format!("[WARN] {}: 未找到主键值", table.table)),
+                    }
+                }
+
+                or_exit(run_batch(&paths, &output, move |path, output| {
+                    let report = remap_file(path, output, &map, dialect)?;
+                    let lines = report.warnings.iter().map(|(line, message)| format!("[WARN] 第 {} 行: {}", line, message)).collect();
+                    Ok(Outcome::done(lines, format!("改写主键 {} 个，外键 {} 个", report.keys, report.references)))
+                }));
+            }
+            DbCommands::Rename { paths, output, table, schema, dialect } => {
+                if table.is_empty() && schema.is_empty() {
+                    eprintln!("请通过 --table 或 --schema 指定替换规则");
+                    std::process::exit(1);
+                }
+                let tables: Result<Vec<_>, _> = table.iter().map(|t| parse_mapping(t)).collect();
+                let schemas: Result<Vec<_>, _> = schema.iter().map(|s| parse_mapping(s)).collect();
+                let renames = match tables.and_then(|tables| Ok(Renames { tables, schemas: schemas? })) {
+                    Ok(renames) => renames,
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        std::process::exit(1);
+                    }
+                };
+                or_exit(run_batch(&paths, &output, move |path, output| {
+                    let report = rename_file(path, output, &renames, dialect)?;
+                    let mut lines = Vec::new();
+                    for ((old, new), count) in renames.tables.iter().zip(&report.tables) {
+                        lines.push(format!("[OK] 表 {} -> {}: {} 处", old, new, count));
+                    }
+                    for ((old, new), count) in renames.schemas.iter().zip(&report.schemas) {
+                        lines.push(format!("[OK] schema {} -> {}: {} 处", old, new, count));
+                    }
+                    Ok(Outcome::done(lines, format!("共替换 {} 处", report.total())))
+                }));
+            }
         },
     }
 }
 
+
+/// 展开输入的文件、目录与通配符，失败或没有文件时退出
+fn expand_or_exit(patterns: &[String]) -> Vec<InputFile> {
+    or_exit(expand_files(patterns, &["sql".to_string()]))
+}
+
+/// 输出到文件时的 " -> 文件" 说明
+fn target_note(output: &Output) -> String {
+    match output {
+        Output::File(path) => format!(" -> {}", path.display()),
+        _ => String::new(),
+    }
+}
+
+/// 出错时输出错误信息并以状态码 1 退出
+fn or_exit<T>(result: Result<T, String>) -> T {
+    match result {
+        Ok(value) => value,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::utils::parallel::ParallelExecutor;
+
+/// Conventional Commit 类型及其在变更日志中的标题，按输出顺序排列
+const COMMIT_TYPES: &[(&str, &str)] = &[
+    ("feat", "新功能"),
+    ("fix", "问题修复"),
+    ("perf", "性能优化"),
+    ("refactor", "代码重构"),
+    ("docs", "文档"),
+    ("test", "测试"),
+    ("build", "构建"),
+    ("ci", "持续集成"),
+    ("style", "代码风格"),
+    ("revert", "回滚"),
+    ("chore", "其他"),
+];
+
+/// 无法归类的提交所在分组
+const OTHER_GROUP: &str = "其他";
+
+/// Conventional Commit 标题：type(scope)!: description
+static CONVENTIONAL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(\w+)(?:\(([^)]*)\))?(!)?:\s*(.+)$").unwrap());
+
+/// 单条提交记录
+#[derive(Debug, Clone)]
+pub struct CommitEntry {
+    pub hash: String,
+    pub short_hash: String,
+    pub subject: String,
+    pub author: String,
+}
+
+/// 单个仓库在指定范围内的提交
+#[derive(Debug)]
+pub struct RepoCommits {
+    pub name: String,
+    pub commits: Result<Vec<CommitEntry>, String>,
+}
+
+/// 变更日志的分组与链接配置
+pub struct ChangelogOptions {
+    /// 自定义分组正则，取命名分组 `group` 或第一个捕获组作为分组名；为空时按 Conventional Commit 类型分组
+    pub group_pattern: Option<Regex>,
+    /// 问题单号的匹配正则
+    pub issue_pattern: Regex,
+    /// 问题单链接模板，`{id}` 会被替换为单号
+    pub issue_url: Option<String>,
+}
+
+/// 解析 `<from>..<to>` 形式的范围，省略 to 时默认为 HEAD
+pub fn parse_range(spec: &str) -> Result<(String, String), String> {
+    let (from, to) = spec
+        .split_once("..")
+        .ok_or(format!("无效的范围 '{}'，格式应为 <from-tag>..<to-ref>", spec))?;
+    if from.is_empty() {
+        return Err(format!("无效的范围 '{}'，缺少起始标签", spec));
+    }
+    let to = if to.is_empty() { "HEAD" } else { to };
+    Ok((from.to_string(), to.to_string()))
+}
+
+/// 读取单个仓库在范围内的提交（不含合并提交）
+fn git_log_range(dir: &Path, from: &str, to: &str) -> Result<Vec<CommitEntry>, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .arg("log")
+        .arg("--no-merges")
+        .arg("--format=%H%x1f%h%x1f%s%x1f%an%x1e")
+        .arg(format!("{}..{}", from, to))
+        .output()
+        .map_err(|e| format!("执行 git log 失败: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(stderr.lines().next().unwrap_or_default().trim().to_string());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let commits = stdout
+        .split('\x1e')
+        .filter_map(|record| {
+            let mut fields = record.trim_start_matches('\n').split('\x1f');
+            Some(CommitEntry {
+                hash: fields.next().filter(|h| !h.is_empty())?.to_string(),
+                short_hash: fields.next()?.to_string(),
+                subject: fields.next()?.to_string(),
+                author: fields.next()?.trim().to_string(),
+            })
+        })
+        .collect();
+    Ok(commits)
+}
+
+/// 并发收集所有仓库在范围内的提交，按仓库名排序
+pub fn collect_commits_parallel(repos: Vec<PathBuf>, from: &str, to: &str) -> Vec<RepoCommits> {
+    let from = from.to_string();
+    let to = to.to_string();
+    let mut results: Vec<RepoCommits> = ParallelExecutor::map(repos, move |repo| git_log_range(repo, &from, &to))
+        .into_iter()
+        .map(|(repo, commits)| RepoCommits {
+            name: repo
+                .file_name()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_else(|| repo.to_string_lossy().to_string()),
+            commits,
+        })
+        .collect();
+    results.sort_by_key(|r| r.name.to_lowercase());
+    results
+}
+
+/// 计算提交的分组名及去掉前缀后的描述
+pub fn classify(subject: &str, options: &ChangelogOptions) -> (String, String) {
+    if let Some(pattern) = &options.group_pattern {
+        let group = pattern.captures(subject).and_then(|caps| caps.name("group").or_else(|| caps.get(1)));
+        return match group {
+            Some(g) => (g.as_str().to_string(), subject.to_string()),
+            None => (OTHER_GROUP.to_string(), subject.to_string()),
+        };
+    }
+
+    let Some(caps) = CONVENTIONAL.captures(subject) else {
+        return (OTHER_GROUP.to_string(), subject.to_string());
+    };
+    let kind = caps[1].to_lowercase();
+    let title = COMMIT_TYPES
+        .iter()
+        .find(|(t, _)| *t == kind)
+        .map(|(_, title)| title.to_string())
+        .unwrap_or_else(|| OTHER_GROUP.to_string());
+
+    let mut description = String::new();
+    if caps.get(3).is_some() {
+        description.push_str("⚠️ **BREAKING** ");
+    }
+    if let Some(scope) = caps.get(2).filter(|s| !s.as_str().is_empty()) {
+        description.push_str(&format!("**{}**: ", scope.as_str()));
+    }
+    description.push_str(&caps[4]);
+    (title, description)
+}
+
+/// 把描述中的问题单号替换为 Markdown 链接
+fn link_issues(text: &str, options: &ChangelogOptions) -> String {
+    match &options.issue_url {
+        Some(template) => options
+            .issue_pattern
+            .replace_all(text, |caps: &regex::Captures| {
+                let id = &caps[0];
+                format!("[{}]({})", id, template.replace("{id}", id))
+            })
+            .to_string(),
+        None => text.to_string(),
+    }
+}
+
+/// 分组顺序：Conventional Commit 类型按预设顺序，自定义分组按首次出现顺序，“其他”放在最后
+fn group_rank(group: &str, options: &ChangelogOptions) -> usize {
+    if group == OTHER_GROUP {
+        return usize::MAX;
+    }
+    if options.group_pattern.is_none() {
+        return COMMIT_TYPES.iter().position(|(_, title)| *title == group).unwrap_or(usize::MAX - 1);
+    }
+    0
+}
+
+/// 生成 Markdown 格式的变更日志
+pub fn render_changelog(range: &str, repos: &[RepoCommits], options: &ChangelogOptions) -> String {
+    let mut out = format!("# 变更日志 {}\n", range);
+
+    for repo in repos {
+        let commits = match &repo.commits {
+            Ok(commits) if !commits.is_empty() => commits,
+            _ => continue,
+        };
+        out.push_str(&format!("\n## {}\n", repo.name));
+
+        let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+        for commit in commits {
+            let (group, description) = classify(&commit.subject, options);
+            let line = format!("- {} (`{}`, {})", link_issues(&description, options), commit.short_hash, commit.author);
+            match groups.iter_mut().find(|(g, _)| *g == group) {
+                Some((_, lines)) => lines.push(line),
+                None => groups.push((group, vec![line])),
+            }
+        }
+        groups.sort_by_key(|(g, _)| group_rank(g, options));
+
+        for (group, lines) in groups {
+            out.push_str(&format!("\n### {}\n\n", group));
+            for line in lines {
+                out.push_str(&line);
+                out.push('\n');
+            }
+        }
+    }
+
+    out
+}
+
+/// 保存变更日志到文件
+pub fn save_changelog(path: &Path, content: &str) -> Result<(), String> {
+    fs::write(path, content).map_err(|e| format!("写入文件失败: {}", e))
+}
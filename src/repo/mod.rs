@@ -1,7 +1,10 @@
+#[allow(clippy::module_inception)]
 pub mod repo;
 pub mod operations;
 pub mod clone;
+pub mod changelog;
 
 pub use repo::{find_git_repos, is_git_repo, RepoInfo, get_repo_info, get_repos_info_parallel, print_repos_table};
 pub use operations::{pull_all_repos_parallel, fetch_all_repos_parallel, switch_all_repos_parallel, clean_all_repos_parallel};
-pub use clone::{gen_clone_commands, save_script};
\ No newline at end of file
+pub use clone::{gen_clone_commands, save_script};
+pub use changelog::{ChangelogOptions, parse_range, collect_commits_parallel, render_changelog, save_changelog};
\ No newline at end of file
@@ -78,7 +78,7 @@ fn branch_exists_local(dir: &Path, branch: &str) -> bool {
         .arg("show-ref")
         .arg("--verify")
         .arg("--quiet")
-        .arg(&format!("refs/heads/{}", branch))
+        .arg(format!("refs/heads/{}", branch))
         .status()
         .map(|status| status.success())
         .unwrap_or(false)
@@ -97,7 +97,7 @@ fn remote_branch_exists(dir: &Path, remote: &str, branch: &str) -> bool {
         .arg("show-ref")
         .arg("--verify")
         .arg("--quiet")
-        .arg(&format!("refs/remotes/{}/{}", remote, branch))
+        .arg(format!("refs/remotes/{}/{}", remote, branch))
         .status()
         .map(|status| status.success())
         .unwrap_or(false)
@@ -131,7 +131,7 @@ fn switch_branch(dir: &Path, branch: &str, force: bool) -> Result<String, String
                 .arg("checkout")
                 .arg("-b")
                 .arg(branch)
-                .arg(&format!("{}/{}", remote, branch))
+                .arg(format!("{}/{}", remote, branch))
                 .output()
                 .map_err(|e| format!("执行 git checkout -b 失败: {}", e))?;
 
@@ -81,7 +81,7 @@ pub fn get_repo_info(dir: &Path) -> RepoInfo {
         let ahead_behind = run_git(dir, &["rev-list", "--left-right", "--count", &format!("{}...@{{u}}", branch)])
             .unwrap_or_else(|| "0\t0".to_string());
         let parts: Vec<&str> = ahead_behind.split_whitespace().collect();
-        let ahead = parts.get(0).and_then(|s| s.parse().ok());
+        let ahead = parts.first().and_then(|s| s.parse().ok());
         let behind = parts.get(1).and_then(|s| s.parse().ok());
         (ahead, behind)
     } else {
@@ -114,7 +114,7 @@ pub fn get_repos_info_parallel(repos: Vec<PathBuf>) -> Vec<RepoInfo> {
     }
     
     // 按名称排序
-    infos.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    infos.sort_by_key(|info| info.name.to_lowercase());
     infos
 }
 
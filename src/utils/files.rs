@@ -0,0 +1,94 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 命令行展开得到的输入文件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputFile {
+    pub path: PathBuf,
+    /// 相对于所给目录或通配符固定前缀的路径，输出到目录时按它存放
+    pub relative: PathBuf,
+}
+
+/// 展开命令行给出的路径：目录递归收集指定扩展名的文件，含 `*`、`?`、`[` 的按通配符匹配，
+/// 其余视为文件。结果按给出的顺序排列并去重
+pub fn expand_inputs(patterns: &[String], extensions: &[String]) -> Result<Vec<InputFile>, String> {
+    let mut inputs = Vec::new();
+    let mut seen = HashSet::new();
+    let mut push = |path: PathBuf, relative: PathBuf, inputs: &mut Vec<InputFile>| {
+        if seen.insert(path.clone()) {
+            inputs.push(InputFile { path, relative });
+        }
+    };
+
+    for pattern in patterns {
+        let path = Path::new(pattern);
+        if path.is_dir() {
+            for file in files_in(path, extensions)? {
+                let relative = file.strip_prefix(path).unwrap_or(&file).to_path_buf();
+                push(file, relative, &mut inputs);
+            }
+        } else if path.exists() {
+            push(path.to_path_buf(), file_name(path), &mut inputs);
+        } else if pattern.contains(['*', '?', '[']) {
+            let base = glob_base(pattern);
+            let options = glob::MatchOptions { require_literal_leading_dot: true, ..Default::default() };
+            let entries = glob::glob_with(pattern, options).map_err(|e| format!("通配符无效 {}: {}", pattern, e))?;
+            let mut matched = false;
+            for entry in entries {
+                let entry = entry.map_err(|e| format!("读取失败: {}", e))?;
+                let files = if entry.is_dir() { files_in(&entry, extensions)? } else { vec![entry] };
+                for file in files {
+                    matched = true;
+                    let relative = file.strip_prefix(&base).map(Path::to_path_buf).unwrap_or_else(|_| file_name(&file));
+                    push(file, relative, &mut inputs);
+                }
+            }
+            if !matched {
+                return Err(format!("没有匹配的文件: {}", pattern));
+            }
+        } else {
+            return Err(format!("文件不存在: {}", pattern));
+        }
+    }
+    Ok(inputs)
+}
+
+/// 递归收集目录下指定扩展名的文件，跳过隐藏文件与目录，按路径排序
+pub fn files_in(dir: &Path, extensions: &[String]) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    collect_files(dir, extensions, &mut files)?;
+    Ok(files)
+}
+
+fn collect_files(dir: &Path, extensions: &[String], files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("读取目录失败 {}: {}", dir.display(), e))?;
+    let mut paths: Vec<PathBuf> = entries.filter_map(|e| e.ok().map(|e| e.path())).collect();
+    paths.sort();
+    for path in paths {
+        if path.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.')) {
+            continue;
+        }
+        if path.is_dir() {
+            collect_files(&path, extensions, files)?;
+        } else if path
+            .extension()
+            .is_some_and(|ext| extensions.iter().any(|e| e.trim_start_matches('.').eq_ignore_ascii_case(&ext.to_string_lossy())))
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// 通配符中不含通配字符的目录前缀
+fn glob_base(pattern: &str) -> PathBuf {
+    Path::new(pattern)
+        .components()
+        .take_while(|c| !c.as_os_str().to_string_lossy().contains(['*', '?', '[']))
+        .collect()
+}
+
+fn file_name(path: &Path) -> PathBuf {
+    path.file_name().map(PathBuf::from).unwrap_or_else(|| path.to_path_buf())
+}
@@ -1,5 +1,11 @@
 pub mod command;
+pub mod files;
 pub mod parallel;
+pub mod prompt;
+pub mod progress;
 
 pub use command::run_cmd_capture;
-pub use parallel::ParallelExecutor;
\ No newline at end of file
+pub use files::{expand_inputs, InputFile};
+pub use parallel::ParallelExecutor;
+pub use prompt::confirm;
+pub use progress::{disable_progress, Progress};
\ No newline at end of file
@@ -6,13 +6,25 @@ use std::thread;
 pub struct ParallelExecutor;
 
 impl ParallelExecutor {
-    /// 并发执行函数，对每个路径执行指定操作
+    /// 并发执行函数，对每个路径执行指定操作，按输入顺序返回失败的路径及错误
     pub fn execute<F, E>(repos: Vec<PathBuf>, operation: F) -> Vec<(PathBuf, E)>
     where
         F: Fn(&PathBuf) -> Result<(), E> + Send + Sync + 'static,
         E: Send + 'static + std::fmt::Debug,
     {
-        let errors: Arc<Mutex<Vec<(PathBuf, E)>>> = Arc::new(Mutex::new(Vec::new()));
+        Self::map(repos, operation)
+            .into_iter()
+            .filter_map(|(repo, result)| result.err().map(|e| (repo, e)))
+            .collect()
+    }
+
+    /// 并发执行函数并收集每个路径的结果，结果顺序与输入一致
+    pub fn map<F, T>(repos: Vec<PathBuf>, operation: F) -> Vec<(PathBuf, T)>
+    where
+        F: Fn(&PathBuf) -> T + Send + Sync + 'static,
+        T: Send + 'static,
+    {
+        let results: Arc<Mutex<Vec<(usize, T)>>> = Arc::new(Mutex::new(Vec::new()));
         let repos_arc = Arc::new(repos);
         let idx = Arc::new(Mutex::new(0usize));
         let operation_arc = Arc::new(operation);
@@ -26,9 +38,9 @@ impl ParallelExecutor {
         for _ in 0..workers {
             let repos_arc_cl = Arc::clone(&repos_arc);
             let idx_cl = Arc::clone(&idx);
-            let errors_cl = Arc::clone(&errors);
+            let results_cl = Arc::clone(&results);
             let operation_cl = Arc::clone(&operation_arc);
-            
+
             let handle = thread::spawn(move || loop {
                 let i = {
                     let mut guard = idx_cl.lock().unwrap();
@@ -39,10 +51,8 @@ impl ParallelExecutor {
                 if i >= repos_arc_cl.len() {
                     break;
                 }
-                let repo = &repos_arc_cl[i];
-                if let Err(e) = operation_cl(repo) {
-                    errors_cl.lock().unwrap().push((repo.clone(), e));
-                }
+                let value = operation_cl(&repos_arc_cl[i]);
+                results_cl.lock().unwrap().push((i, value));
             });
             handles.push(handle);
         }
@@ -51,6 +61,9 @@ impl ParallelExecutor {
             let _ = h.join();
         }
 
-        Arc::try_unwrap(errors).unwrap().into_inner().unwrap()
+        let mut results = Arc::try_unwrap(results).ok().unwrap().into_inner().unwrap();
+        results.sort_by_key(|(i, _)| *i);
+        let repos = Arc::try_unwrap(repos_arc).unwrap();
+        results.into_iter().map(|(i, value)| (repos[i].clone(), value)).collect()
     }
 }
\ No newline at end of file
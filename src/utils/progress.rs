@@ -0,0 +1,72 @@
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// 刷新进度的最小间隔
+const REFRESH_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 是否关闭进度显示
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// 关闭之后创建的所有进度显示，用于并行处理多个文件时避免输出交错
+pub fn disable_progress() {
+    DISABLED.store(true, Ordering::Relaxed);
+}
+
+/// 按字节数显示处理进度，仅在 stderr 为终端时输出
+pub struct Progress {
+    label: String,
+    total: u64,
+    enabled: bool,
+    last_draw: Option<Instant>,
+}
+
+impl Progress {
+    pub fn new(label: &str, total: u64) -> Self {
+        Progress {
+            label: label.to_string(),
+            total,
+            enabled: io::stderr().is_terminal() && !DISABLED.load(Ordering::Relaxed),
+            last_draw: None,
+        }
+    }
+
+    /// 更新已处理的字节数
+    pub fn update(&mut self, done: u64) {
+        if !self.enabled || self.last_draw.is_some_and(|t| t.elapsed() < REFRESH_INTERVAL) {
+            return;
+        }
+        self.last_draw = Some(Instant::now());
+        self.draw(done);
+    }
+
+    /// 输出最终进度并换行
+    pub fn finish(&mut self) {
+        if self.enabled && self.last_draw.is_some() {
+            self.draw(self.total);
+            eprintln!();
+        }
+    }
+
+    fn draw(&self, done: u64) {
+        let percent = (done.min(self.total) * 100).checked_div(self.total).unwrap_or(100);
+        eprint!("\r{} {:>3}% ({} / {})", self.label, percent, format_size(done.min(self.total)), format_size(self.total));
+        let _ = io::stderr().flush();
+    }
+}
+
+/// 格式化字节数，例如 1.5 MB
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
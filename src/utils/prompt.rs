@@ -0,0 +1,15 @@
+use std::io::{self, Write};
+
+/// 在终端询问用户确认，输入 y/yes 返回 true
+pub fn confirm(message: &str) -> bool {
+    print!("{} [y/N] ", message);
+    if io::stdout().flush().is_err() {
+        return false;
+    }
+
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
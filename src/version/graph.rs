@@ -0,0 +1,279 @@
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+
+use super::manifest::{rewrite_manifest, rewrite_version_req, scan_manifests, Manifest};
+
+/// 依赖方随之升级时的版本递增级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BumpLevel {
+    Major,
+    Minor,
+    Patch,
+    /// 只更新依赖声明，不升级依赖方自身版本
+    None,
+}
+
+/// 仓库间的模块依赖图
+pub struct DepGraph {
+    pub modules: Vec<Manifest>,
+    index: HashMap<String, usize>,
+    /// dependents[i] 为直接依赖模块 i 的模块
+    dependents: Vec<Vec<usize>>,
+}
+
+/// 升级计划中的一步
+#[derive(Debug)]
+pub struct PlanStep {
+    pub module: usize,
+    pub old_version: Option<String>,
+    /// 需要写入清单的新版本；None 表示自身版本不变或随 parent 继承
+    pub new_version: Option<String>,
+    /// 随 parent 继承得到的新版本，仅用于显示
+    pub inherited_version: Option<String>,
+    /// (依赖名, 旧版本声明, 新版本声明)
+    pub dep_updates: Vec<(String, String, String)>,
+}
+
+impl DepGraph {
+    /// 扫描所有仓库的清单文件并构建依赖图
+    pub fn build(repos: &[PathBuf]) -> Self {
+        let mut modules = Vec::new();
+        for repo in repos {
+            modules.extend(scan_manifests(repo));
+        }
+
+        let mut index = HashMap::new();
+        for (i, m) in modules.iter().enumerate() {
+            if let Some(&prev) = index.get(&m.name) {
+                let prev: &Manifest = &modules[prev];
+                eprintln!("[WARN] 模块 {} 重复定义: {} 与 {}，使用前者", m.name, prev.path.display(), m.path.display());
+            } else {
+                index.insert(m.name.clone(), i);
+            }
+        }
+
+        let mut dependents = vec![Vec::new(); modules.len()];
+        for (i, m) in modules.iter().enumerate() {
+            let targets: BTreeSet<usize> = m.deps.iter().filter_map(|d| index.get(&d.name).copied()).filter(|&t| t != i).collect();
+            for t in targets {
+                dependents[t].push(i);
+            }
+        }
+
+        DepGraph { modules, index, dependents }
+    }
+
+    /// 按模块名、构件名（artifactId）或仓库目录名查找模块
+    pub fn find(&self, key: &str) -> Result<usize, String> {
+        if let Some(&i) = self.index.get(key) {
+            return Ok(i);
+        }
+
+        let by_artifact: Vec<usize> = (0..self.modules.len())
+            .filter(|&i| self.modules[i].name.rsplit(':').next() == Some(key))
+            .collect();
+        if by_artifact.len() == 1 {
+            return Ok(by_artifact[0]);
+        }
+
+        let by_repo: Vec<usize> = (0..self.modules.len())
+            .filter(|&i| {
+                let m = &self.modules[i];
+                m.repo.file_name().is_some_and(|n| n == key) && m.path.parent() == Some(m.repo.as_path())
+            })
+            .collect();
+        if by_repo.len() == 1 {
+            return Ok(by_repo[0]);
+        }
+
+        let candidates: Vec<&str> = by_artifact.iter().chain(by_repo.iter()).map(|&i| self.modules[i].name.as_str()).collect();
+        if candidates.is_empty() {
+            Err(format!("未找到模块 '{}'", key))
+        } else {
+            Err(format!("模块 '{}' 不唯一，请使用完整名称: {}", key, candidates.join(", ")))
+        }
+    }
+
+    /// 模块所在仓库的目录名
+    pub fn repo_name(&self, module: usize) -> String {
+        repo_label(&self.modules[module].repo)
+    }
+
+    /// 从 root 出发需要处理的模块，按依赖顺序（被依赖者在前）排列
+    pub fn ordered_dependents(&self, root: usize, transitive: bool) -> Result<Vec<usize>, String> {
+        let mut affected = BTreeSet::from([root]);
+        let mut queue = VecDeque::from([root]);
+        while let Some(i) = queue.pop_front() {
+            for &d in &self.dependents[i] {
+                if affected.insert(d) && transitive {
+                    queue.push_back(d);
+                }
+            }
+        }
+
+        // 在受影响的子图上做拓扑排序（Kahn）
+        let mut in_degree: HashMap<usize, usize> = affected.iter().map(|&i| (i, 0)).collect();
+        for &i in &affected {
+            for d in &self.dependents[i] {
+                if let Some(n) = in_degree.get_mut(d) {
+                    *n += 1;
+                }
+            }
+        }
+        let mut ready: VecDeque<usize> = affected.iter().copied().filter(|i| in_degree[i] == 0).collect();
+        let mut order = Vec::with_capacity(affected.len());
+        while let Some(i) = ready.pop_front() {
+            order.push(i);
+            for d in &self.dependents[i] {
+                if let Some(n) = in_degree.get_mut(d) {
+                    *n -= 1;
+                    if *n == 0 {
+                        ready.push_back(*d);
+                    }
+                }
+            }
+        }
+
+        if order.len() < affected.len() {
+            let cycle: Vec<&str> = affected
+                .iter()
+                .filter(|i| !order.contains(i))
+                .map(|&i| self.modules[i].name.as_str())
+                .collect();
+            return Err(format!("模块之间存在循环依赖: {}", cycle.join(", ")));
+        }
+        Ok(order)
+    }
+}
+
+fn repo_label(repo: &Path) -> String {
+    repo.file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| repo.to_string_lossy().to_string())
+}
+
+/// 按级别递增版本号，保留 -SNAPSHOT 等后缀
+pub fn bump_version(version: &str, level: BumpLevel) -> Option<String> {
+    let split = version.find(['-', '+']).unwrap_or(version.len());
+    let (core, suffix) = version.split_at(split);
+    let mut parts: Vec<u64> = core.split('.').map(|p| p.parse().ok()).collect::<Option<_>>()?;
+    while parts.len() < 3 {
+        parts.push(0);
+    }
+    match level {
+        BumpLevel::Major => {
+            parts[0] += 1;
+            parts[1] = 0;
+            parts[2] = 0;
+        }
+        BumpLevel::Minor => {
+            parts[1] += 1;
+            parts[2] = 0;
+        }
+        BumpLevel::Patch => parts[2] += 1,
+        BumpLevel::None => return Some(version.to_string()),
+    }
+    let core: Vec<String> = parts.iter().map(|p| p.to_string()).collect();
+    Some(format!("{}{}", core.join("."), suffix))
+}
+
+/// 计算升级计划：root 升级到 new_version，并沿依赖图传播到所有依赖方
+pub fn plan_bump(graph: &DepGraph, root: usize, old_version: &str, new_version: &str, cascade: BumpLevel) -> Result<Vec<PlanStep>, String> {
+    let root_module = &graph.modules[root];
+    // 版本写在 parent / workspace 中，只改依赖方会让它们引用一个不会发布的版本
+    if root_module.version_inherited {
+        let from = root_module.parent.as_deref().unwrap_or("workspace");
+        return Err(format!("模块 {} 的版本继承自 {}，请对声明版本的模块执行升级", root_module.name, from));
+    }
+    if let Some(current) = &root_module.version
+        && current != old_version
+    {
+        return Err(format!("模块 {} 当前版本为 {}，与指定的旧版本 {} 不一致", root_module.name, current, old_version));
+    }
+
+    let order = graph.ordered_dependents(root, cascade != BumpLevel::None)?;
+    let mut new_versions: HashMap<&str, String> = HashMap::from([(root_module.name.as_str(), new_version.to_string())]);
+    let mut plan = Vec::new();
+
+    for i in order {
+        let module = &graph.modules[i];
+        let mut dep_updates = Vec::new();
+        for dep in &module.deps {
+            let (Some(new_dep_version), Some(req)) = (new_versions.get(dep.name.as_str()), &dep.version) else { continue };
+            match rewrite_version_req(req, new_dep_version) {
+                Some(new_req) if new_req != *req => dep_updates.push((dep.name.clone(), req.clone(), new_req)),
+                Some(_) => {}
+                None => eprintln!("[WARN] {} 对 {} 的版本声明 '{}' 无法自动改写，请手动处理", module.name, dep.name, req),
+            }
+        }
+
+        let inherited_version = module
+            .parent
+            .as_deref()
+            .filter(|_| module.version_inherited)
+            .and_then(|p| new_versions.get(p).cloned());
+
+        let new_own_version = if i == root {
+            Some(new_version.to_string())
+        } else if module.version_inherited || cascade == BumpLevel::None {
+            None
+        } else {
+            module.version.as_deref().and_then(|v| bump_version(v, cascade))
+        };
+
+        if let Some(v) = inherited_version.as_ref().or(new_own_version.as_ref()) {
+            new_versions.insert(module.name.as_str(), v.clone());
+        }
+        if new_own_version.is_none() && inherited_version.is_none() && dep_updates.is_empty() {
+            continue;
+        }
+        plan.push(PlanStep {
+            module: i,
+            old_version: module.version.clone(),
+            new_version: if module.version_inherited { None } else { new_own_version },
+            inherited_version,
+            dep_updates,
+        });
+    }
+
+    Ok(plan)
+}
+
+/// 打印升级计划
+pub fn print_plan(graph: &DepGraph, plan: &[PlanStep]) {
+    println!("升级计划（按依赖顺序执行）:");
+    for (n, step) in plan.iter().enumerate() {
+        let module = &graph.modules[step.module];
+        let version = match (&step.old_version, &step.new_version, &step.inherited_version) {
+            (Some(old), Some(new), _) => format!("{} -> {}", old, new),
+            (Some(old), None, Some(new)) => format!("{} -> {}（继承自 parent）", old, new),
+            (None, None, Some(new)) => format!("{}（继承自 parent）", new),
+            (Some(old), None, None) => format!("{}（不变）", old),
+            (None, _, _) => "-".to_string(),
+        };
+        println!("{:>3}. [{}] {} ({}) {}", n + 1, graph.repo_name(step.module), module.name, module.kind.label(), version);
+        println!("       {}", module.path.display());
+        for (dep, old, new) in &step.dep_updates {
+            println!("       依赖 {}: {} -> {}", dep, old, new);
+        }
+    }
+}
+
+/// 按计划顺序改写清单文件，返回修改的文件数
+pub fn apply_plan(graph: &DepGraph, plan: &[PlanStep]) -> Result<usize, String> {
+    let mut count = 0;
+    for step in plan {
+        if step.new_version.is_none() && step.dep_updates.is_empty() {
+            continue;
+        }
+        let module = &graph.modules[step.module];
+        let deps: Vec<(String, String)> = step.dep_updates.iter().map(|(name, _, new)| (name.clone(), new.clone())).collect();
+        rewrite_manifest(module, step.new_version.as_deref(), &deps)
+            .map_err(|e| format!("{}: {}", module.path.display(), e))?;
+        println!("[OK] {}", module.path.display());
+        count += 1;
+    }
+    Ok(count)
+}
@@ -0,0 +1,561 @@
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use roxmltree::{Document, Node};
+use toml_edit::{DocumentMut, Item, Value};
+
+/// 扫描时跳过的目录
+const SKIP_DIRS: &[&str] = &["target", "node_modules", "build", "dist", "out"];
+
+/// 扫描清单文件的最大目录深度
+const MAX_DEPTH: usize = 6;
+
+/// 清单文件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifestKind {
+    Maven,
+    Cargo,
+    Npm,
+}
+
+impl ManifestKind {
+    /// 根据文件名识别清单类型
+    pub fn from_file_name(name: &str) -> Option<Self> {
+        match name {
+            "pom.xml" => Some(ManifestKind::Maven),
+            "Cargo.toml" => Some(ManifestKind::Cargo),
+            "package.json" => Some(ManifestKind::Npm),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ManifestKind::Maven => "maven",
+            ManifestKind::Cargo => "cargo",
+            ManifestKind::Npm => "npm",
+        }
+    }
+}
+
+/// 清单中的一条依赖声明
+#[derive(Debug, Clone)]
+pub struct DepRef {
+    /// 被依赖模块名（Maven 为 groupId:artifactId）
+    pub name: String,
+    /// 声明的版本号或版本范围（已解析属性引用）
+    pub version: Option<String>,
+}
+
+/// 从清单文件解析出的模块信息
+#[derive(Debug, Clone)]
+pub struct Manifest {
+    pub kind: ManifestKind,
+    pub path: PathBuf,
+    pub repo: PathBuf,
+    pub name: String,
+    pub version: Option<String>,
+    /// 版本继承自 parent / workspace，清单中没有显式版本
+    pub version_inherited: bool,
+    /// Maven parent 坐标
+    pub parent: Option<String>,
+    pub deps: Vec<DepRef>,
+}
+
+/// 递归扫描仓库中的 pom.xml / Cargo.toml / package.json
+pub fn scan_manifests(repo: &Path) -> Vec<Manifest> {
+    let mut files = Vec::new();
+    collect_manifest_files(repo, 0, &mut files);
+    files.sort();
+
+    let mut manifests = Vec::new();
+    for file in files {
+        match parse_manifest(&file, repo) {
+            Ok(Some(m)) => manifests.push(m),
+            Ok(None) => {}
+            Err(e) => eprintln!("[WARN] 解析 {} 失败: {}", file.display(), e),
+        }
+    }
+    manifests
+}
+
+fn collect_manifest_files(dir: &Path, depth: usize, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if path.is_dir() {
+            if depth < MAX_DEPTH && !name.starts_with('.') && !SKIP_DIRS.contains(&name.as_str()) {
+                collect_manifest_files(&path, depth + 1, files);
+            }
+        } else if ManifestKind::from_file_name(&name).is_some() {
+            files.push(path);
+        }
+    }
+}
+
+/// 解析单个清单文件，没有模块名的清单返回 None
+pub fn parse_manifest(path: &Path, repo: &Path) -> Result<Option<Manifest>, String> {
+    let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let kind = ManifestKind::from_file_name(&file_name).ok_or(format!("不支持的清单文件: {}", path.display()))?;
+    let text = fs::read_to_string(path).map_err(|e| format!("读取文件失败: {}", e))?;
+
+    let parsed = match kind {
+        ManifestKind::Maven => parse_maven(&text)?,
+        ManifestKind::Cargo => parse_cargo(&text)?,
+        ManifestKind::Npm => parse_npm(&text)?,
+    };
+
+    Ok(parsed.map(|p| Manifest {
+        kind,
+        path: path.to_path_buf(),
+        repo: repo.to_path_buf(),
+        name: p.name,
+        version: p.version,
+        version_inherited: p.version_inherited,
+        parent: p.parent,
+        deps: p.deps,
+    }))
+}
+
+/// 改写清单：更新模块自身版本及指定依赖的版本声明
+pub fn rewrite_manifest(manifest: &Manifest, new_version: Option<&str>, dep_updates: &[(String, String)]) -> Result<(), String> {
+    let text = fs::read_to_string(&manifest.path).map_err(|e| format!("读取文件失败: {}", e))?;
+    let updated = match manifest.kind {
+        ManifestKind::Maven => rewrite_maven(&text, new_version, dep_updates)?,
+        ManifestKind::Cargo => rewrite_cargo(&text, new_version, dep_updates)?,
+        ManifestKind::Npm => rewrite_npm(&text, new_version, dep_updates)?,
+    };
+    if updated != text {
+        fs::write(&manifest.path, updated).map_err(|e| format!("写入文件失败: {}", e))?;
+    }
+    Ok(())
+}
+
+/// 按新版本号改写版本声明，保留 ^ ~ >= 等前缀；无法识别的版本范围返回 None
+pub fn rewrite_version_req(req: &str, new_version: &str) -> Option<String> {
+    let trimmed = req.trim();
+    let idx = trimmed.find(|c: char| c.is_ascii_digit())?;
+    let (prefix, rest) = trimmed.split_at(idx);
+    if !prefix.chars().all(|c| matches!(c, '^' | '~' | '=' | '>' | '<' | 'v' | ' ')) {
+        return None;
+    }
+    if rest.contains([',', ' ', '|', '*', 'x']) {
+        return None;
+    }
+    Some(format!("{}{}", prefix, new_version))
+}
+
+struct Parsed {
+    name: String,
+    version: Option<String>,
+    version_inherited: bool,
+    parent: Option<String>,
+    deps: Vec<DepRef>,
+}
+
+// ---- Maven ----
+
+fn child<'a, 'i>(node: Node<'a, 'i>, name: &str) -> Option<Node<'a, 'i>> {
+    node.children().find(|n| n.is_element() && n.tag_name().name() == name)
+}
+
+fn child_text(node: Node, name: &str) -> Option<String> {
+    child(node, name).and_then(|n| n.text()).map(|s| s.trim().to_string())
+}
+
+/// 解析 ${...} 属性引用所指向的元素
+fn maven_property_node<'a, 'i>(project: Node<'a, 'i>, value: &str) -> Option<Node<'a, 'i>> {
+    let prop = value.strip_prefix("${")?.strip_suffix('}')?;
+    match prop {
+        "project.version" | "version" => child(project, "version").or_else(|| child(project, "parent").and_then(|p| child(p, "version"))),
+        "project.groupId" | "groupId" => child(project, "groupId").or_else(|| child(project, "parent").and_then(|p| child(p, "groupId"))),
+        _ => child(project, "properties").and_then(|p| child(p, prop)),
+    }
+}
+
+fn maven_resolve(project: Node, value: String) -> String {
+    maven_property_node(project, &value)
+        .and_then(|n| n.text())
+        .map(|s| s.trim().to_string())
+        .unwrap_or(value)
+}
+
+/// 所有依赖坐标元素：parent、dependencies、dependencyManagement、plugins
+fn maven_dep_nodes<'a, 'i>(project: Node<'a, 'i>) -> Vec<Node<'a, 'i>> {
+    let mut nodes = Vec::new();
+    if let Some(parent) = child(project, "parent") {
+        nodes.push(parent);
+    }
+    let mut push_deps = |container: Option<Node<'a, 'i>>, list: &str, item: &str| {
+        if let Some(list) = container.and_then(|c| child(c, list)) {
+            nodes.extend(list.children().filter(|n| n.is_element() && n.tag_name().name() == item));
+        }
+    };
+    push_deps(Some(project), "dependencies", "dependency");
+    push_deps(child(project, "dependencyManagement"), "dependencies", "dependency");
+    push_deps(child(project, "build"), "plugins", "plugin");
+    nodes
+}
+
+fn maven_coord(project: Node, node: Node) -> Option<String> {
+    let group = maven_resolve(project, child_text(node, "groupId")?);
+    let artifact = child_text(node, "artifactId")?;
+    Some(format!("{}:{}", group, artifact))
+}
+
+fn parse_maven(text: &str) -> Result<Option<Parsed>, String> {
+    let doc = Document::parse(text).map_err(|e| format!("XML 解析失败: {}", e))?;
+    let project = doc.root_element();
+    let parent = child(project, "parent");
+
+    let group = child_text(project, "groupId").or_else(|| parent.and_then(|p| child_text(p, "groupId")));
+    let Some(artifact) = child_text(project, "artifactId") else { return Ok(None) };
+    let Some(group) = group else { return Ok(None) };
+
+    let own_version = child_text(project, "version").map(|v| maven_resolve(project, v));
+    let version_inherited = own_version.is_none();
+    let version = own_version.or_else(|| parent.and_then(|p| child_text(p, "version")));
+
+    let deps = maven_dep_nodes(project)
+        .into_iter()
+        .filter_map(|node| {
+            Some(DepRef {
+                name: maven_coord(project, node)?,
+                version: child_text(node, "version").map(|v| maven_resolve(project, v)),
+            })
+        })
+        .collect();
+
+    Ok(Some(Parsed {
+        name: format!("{}:{}", group, artifact),
+        version,
+        version_inherited,
+        parent: parent.and_then(|p| maven_coord(project, p)),
+        deps,
+    }))
+}
+
+/// 记录一处版本文本替换；属性引用会改写到对应的 properties 元素
+fn push_maven_edit(edits: &mut Vec<(Range<usize>, String)>, project: Node, node: Node, value: &str, follow_property: bool) {
+    let raw = node.text().unwrap_or("").trim();
+    let target = if raw.starts_with("${") {
+        if !follow_property || raw == "${project.version}" || raw == "${version}" {
+            return;
+        }
+        match maven_property_node(project, raw) {
+            Some(n) => n,
+            None => return,
+        }
+    } else {
+        node
+    };
+    if let Some(text_node) = target.first_child().filter(|c| c.is_text()) {
+        let range = text_node.range();
+        if !edits.iter().any(|(r, _)| *r == range) {
+            edits.push((range, value.to_string()));
+        }
+    }
+}
+
+fn rewrite_maven(text: &str, new_version: Option<&str>, dep_updates: &[(String, String)]) -> Result<String, String> {
+    let doc = Document::parse(text).map_err(|e| format!("XML 解析失败: {}", e))?;
+    let project = doc.root_element();
+    let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+
+    if let (Some(v), Some(node)) = (new_version, child(project, "version")) {
+        push_maven_edit(&mut edits, project, node, v, false);
+    }
+    for node in maven_dep_nodes(project) {
+        let Some(coord) = maven_coord(project, node) else { continue };
+        let Some((_, new_req)) = dep_updates.iter().find(|(name, _)| *name == coord) else { continue };
+        if let Some(version_node) = child(node, "version") {
+            push_maven_edit(&mut edits, project, version_node, new_req, true);
+        }
+    }
+
+    edits.sort_by_key(|(r, _)| std::cmp::Reverse(r.start));
+    let mut out = text.to_string();
+    for (range, value) in edits {
+        out.replace_range(range, &value);
+    }
+    Ok(out)
+}
+
+// ---- Cargo ----
+
+const CARGO_DEP_TABLES: &[&str] = &["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// 收集所有依赖表（含 workspace 与 target 下的依赖表）
+fn cargo_dep_tables(doc: &DocumentMut) -> Vec<&Item> {
+    let mut tables = Vec::new();
+    for name in CARGO_DEP_TABLES {
+        if let Some(t) = doc.get(name) {
+            tables.push(t);
+        }
+    }
+    if let Some(t) = doc.get("workspace").and_then(|w| w.get("dependencies")) {
+        tables.push(t);
+    }
+    if let Some(targets) = doc.get("target").and_then(|t| t.as_table_like()) {
+        for (_, target) in targets.iter() {
+            for name in CARGO_DEP_TABLES {
+                if let Some(t) = target.get(name) {
+                    tables.push(t);
+                }
+            }
+        }
+    }
+    tables
+}
+
+fn cargo_dep_name(key: &str, item: &Item) -> String {
+    item.get("package").and_then(|p| p.as_str()).unwrap_or(key).to_string()
+}
+
+fn cargo_dep_version(item: &Item) -> Option<&str> {
+    item.as_str().or_else(|| item.get("version").and_then(|v| v.as_str()))
+}
+
+fn parse_cargo(text: &str) -> Result<Option<Parsed>, String> {
+    let doc: DocumentMut = text.parse().map_err(|e| format!("TOML 解析失败: {}", e))?;
+    let Some(name) = doc.get("package").and_then(|p| p.get("name")).and_then(|n| n.as_str()) else {
+        return Ok(None);
+    };
+    let package_version = doc.get("package").and_then(|p| p.get("version"));
+    let workspace_version = doc.get("workspace").and_then(|w| w.get("package")).and_then(|p| p.get("version")).and_then(|v| v.as_str());
+    let version = package_version.and_then(|v| v.as_str()).or(workspace_version).map(|v| v.to_string());
+    let version_inherited = package_version.is_some_and(|v| v.is_table_like()) && workspace_version.is_none();
+
+    let mut deps = Vec::new();
+    for table in cargo_dep_tables(&doc) {
+        if let Some(table) = table.as_table_like() {
+            for (key, item) in table.iter() {
+                deps.push(DepRef {
+                    name: cargo_dep_name(key, item),
+                    version: cargo_dep_version(item).map(|v| v.to_string()),
+                });
+            }
+        }
+    }
+
+    Ok(Some(Parsed {
+        name: name.to_string(),
+        version,
+        version_inherited,
+        parent: None,
+        deps,
+    }))
+}
+
+/// 替换字符串值，保留原有的空白与注释
+fn set_toml_str(item: &mut Item, new_value: &str) {
+    if let Some(value) = item.as_value_mut()
+        && value.is_str()
+    {
+        let decor = value.decor().clone();
+        *value = Value::from(new_value);
+        *value.decor_mut() = decor;
+    }
+}
+
+fn rewrite_cargo_deps(table: &mut Item, dep_updates: &[(String, String)]) {
+    let Some(table) = table.as_table_like_mut() else { return };
+    for (key, item) in table.iter_mut() {
+        let name = cargo_dep_name(key.get(), item);
+        let Some((_, new_req)) = dep_updates.iter().find(|(n, _)| *n == name) else { continue };
+        if item.as_str().is_some() {
+            set_toml_str(item, new_req);
+        } else if let Some(version) = item.as_table_like_mut().and_then(|t| t.get_mut("version")) {
+            set_toml_str(version, new_req);
+        }
+    }
+}
+
+fn rewrite_cargo(text: &str, new_version: Option<&str>, dep_updates: &[(String, String)]) -> Result<String, String> {
+    let mut doc: DocumentMut = text.parse().map_err(|e| format!("TOML 解析失败: {}", e))?;
+
+    if let Some(v) = new_version {
+        let package_version = doc.get_mut("package").and_then(|p| p.get_mut("version"));
+        match package_version {
+            Some(item) if item.as_str().is_some() => set_toml_str(item, v),
+            _ => {
+                if let Some(item) = doc
+                    .get_mut("workspace")
+                    .and_then(|w| w.get_mut("package"))
+                    .and_then(|p| p.get_mut("version"))
+                {
+                    set_toml_str(item, v);
+                }
+            }
+        }
+    }
+
+    for name in CARGO_DEP_TABLES {
+        if let Some(t) = doc.get_mut(name) {
+            rewrite_cargo_deps(t, dep_updates);
+        }
+    }
+    if let Some(t) = doc.get_mut("workspace").and_then(|w| w.get_mut("dependencies")) {
+        rewrite_cargo_deps(t, dep_updates);
+    }
+    if let Some(targets) = doc.get_mut("target").and_then(|t| t.as_table_like_mut()) {
+        for (_, target) in targets.iter_mut() {
+            for name in CARGO_DEP_TABLES {
+                if let Some(t) = target.get_mut(name) {
+                    rewrite_cargo_deps(t, dep_updates);
+                }
+            }
+        }
+    }
+
+    Ok(doc.to_string())
+}
+
+// ---- npm ----
+
+const NPM_DEP_FIELDS: &[&str] = &["dependencies", "devDependencies", "peerDependencies", "optionalDependencies"];
+
+fn parse_npm(text: &str) -> Result<Option<Parsed>, String> {
+    let json: serde_json::Value = serde_json::from_str(text).map_err(|e| format!("JSON 解析失败: {}", e))?;
+    let Some(name) = json.get("name").and_then(|n| n.as_str()) else { return Ok(None) };
+
+    let mut deps = Vec::new();
+    for field in NPM_DEP_FIELDS {
+        if let Some(map) = json.get(field).and_then(|d| d.as_object()) {
+            for (dep, req) in map {
+                deps.push(DepRef { name: dep.clone(), version: req.as_str().map(|s| s.to_string()) });
+            }
+        }
+    }
+
+    Ok(Some(Parsed {
+        name: name.to_string(),
+        version: json.get("version").and_then(|v| v.as_str()).map(|s| s.to_string()),
+        version_inherited: false,
+        parent: None,
+        deps,
+    }))
+}
+
+/// 只替换 version 与依赖版本字符串所在的文本，保留原有的缩进、键顺序与换行
+fn rewrite_npm(text: &str, new_version: Option<&str>, dep_updates: &[(String, String)]) -> Result<String, String> {
+    serde_json::from_str::<serde_json::Value>(text).map_err(|e| format!("JSON 解析失败: {}", e))?;
+    let mut spans = Vec::new();
+    let mut scanner = JsonScanner { text, pos: 0 };
+    scanner.skip_ws();
+    if !text[scanner.pos..].starts_with('{') {
+        return Ok(text.to_string());
+    }
+    scanner.value(&mut Vec::new(), &mut spans)?;
+
+    let quote = |v: &str| serde_json::to_string(v).unwrap_or_default();
+    let span = |path: &[&str]| spans.iter().find(|(p, _)| p.iter().map(String::as_str).eq(path.iter().copied())).map(|(_, r)| r.clone());
+    let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+
+    if let Some(v) = new_version {
+        match (span(&["version"]), span(&["name"])) {
+            (Some(range), _) => edits.push((range, quote(v))),
+            // 没有 version 字段时紧跟 name 插入，沿用 name 之前的缩进
+            (None, Some(name)) => {
+                let key_start = text[..name.start].rfind('"').and_then(|end| text[..end].rfind('"')).unwrap_or(name.start);
+                let open = text[..key_start].rfind(['{', ',']).map_or(key_start, |k| k + 1);
+                let indent = &text[open..key_start];
+                edits.push((name.end..name.end, format!(",{}\"version\": {}", indent, quote(v))));
+            }
+            (None, None) => {}
+        }
+    }
+    for field in NPM_DEP_FIELDS {
+        for (name, new_req) in dep_updates {
+            if let Some(range) = span(&[field, name.as_str()]) {
+                edits.push((range, quote(new_req)));
+            }
+        }
+    }
+
+    edits.sort_by_key(|(r, _)| std::cmp::Reverse(r.start));
+    let mut out = text.to_string();
+    for (range, value) in edits {
+        out.replace_range(range, &value);
+    }
+    Ok(out)
+}
+
+/// 记录 JSON 中字符串值位置的简单扫描器，输入需已通过 serde_json 校验
+struct JsonScanner<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl JsonScanner<'_> {
+    fn skip_ws(&mut self) {
+        let rest = &self.text[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    /// 读取一个字符串，返回解码后的内容及含引号的字节范围
+    fn string(&mut self) -> Result<(String, Range<usize>), String> {
+        let start = self.pos;
+        let bytes = self.text.as_bytes();
+        let mut k = start + 1;
+        while k < bytes.len() && bytes[k] != b'"' {
+            k += if bytes[k] == b'\\' { 2 } else { 1 };
+        }
+        self.pos = (k + 1).min(bytes.len());
+        let range = start..self.pos;
+        let value = serde_json::from_str(&self.text[range.clone()]).map_err(|e| format!("JSON 解析失败: {}", e))?;
+        Ok((value, range))
+    }
+
+    /// 读取一个值；对象中的字符串值按键路径记录，数组中的元素不记录
+    fn value(&mut self, path: &mut Vec<String>, spans: &mut Vec<(Vec<String>, Range<usize>)>) -> Result<(), String> {
+        self.skip_ws();
+        match self.peek() {
+            Some(b'{') | Some(b'[') => {
+                let object = self.peek() == Some(b'{');
+                let close = if object { b'}' } else { b']' };
+                self.pos += 1;
+                loop {
+                    self.skip_ws();
+                    match self.peek() {
+                        Some(c) if c == close => {
+                            self.pos += 1;
+                            return Ok(());
+                        }
+                        Some(b',') => self.pos += 1,
+                        None => return Err("JSON 解析失败: 意外的结尾".to_string()),
+                        _ if object => {
+                            let (key, _) = self.string()?;
+                            self.skip_ws();
+                            self.pos += 1; // 冒号
+                            path.push(key);
+                            self.value(path, spans)?;
+                            path.pop();
+                        }
+                        _ => {
+                            path.push(String::new());
+                            self.value(path, &mut Vec::new())?;
+                            path.pop();
+                        }
+                    }
+                }
+            }
+            Some(b'"') => {
+                let (_, range) = self.string()?;
+                spans.push((path.clone(), range));
+                Ok(())
+            }
+            _ => {
+                let rest = &self.text[self.pos..];
+                self.pos += rest.find([',', '}', ']', ' ', '\t', '\r', '\n']).unwrap_or(rest.len());
+                Ok(())
+            }
+        }
+    }
+}
@@ -1,3 +1,6 @@
 pub mod replace;
+pub mod manifest;
+pub mod graph;
 
-pub use replace::version_replace;
\ No newline at end of file
+pub use replace::version_replace;
+pub use graph::{DepGraph, BumpLevel, PlanStep, plan_bump, print_plan, apply_plan};
\ No newline at end of file
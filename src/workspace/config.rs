@@ -0,0 +1,305 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use regex::Regex;
+use toml_edit::{value, Array, DocumentMut, Item, Table};
+
+/// 当前配置文件格式版本
+pub const CONFIG_VERSION: i64 = 1;
+/// 配置文件名
+pub const CONFIG_FILE: &str = "workspaces.toml";
+/// 旧版按 `名称=路径` 逐行保存的配置文件名
+pub const LEGACY_FILE: &str = "workspaces.txt";
+
+/// 环境变量名，切换工作区时原样写入 shell 的 export 语句
+static ENV_KEY: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*$").unwrap());
+
+/// 一个工作区
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Workspace {
+    pub name: String,
+    pub path: PathBuf,
+    pub description: Option<String>,
+    pub default_branch: Option<String>,
+    /// 仓库分组：组名到仓库目录列表
+    pub groups: BTreeMap<String, Vec<String>>,
+    /// 远程仓库：名称到地址
+    pub remotes: BTreeMap<String, String>,
+    /// 切换到工作区时设置的环境变量
+    pub env: BTreeMap<String, String>,
+    /// RFC 3339 格式的 UTC 时间，例如 2024-01-01T08:00:00Z
+    pub created_at: Option<String>,
+    pub last_used_at: Option<String>,
+}
+
+impl Workspace {
+    pub fn new(name: &str, path: PathBuf) -> Self {
+        Workspace { name: name.to_string(), path, created_at: Some(now()), ..Default::default() }
+    }
+
+    /// 按修改项更新字段，空值表示删除该项
+    pub fn apply(&mut self, update: &WorkspaceUpdate) -> Result<(), String> {
+        if let Some(path) = &update.path {
+            self.path = path.clone();
+        }
+        if let Some(description) = &update.description {
+            self.description = Some(description.clone()).filter(|d| !d.is_empty());
+        }
+        if let Some(branch) = &update.default_branch {
+            self.default_branch = Some(branch.clone()).filter(|b| !b.is_empty());
+        }
+        for spec in &update.groups {
+            let (name, repos) = assignment(spec, "--group 格式应为 组名=仓库[,仓库]")?;
+            let repos: Vec<String> = repos.split(',').map(|r| r.trim().to_string()).filter(|r| !r.is_empty()).collect();
+            set_or_remove(&mut self.groups, name, (!repos.is_empty()).then_some(repos));
+        }
+        for spec in &update.remotes {
+            let (name, url) = assignment(spec, "--remote 格式应为 名称=地址")?;
+            set_or_remove(&mut self.remotes, name, (!url.is_empty()).then(|| url.to_string()));
+        }
+        for spec in &update.env {
+            let (key, val) = assignment(spec, "--env 格式应为 变量名=值")?;
+            check_env_key(&self.name, key)?;
+            set_or_remove(&mut self.env, key, (!val.is_empty()).then(|| val.to_string()));
+        }
+        Ok(())
+    }
+
+    /// 记录最近使用时间
+    pub fn touch(&mut self) {
+        self.last_used_at = Some(now());
+    }
+}
+
+/// `workspace set` 的修改项
+#[derive(Debug, Clone, Default)]
+pub struct WorkspaceUpdate {
+    pub path: Option<PathBuf>,
+    pub description: Option<String>,
+    pub default_branch: Option<String>,
+    /// `组名=仓库[,仓库]`，仓库为空时删除该组
+    pub groups: Vec<String>,
+    /// `名称=地址`，地址为空时删除
+    pub remotes: Vec<String>,
+    /// `变量名=值`，值为空时删除
+    pub env: Vec<String>,
+}
+
+fn assignment<'a>(spec: &'a str, usage: &str) -> Result<(&'a str, &'a str), String> {
+    match spec.split_once('=') {
+        Some((key, val)) if !key.trim().is_empty() => Ok((key.trim(), val.trim())),
+        _ => Err(format!("{}: {}", usage, spec)),
+    }
+}
+
+fn check_env_key(workspace: &str, key: &str) -> Result<(), String> {
+    if ENV_KEY.is_match(key) {
+        Ok(())
+    } else {
+        Err(format!("工作区 {} 的环境变量名无效: {}（只能包含字母、数字和下划线，且不能以数字开头）", workspace, key))
+    }
+}
+
+fn set_or_remove<T>(map: &mut BTreeMap<String, T>, key: &str, val: Option<T>) {
+    match val {
+        Some(val) => map.insert(key.to_string(), val),
+        None => map.remove(key),
+    };
+}
+
+/// 工作区配置，保存为带版本号的 TOML 文件
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkspaceConfig {
+    pub workspaces: Vec<Workspace>,
+}
+
+impl WorkspaceConfig {
+    pub fn get(&self, name: &str) -> Option<&Workspace> {
+        self.workspaces.iter().find(|w| w.name == name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Workspace> {
+        self.workspaces.iter_mut().find(|w| w.name == name)
+    }
+
+    /// 解析 TOML 配置；版本高于当前支持的版本或环境变量名无效时报错
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let doc: DocumentMut = text.parse().map_err(|e| format!("解析工作区配置失败: {}", e))?;
+        let version = match doc.get("version") {
+            None => CONFIG_VERSION,
+            Some(item) => item.as_integer().ok_or("工作区配置中的 version 应为整数")?,
+        };
+        if version > CONFIG_VERSION {
+            return Err(format!("工作区配置版本为 {}，当前仅支持到 {}，请升级 synapse", version, CONFIG_VERSION));
+        }
+        if version < 1 {
+            return Err(format!("无效的工作区配置版本: {}", version));
+        }
+
+        let mut config = WorkspaceConfig::default();
+        let Some(workspaces) = doc.get("workspaces") else { return Ok(config) };
+        let workspaces = workspaces.as_table_like().ok_or("工作区配置中的 workspaces 应为表")?;
+        for (name, item) in workspaces.iter() {
+            let table = item.as_table_like().ok_or_else(|| format!("工作区 {} 的配置应为表", name))?;
+            let text = |key: &str| table.get(key).and_then(|v| v.as_str()).map(|s| s.to_string());
+            // 时间可以是 TOML 日期时间，也可以是字符串
+            let time = |key: &str| table.get(key).and_then(|v| v.as_datetime().map(|d| d.to_string()).or_else(|| v.as_str().map(|s| s.to_string())));
+            let path = text("path").ok_or_else(|| format!("工作区 {} 缺少 path", name))?;
+            config.workspaces.push(Workspace {
+                name: name.to_string(),
+                path: PathBuf::from(path),
+                description: text("description"),
+                default_branch: text("default_branch"),
+                groups: string_map(table.get("groups"), |v| {
+                    v.as_array().map(|a| a.iter().filter_map(|r| r.as_str().map(|s| s.to_string())).collect())
+                }),
+                remotes: string_map(table.get("remotes"), |v| v.as_str().map(|s| s.to_string())),
+                env: string_map(table.get("env"), |v| v.as_str().map(|s| s.to_string())),
+                created_at: time("created_at"),
+                last_used_at: time("last_used_at"),
+            });
+        }
+        config.check_env_keys()?;
+        Ok(config)
+    }
+
+    /// 检查所有工作区的环境变量名
+    fn check_env_keys(&self) -> Result<(), String> {
+        for workspace in &self.workspaces {
+            for key in workspace.env.keys() {
+                check_env_key(&workspace.name, key)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 生成 TOML 配置
+    pub fn to_toml(&self) -> String {
+        let mut doc = DocumentMut::new();
+        doc["version"] = value(CONFIG_VERSION);
+        let mut workspaces = Table::new();
+        workspaces.set_implicit(true);
+        for workspace in &self.workspaces {
+            let mut table = Table::new();
+            table["path"] = value(workspace.path.display().to_string());
+            if let Some(description) = &workspace.description {
+                table["description"] = value(description.as_str());
+            }
+            if let Some(branch) = &workspace.default_branch {
+                table["default_branch"] = value(branch.as_str());
+            }
+            for (key, time) in [("created_at", &workspace.created_at), ("last_used_at", &workspace.last_used_at)] {
+                if let Some(time) = time {
+                    table[key] = match time.parse::<toml_edit::Datetime>() {
+                        Ok(datetime) => value(datetime),
+                        Err(_) => value(time.as_str()),
+                    };
+                }
+            }
+            let groups = workspace.groups.iter().map(|(k, repos)| (k, value(repos.iter().collect::<Array>())));
+            for (key, entries) in [
+                ("groups", groups.collect::<Vec<_>>()),
+                ("remotes", workspace.remotes.iter().map(|(k, v)| (k, value(v.as_str()))).collect()),
+                ("env", workspace.env.iter().map(|(k, v)| (k, value(v.as_str()))).collect()),
+            ] {
+                if !entries.is_empty() {
+                    let mut sub = Table::new();
+                    for (k, v) in entries {
+                        sub[k.as_str()] = v;
+                    }
+                    table[key] = Item::Table(sub);
+                }
+            }
+            workspaces[workspace.name.as_str()] = Item::Table(table);
+        }
+        doc["workspaces"] = Item::Table(workspaces);
+        format!("# synapse 工作区配置，可手动编辑\n{}", doc)
+    }
+
+    /// 解析旧版 `名称=路径` 配置，返回配置与无法识别的行
+    pub fn parse_legacy(text: &str) -> (Self, Vec<String>) {
+        let mut config = WorkspaceConfig::default();
+        let mut skipped = Vec::new();
+        for line in text.lines().filter(|l| !l.trim().is_empty()) {
+            // 名称取自目录名，以第一个 = 为界，路径中的 = 保留
+            match line.split_once('=') {
+                Some((name, path)) if !name.is_empty() && !path.is_empty() && config.get(name).is_none() => {
+                    config.workspaces.push(Workspace { name: name.to_string(), path: PathBuf::from(path), ..Default::default() });
+                }
+                _ => skipped.push(line.to_string()),
+            }
+        }
+        (config, skipped)
+    }
+
+    /// 读取配置目录下的工作区配置。只有旧版 workspaces.txt 时自动迁移为 workspaces.toml，
+    /// 原文件保留为 workspaces.txt.bak
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        let file = dir.join(CONFIG_FILE);
+        if file.exists() {
+            let text = fs::read_to_string(&file).map_err(|e| format!("读取工作区配置失败: {}", e))?;
+            return WorkspaceConfig::parse(&text);
+        }
+        let legacy = dir.join(LEGACY_FILE);
+        if !legacy.exists() {
+            return Ok(WorkspaceConfig::default());
+        }
+
+        let text = fs::read_to_string(&legacy).map_err(|e| format!("读取工作区配置失败: {}", e))?;
+        let (config, skipped) = WorkspaceConfig::parse_legacy(&text);
+        for line in &skipped {
+            eprintln!("[WARN] 无法识别的工作区配置行，未迁移: {}", line);
+        }
+        config.save(dir)?;
+        let backup = dir.join(format!("{}.bak", LEGACY_FILE));
+        fs::rename(&legacy, &backup).map_err(|e| format!("备份旧版工作区配置失败: {}", e))?;
+        eprintln!(
+            "[OK] 已将 {} 个工作区从 {} 迁移到 {}（原文件保留为 {}）",
+            config.workspaces.len(),
+            LEGACY_FILE,
+            CONFIG_FILE,
+            backup.display()
+        );
+        Ok(config)
+    }
+
+    /// 保存到配置目录，先写临时文件再替换；环境变量名无效时报错
+    pub fn save(&self, dir: &Path) -> Result<(), String> {
+        self.check_env_keys()?;
+        let file = dir.join(CONFIG_FILE);
+        let tmp = dir.join(format!(".{}.tmp", CONFIG_FILE));
+        fs::write(&tmp, self.to_toml()).map_err(|e| format!("保存工作区配置失败: {}", e))?;
+        fs::rename(&tmp, &file).map_err(|e| format!("保存工作区配置失败: {}", e))
+    }
+}
+
+/// 读取值均为同一类型的子表，跳过类型不符的项
+fn string_map<T>(item: Option<&Item>, convert: impl Fn(&toml_edit::Value) -> Option<T>) -> BTreeMap<String, T> {
+    let Some(table) = item.and_then(|i| i.as_table_like()) else { return BTreeMap::new() };
+    table.iter().filter_map(|(k, v)| Some((k.to_string(), convert(v.as_value()?)?))).collect()
+}
+
+/// 当前 UTC 时间
+pub fn now() -> String {
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    format_timestamp(secs)
+}
+
+/// 把 Unix 时间戳格式化为 RFC 3339 UTC 时间
+pub fn format_timestamp(secs: u64) -> String {
+    // 由天数换算公历日期（Howard Hinnant 的 civil_from_days）
+    let days = (secs / 86400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    let rem = secs % 86400;
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month, day, rem / 3600, rem % 3600 / 60, rem % 60)
+}
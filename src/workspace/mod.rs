@@ -1,9 +1,13 @@
+pub mod config;
 pub mod operations;
 
+pub use config::{Workspace, WorkspaceConfig, WorkspaceUpdate};
 pub use operations::{
     create_workspace,
     init_workspace,
     list_workspaces,
     switch_workspace,
+    show_workspace,
+    update_workspace,
     get_config_dir,
 };
@@ -1,8 +1,10 @@
 use directories::ProjectDirs;
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 use std::env;
 
+use super::config::{Workspace, WorkspaceConfig, WorkspaceUpdate};
+
 /// 获取配置目录路径
 pub fn get_config_dir() -> Result<PathBuf, String> {
     if let Some(proj_dirs) = ProjectDirs::from("", "", "synapse-cli") {
@@ -20,94 +22,76 @@ pub fn get_config_dir() -> Result<PathBuf, String> {
     }
 }
 
-/// 获取工作区配置文件路径
-fn get_workspaces_file() -> Result<PathBuf, String> {
+/// 读取工作区配置，必要时从旧版 workspaces.txt 迁移
+fn load_config() -> Result<(PathBuf, WorkspaceConfig), String> {
     let config_dir = get_config_dir()?;
-    Ok(config_dir.join("workspaces.txt"))
+    let config = WorkspaceConfig::load(&config_dir)?;
+    Ok((config_dir, config))
 }
 
-/// 读取所有工作区
-fn read_workspaces() -> Result<Vec<(String, PathBuf)>, String> {
-    let workspaces_file = get_workspaces_file()?;
-    
-    if !workspaces_file.exists() {
-        return Ok(Vec::new());
-    }
-    
-    let content = fs::read_to_string(&workspaces_file)
-        .map_err(|e| format!("读取工作区配置失败: {}", e))?;
-    
-    let mut workspaces = Vec::new();
-    for line in content.lines() {
-        if let Some((name, path)) = line.split_once('=') {
-            workspaces.push((name.to_string(), PathBuf::from(path)));
-        }
-    }
-    
-    Ok(workspaces)
-}
-
-/// 保存工作区配置
-fn save_workspaces(workspaces: &[(String, PathBuf)]) -> Result<(), String> {
-    let workspaces_file = get_workspaces_file()?;
-    
-    let content: String = workspaces
-        .iter()
-        .map(|(name, path)| format!("{}={}", name, path.display()))
-        .collect::<Vec<_>>()
-        .join("\n");
-    
-    fs::write(&workspaces_file, content)
-        .map_err(|e| format!("保存工作区配置失败: {}", e))?;
-    
-    Ok(())
-}
-
-/// 创建新工作区
-pub fn create_workspace() -> Result<(), String> {
+/// 创建新工作区，名称默认为当前目录名
+pub fn create_workspace(name: Option<String>, description: Option<String>, default_branch: Option<String>) -> Result<(), String> {
     let cwd = env::current_dir()
         .map_err(|e| format!("无法获取当前目录: {}", e))?;
     
-    let workspace_name = cwd
-        .file_name()
-        .and_then(|n| n.to_str())
-        .ok_or("无法获取当前目录名称")?
-        .to_string();
+    let workspace_name = match name {
+        Some(name) => name,
+        None => cwd
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or("无法获取当前目录名称")?
+            .to_string(),
+    };
     
-    let mut workspaces = read_workspaces()?;
+    let (config_dir, mut config) = load_config()?;
     
     // 检查是否已存在
-    if workspaces.iter().any(|(name, _)| name == &workspace_name) {
+    if config.get(&workspace_name).is_some() {
         return Err(format!("工作区 '{}' 已存在", workspace_name));
     }
     
-    workspaces.push((workspace_name.clone(), cwd.clone()));
-    save_workspaces(&workspaces)?;
+    let mut workspace = Workspace::new(&workspace_name, cwd.clone());
+    workspace.description = description.filter(|d| !d.is_empty());
+    workspace.default_branch = default_branch.filter(|b| !b.is_empty());
+    config.workspaces.push(workspace);
+    config.save(&config_dir)?;
     
     println!("✅ 成功创建工作区: {} -> {}", workspace_name, cwd.display());
     Ok(())
 }
 
 /// 初始化当前目录为工作区
-pub fn init_workspace() -> Result<(), String> {
-    create_workspace()
+pub fn init_workspace(name: Option<String>, description: Option<String>, default_branch: Option<String>) -> Result<(), String> {
+    create_workspace(name, description, default_branch)
 }
 
 /// 列出所有工作区
 pub fn list_workspaces() -> Result<(), String> {
-    let workspaces = read_workspaces()?;
+    let (_, config) = load_config()?;
     
-    if workspaces.is_empty() {
+    if config.workspaces.is_empty() {
         println!("暂无工作区");
         return Ok(());
     }
     
     println!("工作区列表:");
-    println!("{:<20} {}", "名称", "路径");
+    println!("{:<20} {:<12} {:<12} 路径", "名称", "默认分支", "最近使用");
     println!("{}", "-".repeat(80));
     
-    for (name, path) in workspaces {
-        println!("{:<20} {}", name, path.display());
+    for workspace in &config.workspaces {
+        // 只显示日期部分
+        let last_used = workspace.last_used_at.as_deref().map_or("-", |t| t.get(..10).unwrap_or(t));
+        print!(
+            "{:<20} {:<12} {:<12} {}",
+            workspace.name,
+            workspace.default_branch.as_deref().unwrap_or("-"),
+            last_used,
+            workspace.path.display()
+        );
+        match &workspace.description {
+            Some(description) => println!("  # {}", description),
+            None => println!(),
+        }
     }
     
     Ok(())
@@ -115,23 +99,88 @@ pub fn list_workspaces() -> Result<(), String> {
 
 /// 切换到指定工作区
 pub fn switch_workspace(workspace_name: &str) -> Result<(), String> {
-    let workspaces = read_workspaces()?;
+    let (config_dir, mut config) = load_config()?;
     
-    let workspace = workspaces
-        .iter()
-        .find(|(name, _)| name == workspace_name)
+    let workspace = config
+        .get_mut(workspace_name)
         .ok_or(format!("工作区 '{}' 不存在", workspace_name))?;
     
-    let path = &workspace.1;
-    
-    if !path.exists() {
-        return Err(format!("工作区路径不存在: {}", path.display()));
+    if !workspace.path.exists() {
+        return Err(format!("工作区路径不存在: {}", workspace.path.display()));
     }
     
+    workspace.touch();
+    let workspace = workspace.clone();
+    config.save(&config_dir)?;
+    
     // 在终端中，我们不能直接改变父进程的工作目录
     // 所以我们只能输出切换命令，让用户执行
     println!("请执行以下命令切换到工作区:");
-    println!("cd {}", path.display());
+    println!("cd {}", workspace.path.display());
+    for (key, value) in &workspace.env {
+        println!("export {}='{}'", key, value.replace('\'', "'\\''"));
+    }
+    
+    Ok(())
+}
+
+/// 显示工作区的详细配置
+pub fn show_workspace(workspace_name: &str) -> Result<(), String> {
+    let (_, config) = load_config()?;
+    
+    let workspace = config
+        .get(workspace_name)
+        .ok_or(format!("工作区 '{}' 不存在", workspace_name))?;
+    
+    let or_dash = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+    println!("工作区: {}", workspace.name);
+    println!("  路径:     {}", workspace.path.display());
+    println!("  描述:     {}", or_dash(&workspace.description));
+    println!("  默认分支: {}", or_dash(&workspace.default_branch));
+    println!("  创建时间: {}", or_dash(&workspace.created_at));
+    println!("  最近使用: {}", or_dash(&workspace.last_used_at));
+    
+    if !workspace.groups.is_empty() {
+        println!("  仓库分组:");
+        for (name, repos) in &workspace.groups {
+            println!("    {} = {}", name, repos.join(", "));
+        }
+    }
+    if !workspace.remotes.is_empty() {
+        println!("  远程仓库:");
+        for (name, url) in &workspace.remotes {
+            println!("    {} = {}", name, url);
+        }
+    }
+    if !workspace.env.is_empty() {
+        println!("  环境变量:");
+        for (key, value) in &workspace.env {
+            println!("    {}={}", key, value);
+        }
+    }
+    
+    Ok(())
+}
+
+/// 修改工作区配置
+pub fn update_workspace(workspace_name: &str, update: &WorkspaceUpdate) -> Result<(), String> {
+    let (config_dir, mut config) = load_config()?;
+    
+    let workspace = config
+        .get_mut(workspace_name)
+        .ok_or(format!("工作区 '{}' 不存在", workspace_name))?;
+    
+    // 路径统一保存为绝对路径
+    let mut update = update.clone();
+    if let Some(path) = &update.path {
+        let path = fs::canonicalize(path)
+            .map_err(|_| format!("工作区路径不存在: {}", path.display()))?;
+        update.path = Some(path);
+    }
+    
+    workspace.apply(&update)?;
+    config.save(&config_dir)?;
     
+    println!("✅ 已更新工作区: {}", workspace_name);
     Ok(())
 }
@@ -0,0 +1,66 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use synapse_cli::db::batch::{batch_outputs, OutputKind};
+use synapse_cli::db::stream::Output;
+use synapse_cli::utils::expand_inputs;
+use synapse_cli::OutputArgs;
+
+use common::TempDir;
+
+fn args(output: Option<String>) -> OutputArgs {
+    OutputArgs { output, stdout: false, in_place: false, backup_suffix: None }
+}
+
+#[test]
+fn batch_outputs_mirror_inputs_and_reject_collisions() {
+    let dir = TempDir::new("batch");
+    for name in ["a/x.sql", "b/x.sql", "b/sub/y.sql"] {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "SELECT 1;\n").unwrap();
+    }
+    let out = dir.join("out").display().to_string();
+    let paths = |names: &[&str]| names.iter().map(|n| dir.join(n).display().to_string()).collect::<Vec<_>>();
+
+    // 未指定输出方式时原地修改，不生成备份
+    let files = expand_inputs(&paths(&["a/x.sql"]), &["sql".to_string()]).unwrap();
+    assert_eq!(batch_outputs(&files, &args(None), OutputKind::Rewrite).unwrap(), vec![Output::InPlace { backup: None }]);
+
+    let files = expand_inputs(&paths(&["b"]), &["sql".to_string()]).unwrap();
+    let outputs = batch_outputs(&files, &args(Some(out.clone())), OutputKind::Rewrite).unwrap();
+    assert_eq!(outputs, vec![Output::File(dir.join("out/sub/y.sql")), Output::File(dir.join("out/x.sql"))]);
+
+    // 不同目录下的同名文件
+    let files = expand_inputs(&paths(&["a/x.sql", "b/x.sql"]), &["sql".to_string()]).unwrap();
+    let err = batch_outputs(&files, &args(Some(out)), OutputKind::Rewrite).unwrap_err();
+    assert!(err.contains("会写入同一个输出文件"), "{}", err);
+}
+
+#[test]
+fn generated_outputs_use_derived_names() {
+    let dir = TempDir::new("batch-generated");
+    for name in ["a.sql", "sub/b.sql"] {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "SELECT 1;\n").unwrap();
+    }
+    let files = expand_inputs(&[dir.display().to_string()], &["sql".to_string()]).unwrap();
+    let rollback = OutputKind::File(Some(|path: &Path| path.with_extension("rollback.sql")));
+
+    // 默认写到输入文件旁，-o 时沿用生成的文件名
+    let outputs = batch_outputs(&files, &args(None), rollback).unwrap();
+    assert_eq!(outputs, vec![Output::File(dir.join("a.rollback.sql")), Output::File(dir.join("sub/b.rollback.sql"))]);
+    let out = dir.join("out").display().to_string();
+    let outputs = batch_outputs(&files, &args(Some(out)), rollback).unwrap();
+    assert_eq!(outputs, vec![Output::File(dir.join("out/a.rollback.sql")), Output::File(dir.join("out/sub/b.rollback.sql"))]);
+    assert_eq!(batch_outputs(&files, &args(None), OutputKind::File(None)).unwrap(), vec![Output::Stdout, Output::Stdout]);
+
+    // 生成新文件的命令不修改原文件
+    let in_place = OutputArgs { in_place: true, ..args(None) };
+    assert!(batch_outputs(&files, &in_place, rollback).is_err());
+    let stdout = OutputArgs { stdout: true, ..args(None) };
+    assert!(batch_outputs(&files, &stdout, OutputKind::Directory(|path: &Path| path.with_extension(""))).is_err());
+}
@@ -0,0 +1,46 @@
+use synapse_cli::db::check::{normalize_statement, Checker, FailureKind, Normalized};
+use synapse_cli::db::dialect::Dialect;
+
+#[test]
+fn check_normalizes_mysql_ddl() {
+    let ddl = "CREATE TABLE `t` (\n  `id` bigint(20) unsigned NOT NULL AUTO_INCREMENT COMMENT '主键',\n  `s` enum('a','b') DEFAULT 'a',\n  \
+               PRIMARY KEY (`id`) USING BTREE,\n  UNIQUE KEY `uk_s` (`s`),\n  KEY `idx_s` (`s`)\n) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;";
+    let Normalized::Sql(sql) = normalize_statement(ddl, Dialect::Mysql) else { panic!("should be executable") };
+    for gone in ["AUTO_INCREMENT", "unsigned", "COMMENT", "enum", "USING", "KEY `", "idx_s", "ENGINE", "CHARSET"] {
+        assert!(!sql.contains(gone), "{} should be removed: {}", gone, sql);
+    }
+    assert!(sql.contains("\"s\" TEXT DEFAULT 'a'") && sql.contains("UNIQUE   (\"s\")"));
+    assert_eq!(normalize_statement("/*!40014 SET FOREIGN_KEY_CHECKS=0 */;", Dialect::Mysql), Normalized::ForeignKeys(false));
+    assert!(matches!(normalize_statement("LOCK TABLES `t` WRITE;", Dialect::Mysql), Normalized::Skip(_)));
+
+    let mut checker = Checker::new(Dialect::Mysql).unwrap();
+    checker.run_sql("ddl.sql", ddl).unwrap();
+    checker
+        .run_sql("data.sql", "INSERT INTO t (id, s) VALUES (1, 'a');\nINSERT INTO t (id, s) VALUES (2, 'a');\nINSERT INTO x VALUES (1);\n")
+        .unwrap();
+    let report = checker.finish();
+    assert_eq!(report.executed, 2);
+    let failures: Vec<(usize, FailureKind)> = report.failures.iter().map(|f| (f.line, f.kind)).collect();
+    assert_eq!(failures, vec![(2, FailureKind::Constraint), (3, FailureKind::Error)]);
+}
+
+#[test]
+fn check_keeps_columns_named_like_attributes() {
+    let ddl = "CREATE TABLE t (id INT PRIMARY KEY, comment VARCHAR(100) NOT NULL COMMENT 'c', charset CHAR(8) CHARACTER SET utf8);";
+    let Normalized::Sql(sql) = normalize_statement(ddl, Dialect::Mysql) else { panic!("should be executable") };
+    assert_eq!(sql, "CREATE TABLE t (id INT PRIMARY KEY, comment VARCHAR(100) NOT NULL  , charset CHAR(8)   );");
+
+    let mut checker = Checker::new(Dialect::Mysql).unwrap();
+    checker.run_sql("ddl.sql", ddl).unwrap();
+    checker.run_sql("data.sql", "INSERT INTO t (id, comment, charset) VALUES (1, 'x', 'utf8');\n").unwrap();
+    let report = checker.finish();
+    assert_eq!((report.executed, report.failures.len()), (2, 0));
+}
+
+#[test]
+fn check_skips_unterminated_conditional_comments() {
+    for sql in ["/*!", "/*!4", "/*!40014 SET FOREIGN_KEY_CHECKS=0"] {
+        assert!(matches!(normalize_statement(sql, Dialect::Mysql), Normalized::Skip(_)), "{}", sql);
+    }
+    assert_eq!(normalize_statement("/*!*/", Dialect::Mysql), Normalized::Empty);
+}
@@ -0,0 +1,55 @@
+use synapse_cli::db::convert::{convert_sql, ConvertOptions, ConvertReport, MultiRowMode};
+use synapse_cli::db::dialect::Dialect;
+
+fn convert(sql: &str, from: Dialect, to: Dialect, multi_row: MultiRowMode) -> (String, ConvertReport) {
+    let options = ConvertOptions { from, to, multi_row, date_literals: true };
+    let mut report = ConvertReport::default();
+    let out = convert_sql(sql, 1, &options, &mut report);
+    (out, report)
+}
+
+#[test]
+fn convert_mysql_to_oracle() {
+    let sql = "INSERT INTO `t` (`a`, `b`, `c`) VALUES ('O\\'Brien', TRUE, '2024-01-02 03:04:05'), ('', FALSE, NOW());";
+    let (out, report) = convert(sql, Dialect::Mysql, Dialect::Oracle, MultiRowMode::Split);
+    assert_eq!(
+        out,
+        "INSERT INTO T (A, B, C) VALUES ('O''Brien', 1, TO_DATE('2024-01-02 03:04:05', 'YYYY-MM-DD HH24:MI:SS'));\n\
+         INSERT INTO T (A, B, C) VALUES ('', 0, SYSDATE);"
+    );
+    assert_eq!(report.warnings.len(), 1);
+
+    let (out, _) = convert(sql, Dialect::Mysql, Dialect::Oracle, MultiRowMode::All);
+    assert!(out.starts_with("INSERT ALL\n  INTO T (A, B, C) VALUES ('O''Brien'"));
+    assert!(out.ends_with("SELECT 1 FROM DUAL;"));
+}
+
+#[test]
+fn convert_quoted_identifiers_to_oracle_case() {
+    let sql = "INSERT INTO `user_info` (`Id`, `level`, `_flag`, `用户名`, `a b`) VALUES (1, 2, 3, 4, 5);";
+    let (out, _) = convert(sql, Dialect::Mysql, Dialect::Dm, MultiRowMode::Split);
+    assert_eq!(out, "INSERT INTO USER_INFO (ID, \"LEVEL\", \"_FLAG\", \"用户名\", \"a b\") VALUES (1, 2, 3, 4, 5);");
+
+    // 其他目标库保留原有大小写
+    let (out, _) = convert(sql, Dialect::Mysql, Dialect::Postgres, MultiRowMode::Split);
+    assert!(out.starts_with("INSERT INTO \"user_info\" (\"Id\", \"level\""));
+}
+
+#[test]
+fn convert_oracle_insert_all_to_mysql() {
+    let sql = "INSERT ALL\n  INTO t (a) VALUES (q'[it's \\]')\n  INTO t (a) VALUES (TO_DATE('2024-01-01', 'YYYY-MM-DD'))\nSELECT * FROM dual;";
+    let (out, report) = convert(sql, Dialect::Oracle, Dialect::Mysql, MultiRowMode::Split);
+    assert_eq!(out, "INSERT INTO t (a) VALUES ('it''s \\\\');\nINSERT INTO t (a) VALUES ('2024-01-01');");
+    assert!(report.warnings.is_empty());
+}
+
+#[test]
+fn convert_keeps_cjk_and_national_strings() {
+    let sql = "INSERT INTO t (a, b, c) VALUES ('中文', 1, N'名称'), ('é', 2, n'x');";
+    let (out, report) = convert(sql, Dialect::Mysql, Dialect::Oracle, MultiRowMode::Split);
+    assert_eq!(
+        out,
+        "INSERT INTO t (a, b, c) VALUES ('中文', 1, N'名称');\nINSERT INTO t (a, b, c) VALUES ('é', 2, N'x');"
+    );
+    assert!(report.warnings.is_empty());
+}
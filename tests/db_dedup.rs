@@ -0,0 +1,32 @@
+mod common;
+
+use std::fs;
+
+use synapse_cli::db::dedup::{dedup_file, KeepPolicy};
+use synapse_cli::db::dialect::Dialect;
+use synapse_cli::db::stream::Output;
+
+use common::TempDir;
+
+#[test]
+fn dedup_keep_policies_and_sort() {
+    let dir = TempDir::new("dedup");
+    let path = dir.join("seed.sql");
+    let sql = "-- users\nINSERT INTO t (id, v) VALUES (3, 'c');\nINSERT INTO t (id, v) VALUES (1, 'a');\n\
+               INSERT INTO t (id, v) VALUES ('1', 'a'), (3, 'C'), (2, 'b');\nCOMMIT;\n";
+    let keys = ["id".to_string()];
+
+    fs::write(&path, sql).unwrap();
+    let report = dedup_file(&path, &Output::default(), &keys, KeepPolicy::Fail, false, Dialect::Mysql).unwrap();
+    assert!(!report.applied);
+    assert_eq!(report.conflicts(), 1);
+    assert_eq!(report.duplicates[0].lines, vec![2, 4]);
+    assert_eq!(fs::read_to_string(&path).unwrap(), sql);
+
+    let report = dedup_file(&path, &Output::default(), &keys, KeepPolicy::Last, true, Dialect::Mysql).unwrap();
+    assert_eq!(report.removed, 2);
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "-- users\nINSERT INTO t (id, v) VALUES ('1', 'a'), (2, 'b'), (3, 'C');\nCOMMIT;\n"
+    );
+}
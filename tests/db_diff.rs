@@ -0,0 +1,25 @@
+mod common;
+
+use std::fs;
+
+use synapse_cli::db::dialect::Dialect;
+use synapse_cli::db::diff::diff_files;
+
+use common::TempDir;
+
+#[test]
+fn diff_reports_rows_and_script() {
+    let dir = TempDir::new("diff");
+    let (old, new) = (dir.join("old.sql"), dir.join("new.sql"));
+    fs::write(&old, "INSERT INTO t (id, name, age) VALUES (1, 'a', 20), (2, 'b', 30), (3, 'c', 1);\n").unwrap();
+    fs::write(&new, "INSERT INTO t (id, age, name, note) VALUES ('1', 20, 'a', NULL), (2, 31, 'b', NULL);\nINSERT INTO t (id, name) VALUES (4, 'd');\n").unwrap();
+
+    let diff = diff_files(&old, &new, &["id".to_string()], Dialect::Mysql).unwrap();
+    assert_eq!(diff.counts(), (1, 1, 1));
+    let modified = &diff.tables[0].modified[0];
+    assert_eq!((modified.changes.len(), modified.changes[0].column.as_str()), (1, "age"));
+    assert_eq!(
+        diff.script("t"),
+        "-- t\nDELETE FROM t WHERE id = 3;\nUPDATE t SET age = 31 WHERE id = 2;\nINSERT INTO t (id, name) VALUES (4, 'd');\nCOMMIT;\n"
+    );
+}
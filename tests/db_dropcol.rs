@@ -0,0 +1,28 @@
+use synapse_cli::db::dialect::Dialect;
+use synapse_cli::db::dropcol::drop_columns_sql;
+
+#[test]
+fn dropcol_uses_column_positions_per_statement() {
+    let sql = "INSERT INTO a (name, `ID`, create_time) VALUES ('x', 1, NOW()), ('y', 2, NOW());\n\
+               INSERT INTO b (code, label) VALUES ('c', 'd');\n\
+               INSERT INTO c VALUES (1, 2);\n";
+    let columns = vec!["id".to_string(), "create_time".to_string()];
+    let (out, report) = drop_columns_sql(sql, &columns, Dialect::Mysql);
+    assert_eq!(
+        out,
+        "INSERT INTO a (name) VALUES ('x'), ('y');\n\
+         INSERT INTO b (code, label) VALUES ('c', 'd');\n\
+         INSERT INTO c VALUES (1, 2);\n"
+    );
+    assert_eq!(report.tables.len(), 1);
+    assert_eq!((report.tables[0].table.as_str(), report.tables[0].statements, report.tables[0].rows), ("a", 1, 2));
+}
+
+#[test]
+fn dropcol_leaves_misaligned_statements_unchanged() {
+    let sql = "INSERT INTO t (id, name) VALUES (1, 'a'), (2);\nINSERT INTO t (id, name) VALUES (3, 'c');\n";
+    let (out, report) = drop_columns_sql(sql, &["id".to_string()], Dialect::Mysql);
+    assert_eq!(out, "INSERT INTO t (id, name) VALUES (1, 'a'), (2);\nINSERT INTO t (name) VALUES ('c');\n");
+    assert_eq!(report.warnings, [(1, "t: 第 2 个元组有 1 个值，与 2 列不一致，语句保持原样".to_string())]);
+    assert_eq!((report.tables[0].statements, report.tables[0].rows), (1, 1));
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use std::fs;
+
+use synapse_cli::db::dialect::Dialect;
+use synapse_cli::db::encoding::{recode_file, EncodedWriter, FileEncoding};
+use synapse_cli::db::rename::{rename_file, Renames};
+use synapse_cli::db::stream::Output;
+
+use common::TempDir;
+
+#[test]
+fn legacy_encodings_round_trip() {
+    // 0x80 是 GBK 中的欧元符号；0x95 0x32 0x82 0x36 是 GB18030 四字节的 U+20000
+    let gbk = b"INSERT INTO t_user VALUES (1, '\xd5\xc5\xc8\xfd', '\x80');\n";
+    let gb18030 = b"INSERT INTO t VALUES ('\x95\x32\x82\x36');\n";
+    assert_eq!(FileEncoding::detect_bytes(gbk).name(), "GBK");
+    assert_eq!(FileEncoding::detect_bytes(gb18030).name(), "gb18030");
+    assert_eq!(FileEncoding::detect_bytes("张三".as_bytes()), FileEncoding::UTF8);
+    assert_eq!(FileEncoding::detect_bytes(b"\xef\xbb\xbfSELECT 1;").name(), "UTF-8 (BOM)");
+    assert_eq!(FileEncoding::parse("utf-16le").unwrap().name(), "UTF-16LE");
+    assert!(FileEncoding::parse("gbk-bom").is_err());
+
+    let enc = FileEncoding::parse("gbk").unwrap();
+    assert_eq!(enc.decode(gbk).unwrap(), "INSERT INTO t_user VALUES (1, '张三', '€');\n");
+    assert!(enc.encode("𠀀").unwrap_err().contains("U+20000"));
+    assert!(enc.decode(b"\x81\x7f").unwrap_err().contains("第 1 字节"));
+    // 多字节字符被拆在两次写入之间
+    let mut writer = EncodedWriter::new(Vec::new(), enc);
+    let bytes = "张三".as_bytes();
+    std::io::Write::write_all(&mut writer, &bytes[..2]).unwrap();
+    std::io::Write::write_all(&mut writer, &bytes[2..]).unwrap();
+    assert_eq!(enc.decode(&writer.into_inner()).unwrap(), "张三");
+
+    let dir = TempDir::new("encoding");
+    let path = dir.join("legacy.sql");
+    fs::write(&path, gbk).unwrap();
+    let renames = Renames { tables: vec![("t_user".to_string(), "用户".to_string())], schemas: Vec::new() };
+    rename_file(&path, &Output::default(), &renames, Dialect::Mysql).unwrap();
+    assert_eq!(enc.decode(&fs::read(&path).unwrap()).unwrap(), "INSERT INTO 用户 VALUES (1, '张三', '€');\n");
+
+    let utf8_bom = FileEncoding::parse("utf-8-bom").unwrap();
+    let copy = dir.join("utf8.sql");
+    assert_eq!(recode_file(&path, &Output::File(copy.clone()), utf8_bom).unwrap(), Some(enc));
+    assert_eq!(fs::read(&copy).unwrap(), "\u{feff}INSERT INTO 用户 VALUES (1, '张三', '€');\n".as_bytes());
+    assert_eq!(enc.decode(&fs::read(&path).unwrap()).unwrap(), "INSERT INTO 用户 VALUES (1, '张三', '€');\n");
+
+    let backup = Output::InPlace { backup: Some(".bak".to_string()) };
+    assert_eq!(recode_file(&path, &backup, utf8_bom).unwrap(), Some(enc));
+    assert_eq!(fs::read(&path).unwrap(), fs::read(&copy).unwrap());
+    let backup = fs::read(dir.join("legacy.sql.bak")).unwrap();
+    assert_eq!(enc.decode(&backup).unwrap(), "INSERT INTO 用户 VALUES (1, '张三', '€');\n");
+    let in_place = Output::InPlace { backup: Some(".orig".to_string()) };
+    assert_eq!(recode_file(&path, &in_place, utf8_bom).unwrap(), None);
+    assert!(!dir.join("legacy.sql.orig").exists());
+    assert!(recode_file(&path, &Output::File(path.clone()), enc).unwrap_err().contains("不能与输入文件相同"));
+}
@@ -0,0 +1,33 @@
+mod common;
+
+use std::fs;
+
+use synapse_cli::db::dialect::Dialect;
+use synapse_cli::db::export::{export_file, DataFormat};
+use synapse_cli::db::import::{import_file, ImportOptions};
+use synapse_cli::db::stream::Output;
+
+use common::{fixture, TempDir};
+
+#[test]
+fn export_import_round_trip() {
+    let dir = TempDir::new("export");
+    let sql_path = dir.join("dump.sql");
+    let mut sql = fixture("mysqldump_sample.sql");
+    sql.push_str("INSERT INTO t (a, b, c, d) VALUES (1.50, '007', NOW(), '\\\\N'), (-3, 'NULL', x'AF', 'a,\"b\"\\nc');\n");
+    fs::write(&sql_path, sql).unwrap();
+
+    for format in [DataFormat::Csv, DataFormat::Json] {
+        let first = export_file(&sql_path, &dir.join("first"), format, Dialect::Mysql).unwrap();
+        assert_eq!(first.tables.iter().map(|t| t.rows).collect::<Vec<_>>(), vec![3, 2, 2]);
+
+        for table in &first.tables {
+            let imported = dir.join(format!("{}.sql", table.table));
+            let options = ImportOptions { table: table.table.clone(), types: Vec::new(), dialect: Dialect::Mysql };
+            assert_eq!(import_file(&table.path, &Output::File(imported.clone()), &options).unwrap(), table.rows);
+
+            let second = export_file(&imported, &dir.join("second"), format, Dialect::Mysql).unwrap();
+            assert_eq!(fs::read(&second.tables[0].path).unwrap(), fs::read(&table.path).unwrap());
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use synapse_cli::db::dialect::Dialect;
+use synapse_cli::db::fmt::{format_sql, FmtOptions, KeywordCase};
+
+#[test]
+fn fmt_normalises_case_layout_and_semicolons() {
+    let sql = "set names utf8mb4;  -- session\n\n\n\
+               create table `t` (`id` int not null, `name` varchar(20) default 'a  b', primary key (`id`)) engine=InnoDB;\n\
+               insert into t(id,name) values (1,'张三'),(22,'it''s  ok');\n\
+               update t set name = 'x'  where t.id in (1,2)";
+    let options = FmtOptions::default();
+    let formatted = format_sql(sql, &options, Dialect::Mysql);
+    assert_eq!(
+        formatted,
+        "SET names utf8mb4; -- session\n\n\
+         CREATE TABLE `t` (\n  `id`   INT         NOT NULL,\n  `name` VARCHAR(20) DEFAULT 'a  b',\n  PRIMARY KEY (`id`)\n) ENGINE=InnoDB;\n\
+         INSERT INTO t (id, name) VALUES\n  (1,  '张三'),\n  (22, 'it''s  ok');\n\
+         UPDATE t SET name = 'x' WHERE t.id IN (1, 2);\n"
+    );
+    assert_eq!(format_sql(&formatted, &options, Dialect::Mysql), formatted);
+
+    let lower = FmtOptions { keyword_case: KeywordCase::Lower, indent: 4 };
+    let sql = "INSERT ALL INTO t (id) VALUES (1) INTO t (id) VALUES (2) SELECT 1 FROM DUAL;\nBEGIN NULL; END;\n/\n";
+    assert_eq!(
+        format_sql(sql, &lower, Dialect::Oracle),
+        "insert all\n    into t (id) values (1)\n    into t (id) values (2)\nselect 1 from DUAL;\nBEGIN NULL; END;\n/\n"
+    );
+}
@@ -0,0 +1,39 @@
+use synapse_cli::db::dialect::Dialect;
+use synapse_cli::db::infer::infer_schema_sql;
+
+#[test]
+fn infer_schema_types_nullability_and_keys() {
+    let sql = "INSERT INTO t_user (id, code, name, amount, birthday, created_at, remark, active) VALUES \
+               (1, 'U001', '张三', 12.50, '1990-01-02', '2024-01-01 10:00:00', NULL, TRUE),\
+               (2, 'U002', 'Li Si', 3, '1991-03-04', NOW(), NULL, FALSE);\n\
+               INSERT INTO t_user (id, code) VALUES (12345678901, 'U003');\n\
+               INSERT INTO t_role_user (role_id, user_id) VALUES (1, 1), (1, 2), (2, 1);\n\
+               INSERT INTO t_log VALUES (1);\n";
+    let schema = infer_schema_sql(sql, Dialect::Mysql);
+    assert_eq!(schema.warnings.len(), 1);
+    assert_eq!(schema.tables.len(), 2);
+
+    let user = &schema.tables[0];
+    let types: Vec<(&str, &str, bool)> = user.columns.iter().map(|c| (c.name.as_str(), c.data_type.as_str(), c.nullable)).collect();
+    assert_eq!(
+        types,
+        [
+            ("id", "BIGINT", false),
+            ("code", "CHAR(4)", false),
+            ("name", "VARCHAR(16)", true),
+            ("amount", "DECIMAL(4, 2)", true),
+            ("birthday", "DATE", true),
+            ("created_at", "DATETIME", true),
+            ("remark", "VARCHAR(255)", true),
+            ("active", "TINYINT(1)", true),
+        ]
+    );
+    assert_eq!(user.candidate_keys, [vec!["id".to_string()], vec!["code".to_string()]]);
+    assert_eq!(user.primary_key, Some(vec!["id".to_string()]));
+    assert_eq!(user.notes, ["列 remark 只有 NULL，类型待定"]);
+    assert_eq!(schema.tables[1].primary_key, Some(vec!["role_id".to_string(), "user_id".to_string()]));
+
+    let oracle = infer_schema_sql("INSERT INTO T (ID, NAME) VALUES (1, ''), (2, '张三丰');", Dialect::Oracle);
+    let render = oracle.render("test", Dialect::Oracle);
+    assert!(render.contains("ID   NUMBER(10) NOT NULL,\n  NAME VARCHAR2(16),\n  PRIMARY KEY (ID)"), "{}", render);
+}
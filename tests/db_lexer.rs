@@ -0,0 +1,64 @@
+mod common;
+
+use synapse_cli::db::dialect::Dialect;
+use synapse_cli::db::dropcol::drop_columns_sql;
+use synapse_cli::db::lexer::{tokenize, tokenize_with, TokenKind};
+
+use common::fixture;
+
+#[test]
+fn tokenize_is_lossless_on_dump() {
+    let sql = fixture("mysqldump_sample.sql");
+    let joined: String = tokenize(&sql).iter().map(|t| t.text).collect();
+    assert_eq!(joined, sql);
+}
+
+#[test]
+fn tokenize_recognizes_literals_and_comments() {
+    let sql = "INSERT INTO t (inserted_at) VALUES ('It''s', 'a\\'b', \"x\", `c`, $f$ ; $f$) -- tail\n/* block */ # hash";
+    let kinds: Vec<(TokenKind, &str)> = tokenize(sql)
+        .into_iter()
+        .filter(|t| t.kind != TokenKind::Whitespace && t.kind != TokenKind::Punct)
+        .map(|t| (t.kind, t.text))
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            (TokenKind::Word, "INSERT"),
+            (TokenKind::Word, "INTO"),
+            (TokenKind::Word, "t"),
+            (TokenKind::Word, "inserted_at"),
+            (TokenKind::Word, "VALUES"),
+            (TokenKind::String, "'It''s'"),
+            (TokenKind::String, "'a\\'b'"),
+            (TokenKind::QuotedIdent, "\"x\""),
+            (TokenKind::QuotedIdent, "`c`"),
+            (TokenKind::String, "$f$ ; $f$"),
+            (TokenKind::LineComment, "-- tail"),
+            (TokenKind::BlockComment, "/* block */"),
+            (TokenKind::LineComment, "# hash"),
+        ]
+    );
+}
+
+#[test]
+fn tokenize_tracks_lines() {
+    let tokens = tokenize("a\n'x\ny'\nb");
+    let b = tokens.iter().find(|t| t.text == "b").unwrap();
+    assert_eq!(b.line, 4);
+}
+
+#[test]
+fn oracle_q_quotes_and_insert_all() {
+    let sql = fixture("oracle_sample.sql");
+    let config = Dialect::Oracle.lexer_config();
+    let tokens = tokenize_with(&sql, &config);
+    let q = tokens.iter().find(|t| t.text.starts_with("q'")).unwrap();
+    assert_eq!(q.text, "q'[It's; a 'quoted' value]'");
+    assert_eq!(Dialect::Oracle.unquote_string(q).unwrap(), "It's; a 'quoted' value");
+
+    let (out, report) = drop_columns_sql(&sql, &["ID".to_string()], Dialect::Oracle);
+    assert!(out.contains("INTO T_DICT (CODE, LABEL) VALUES ('a', '甲')"));
+    assert!(out.contains("INTO T_DICT (CODE, LABEL) VALUES ('b', q'[It's; a 'quoted' value]')"));
+    assert_eq!((report.tables[0].statements, report.tables[0].rows), (3, 3));
+}
@@ -0,0 +1,38 @@
+use synapse_cli::db::dialect::Dialect;
+use synapse_cli::db::lint::{lint_sql, LintConfig, Severity};
+
+#[test]
+fn lint_rules_and_severity() {
+    let sql = "INSERT INTO t (id, v) VALUES (1, 'a'), (2);\nINSERT INTO t (id, v) VALUES ('1', 'b');\nDELETE FROM t;\n\
+               UPDATE t SET v = (SELECT 1 FROM x WHERE y = 1);\nDELETE FROM t WHERE id = 3;\n";
+    let mut config = LintConfig::default();
+    let rules: Vec<(&str, usize)> = lint_sql(sql, &config, Dialect::Mysql).iter().map(|i| (i.rule, i.line)).collect();
+    assert_eq!(
+        rules,
+        vec![
+            ("column-count", 1),
+            ("duplicate-key", 2),
+            ("delete-without-where", 3),
+            ("update-without-where", 4),
+            ("missing-commit", 5),
+        ]
+    );
+
+    config.set("missing-commit", Severity::Off).unwrap();
+    config.apply("duplicate-key=warning").unwrap();
+    assert!(config.set("no-such-rule", Severity::Off).is_err());
+    let issues = lint_sql(&format!("{}COMMIT;\n", sql), &config, Dialect::Mysql);
+    assert_eq!(issues.len(), 4);
+    assert_eq!(issues[1].severity, Severity::Warning);
+}
+
+#[test]
+fn lint_only_counts_commit_statements() {
+    let config = LintConfig::default();
+    let missing = |sql: &str| lint_sql(sql, &config, Dialect::Mysql).iter().filter(|i| i.rule == "missing-commit").count();
+    assert_eq!(missing("INSERT INTO t (id, commit) VALUES (1, 2);\n"), 1);
+    assert_eq!(missing("INSERT INTO t (id) VALUES (1);\nUPDATE t SET commit = 1 WHERE id = 1;\n"), 1);
+    assert_eq!(missing("INSERT INTO t (id) VALUES (1);\nCOMMIT;\n"), 0);
+    assert_eq!(missing("INSERT INTO t (id) VALUES (1);\ncommit work;\n"), 0);
+    assert_eq!(missing("BEGIN\n  INSERT INTO t (id) VALUES (1);\n  COMMIT;\nEND;\n"), 0);
+}
@@ -0,0 +1,48 @@
+use synapse_cli::db::dialect::Dialect;
+use synapse_cli::db::mask::{mask_sql, mask_value, MaskReport, MaskRule, MaskStrategy};
+
+#[test]
+fn mask_is_deterministic_and_valid() {
+    let rules: Vec<MaskRule> = ["id_card=idcard", "phone=phone", "t_user.name=partial:1:0", "remark=null"]
+        .iter()
+        .map(|r| MaskRule::parse(r).unwrap())
+        .collect();
+    let sql = "INSERT INTO t_user (id, name, id_card, phone, remark) VALUES (1, '张三丰', '110101199003071234', 13812345678, 'vip');\n\
+               INSERT INTO t_order (id, phone, name) VALUES (1, '13812345678', '订单');";
+    let mut report = MaskReport::default();
+    let out = mask_sql(sql, 1, &rules, "seed", Dialect::Mysql, &mut report);
+    assert_eq!(report.masked, vec![1, 2, 1, 1]);
+    assert!(out.contains("'张**'") && out.contains("NULL);") && out.contains("'订单'"));
+
+    let phone = mask_value(MaskStrategy::Phone, "13812345678", "seed").unwrap();
+    assert!(out.contains(&format!(", {}, NULL)", phone)) && out.contains(&format!("'{}'", phone)));
+    assert_ne!(mask_value(MaskStrategy::Phone, "13812345678", "other"), Some(phone));
+
+    let id = mask_value(MaskStrategy::IdCard, "110101199003071234", "seed").unwrap();
+    let weights = [7, 9, 10, 5, 8, 4, 2, 1, 6, 3, 7, 9, 10, 5, 8, 4, 2];
+    let sum: u32 = id.chars().zip(weights).map(|(c, w)| c.to_digit(10).unwrap() * w).sum();
+    assert_eq!(id.len(), 18);
+    assert_eq!(id.chars().last(), "10X98765432".chars().nth((sum % 11) as usize));
+    assert!(MaskStrategy::parse("hash:100").is_err());
+}
+
+#[test]
+fn mask_skips_short_rows_and_keeps_sign() {
+    let rules = vec![MaskRule::parse("amount=digits").unwrap()];
+    let sql = "INSERT INTO t (id, amount) VALUES (1, -123), (2);";
+    let mut report = MaskReport::default();
+    let out = mask_sql(sql, 5, &rules, "seed", Dialect::Mysql, &mut report);
+    assert_eq!(report.masked, vec![1]);
+    assert_eq!(report.warnings, vec![(5, "t: 值个数与列数不一致，跳过该行".to_string())]);
+    assert!(out.ends_with(", (2);"));
+
+    // 负数的第一个数字同样不为 0
+    for value in ["-123", "-0.5", "0"] {
+        for seed in ["a", "b", "c", "d", "e", "f", "g", "h"] {
+            let masked = mask_value(MaskStrategy::Digits, value, seed).unwrap();
+            let first = masked.chars().find(|c| c.is_ascii_digit()).unwrap();
+            assert_ne!(first, '0', "{} -> {}", value, masked);
+            assert_eq!(masked.len(), value.len());
+        }
+    }
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use std::fs;
+
+use synapse_cli::db::dialect::Dialect;
+use synapse_cli::db::rebatch::{parse_size, rebatch_file, RebatchOptions};
+use synapse_cli::db::stream::Output;
+
+use common::TempDir;
+
+#[test]
+fn rebatch_rows_bytes_and_commits() {
+    assert_eq!((parse_size("1M"), parse_size("64kb"), parse_size("500")), (Ok(1 << 20), Ok(64 << 10), Ok(500)));
+    assert!(parse_size("1X").is_err());
+    assert!(parse_size("99999999999999999G").is_err());
+    assert!(parse_size(&format!("{}K", usize::MAX >> 9)).is_err());
+
+    let dir = TempDir::new("rebatch");
+    let path = dir.join("seed.sql");
+    let seed = "INSERT INTO t (id) VALUES (1), (2), (3);\nINSERT INTO t (id) VALUES (4);\n-- u\nINSERT INTO u VALUES (1);\n";
+    fs::write(&path, seed).unwrap();
+
+    let options = RebatchOptions { rows: Some(2), max_bytes: None, commit_every: Some(2) };
+    let report = rebatch_file(&path, &Output::default(), &options, Dialect::Mysql).unwrap();
+    assert_eq!((report.read, report.written, report.rows, report.commits), (3, 3, 5, 2));
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "INSERT INTO t (id) VALUES\n  (1),\n  (2);\nINSERT INTO t (id) VALUES\n  (3),\n  (4);\nCOMMIT;\n-- u\nINSERT INTO u VALUES (1);\nCOMMIT;\n"
+    );
+
+    fs::write(&path, seed).unwrap();
+    let options = RebatchOptions { rows: None, max_bytes: Some(90), commit_every: None };
+    let report = rebatch_file(&path, &Output::default(), &options, Dialect::Oracle).unwrap();
+    assert_eq!((report.written, report.oversized), (3, 0));
+    let text = fs::read_to_string(&path).unwrap();
+    assert!(text.starts_with("INSERT ALL\n  INTO t (id) VALUES (1)\n  INTO t (id) VALUES (2)\nSELECT 1 FROM DUAL;\nINSERT ALL\n"));
+}
@@ -0,0 +1,67 @@
+use synapse_cli::db::dialect::Dialect;
+use synapse_cli::db::remap::{plan_remap_sql, remap_sql, ForeignKey, RemapOptions, RemapReport};
+
+#[test]
+fn remap_ids_and_foreign_keys() {
+    let sql = "INSERT INTO t_user (id, name, parent_id) VALUES (7, 'a', NULL), (3, 'b', 7), ('9', 'c', 3);\n\
+               INSERT INTO t_order (id, user_id) VALUES (1, 3), (2, '9'), (3, 42);\n";
+    let options = RemapOptions {
+        tables: vec!["t_user".to_string()],
+        key: "id".to_string(),
+        start: 10000,
+        foreign_keys: vec![
+            ForeignKey::parse("t_order.user_id=t_user.id").unwrap(),
+            ForeignKey::parse("app.t_user.parent_id = t_user.id").unwrap(),
+        ],
+    };
+    let map = plan_remap_sql(sql, &options, Dialect::Mysql).unwrap();
+    assert_eq!(map.tables[0].range(), Some((10000, 10002)));
+
+    let mut report = RemapReport::default();
+    assert_eq!(
+        remap_sql(sql, 1, &map, Dialect::Mysql, &mut report),
+        "INSERT INTO t_user (id, name, parent_id) VALUES (10000, 'a', NULL), (10001, 'b', 10000), ('10002', 'c', 10001);\n\
+         INSERT INTO t_order (id, user_id) VALUES (1, 10001), (2, '10002'), (3, 42);\n"
+    );
+    assert_eq!((report.keys, report.references), (3, 4));
+    assert_eq!(report.warnings, [(2, "t_order.user_id: 值 42 在 t_user 中不存在，保持原值".to_string())]);
+
+    // 起始值接近 i64::MAX 时，最后一个值可以用到 i64::MAX，再多一行就报错
+    let two_rows = "INSERT INTO t_user (id) VALUES (1), (2);";
+    let near_max = RemapOptions { start: i64::MAX - 1, foreign_keys: Vec::new(), ..options.clone() };
+    assert_eq!(plan_remap_sql(two_rows, &near_max, Dialect::Mysql).unwrap().tables[0].range(), Some((i64::MAX - 1, i64::MAX)));
+    let at_max = RemapOptions { start: i64::MAX, ..near_max };
+    assert!(plan_remap_sql(two_rows, &at_max, Dialect::Mysql).unwrap_err().contains("超出 i64 范围"));
+
+    assert!(ForeignKey::parse("t_order.user_id").is_err());
+    let options = RemapOptions { foreign_keys: vec![ForeignKey::parse("t_order.user_id=t_role.id").unwrap()], ..options };
+    assert!(plan_remap_sql(sql, &options, Dialect::Mysql).unwrap_err().contains("t_role"));
+}
+
+#[test]
+fn remap_skips_rows_with_wrong_value_count() {
+    let sql = "INSERT INTO t_user (id, name) VALUES (5, 'a'), (6), (7, 'c');\n\
+               INSERT INTO t_order (id, user_id) VALUES (1, 7, 'x'), (2, 5);\n";
+    let options = RemapOptions {
+        tables: vec!["t_user".to_string()],
+        key: "id".to_string(),
+        start: 1,
+        foreign_keys: vec![ForeignKey::parse("t_order.user_id=t_user.id").unwrap()],
+    };
+    let map = plan_remap_sql(sql, &options, Dialect::Mysql).unwrap();
+    assert_eq!(map.tables[0].range(), Some((1, 2)));
+
+    let mut report = RemapReport::default();
+    assert_eq!(
+        remap_sql(sql, 1, &map, Dialect::Mysql, &mut report),
+        "INSERT INTO t_user (id, name) VALUES (1, 'a'), (6), (2, 'c');\n\
+         INSERT INTO t_order (id, user_id) VALUES (1, 7, 'x'), (2, 1);\n"
+    );
+    assert_eq!(
+        report.warnings,
+        [
+            (1, "t_user: 值个数与列数不一致，跳过该行".to_string()),
+            (2, "t_order: 值个数与列数不一致，跳过该行".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,22 @@
+use synapse_cli::db::dialect::Dialect;
+use synapse_cli::db::rename::{rename_sql, RenameReport, Renames};
+
+#[test]
+fn rename_tables_and_schema() {
+    let renames = Renames {
+        tables: vec![("t_user".to_string(), "sys_user".to_string())],
+        schemas: vec![("dev".to_string(), "uat".to_string())],
+    };
+    let mut report = RenameReport::default();
+    let sql = "-- t_user\nUPDATE `dev`.`t_user` u SET name = 't_user' WHERE t_user.id = 1;\n\
+               SELECT a.t_user_id FROM t_order a, t_user b JOIN dev.t_dict d ON d.id = b.id;\n\
+               INSERT INTO t (t_user) VALUES (1) ON DUPLICATE KEY UPDATE t_user = 2;";
+    let out = rename_sql(sql, &renames, Dialect::Mysql, &mut report);
+    assert_eq!(
+        out,
+        "-- t_user\nUPDATE `uat`.`sys_user` u SET name = 't_user' WHERE sys_user.id = 1;\n\
+         SELECT a.t_user_id FROM t_order a, sys_user b JOIN uat.t_dict d ON d.id = b.id;\n\
+         INSERT INTO t (t_user) VALUES (1) ON DUPLICATE KEY UPDATE t_user = 2;"
+    );
+    assert_eq!((report.tables, report.schemas), (vec![3], vec![2]));
+}
@@ -0,0 +1,19 @@
+mod common;
+
+use synapse_cli::db::dialect::Dialect;
+use synapse_cli::db::rmid::rmid_sql;
+
+use common::fixture;
+
+#[test]
+fn rmid_handles_real_dump() {
+    let (out, count) = rmid_sql(&fixture("mysqldump_sample.sql"), Dialect::Mysql);
+    assert_eq!(out, fixture("mysqldump_sample.rmid.sql"));
+    assert_eq!(count, 8);
+}
+
+#[test]
+fn rmid_ignores_columns_that_contain_insert() {
+    let sql = "UPDATE t SET inserted_at = NOW() WHERE values_count = 1;";
+    assert_eq!(rmid_sql(sql, Dialect::Mysql), (sql.to_string(), 0));
+}
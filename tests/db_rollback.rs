@@ -0,0 +1,20 @@
+use synapse_cli::db::dialect::Dialect;
+use synapse_cli::db::rollback::{rollback_sql, RollbackReport};
+
+#[test]
+fn rollback_composite_key() {
+    let keys = ["tenant_id".to_string(), "code".to_string()];
+    let mut report = RollbackReport::default();
+    let sql = "INSERT INTO `t` (`tenant_id`, `code`, `v`) VALUES (1, 'a', 'x'), (NULL, 'b', 'y');\nINSERT INTO t (v) VALUES (1);";
+    let deletes = rollback_sql(sql, 1, &keys, Dialect::Mysql, &mut report);
+    assert_eq!(
+        deletes,
+        vec![
+            "DELETE FROM `t` WHERE `tenant_id` = 1 AND `code` = 'a';",
+            "DELETE FROM `t` WHERE `tenant_id` IS NULL AND `code` = 'b';",
+        ]
+    );
+    assert_eq!(report.deletes, 2);
+    assert_eq!(report.warnings.len(), 1);
+    assert_eq!(report.warnings[0].0, 2);
+}
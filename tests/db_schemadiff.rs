@@ -0,0 +1,38 @@
+use synapse_cli::db::ddl::parse_schema;
+use synapse_cli::db::dialect::Dialect;
+use synapse_cli::db::schemadiff::schema_diff;
+
+#[test]
+fn schemadiff_generates_alters() {
+    let old = parse_schema(
+        "CREATE TABLE t (id NUMBER(19) NOT NULL, name VARCHAR2(64), note VARCHAR2(10), CONSTRAINT pk_t PRIMARY KEY (id));\n\
+         CREATE INDEX idx_name ON t (name);\nCREATE TABLE gone (id INT);",
+        Dialect::Oracle,
+    );
+    let new = parse_schema(
+        "CREATE TABLE t (id NUMBER(19) NOT NULL, name VARCHAR2(32) DEFAULT 'x' NOT NULL, mail VARCHAR2(100), CONSTRAINT pk_t PRIMARY KEY (id));\n\
+         COMMENT ON COLUMN t.mail IS '邮箱';\nCREATE UNIQUE INDEX uk_mail ON t (mail);",
+        Dialect::Oracle,
+    );
+    assert!(old.warnings.is_empty() && new.warnings.is_empty());
+    assert_eq!(new.table("T").unwrap().column("MAIL").unwrap().comment.as_deref(), Some("邮箱"));
+
+    let migration = schema_diff(&old, &new, Dialect::Oracle);
+    let sqls: Vec<&str> = migration.steps.iter().map(|s| s.sql.as_str()).collect();
+    assert_eq!(
+        sqls,
+        vec![
+            "DROP INDEX idx_name;",
+            "ALTER TABLE t MODIFY (name VARCHAR2(32) DEFAULT 'x' NOT NULL);",
+            "ALTER TABLE t ADD (mail VARCHAR2(100));",
+            "COMMENT ON COLUMN t.mail IS '邮箱';",
+            "CREATE UNIQUE INDEX uk_mail ON t (mail);",
+            "ALTER TABLE t DROP COLUMN note;",
+            "DROP TABLE gone;",
+        ]
+    );
+    assert_eq!(migration.destructive().count(), 3);
+    let script = migration.render("m", false);
+    assert!(script.contains("-- DROP TABLE gone;") && !script.contains("\nDROP TABLE"));
+    assert!(migration.render("m", true).contains("\nDROP TABLE gone;"));
+}
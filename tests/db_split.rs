@@ -0,0 +1,44 @@
+mod common;
+
+use std::fs;
+
+use synapse_cli::db::dialect::Dialect;
+use synapse_cli::db::split::{extract_file, merge_files, split_file, ExtractOptions};
+
+use common::TempDir;
+
+#[test]
+fn split_extract_and_merge_by_table() {
+    let dir = TempDir::new("split");
+    let dump = dir.join("dump.sql");
+    fs::write(
+        &dump,
+        "SET NAMES utf8mb4;\nCREATE TABLE emp (id INT, dept_id INT REFERENCES dept (id), status CHAR(1));\n\
+         INSERT INTO emp VALUES (1, 1, 'A'), (2, 1, 'I');\nCOMMIT;\nCREATE TABLE dept (id INT);\n\
+         INSERT INTO dept (id) VALUES (1);\nSET FOREIGN_KEY_CHECKS=1;\n",
+    )
+    .unwrap();
+
+    let report = split_file(&dump, &dir.join("out"), Dialect::Mysql).unwrap();
+    let tables: Vec<(&str, usize)> = report.files.iter().map(|(t, _, n)| (t.as_str(), *n)).collect();
+    assert_eq!((tables, report.shared, report.others), (vec![("emp", 2), ("dept", 2)], 2, 0));
+    assert_eq!(
+        fs::read_to_string(dir.join("out/dept.sql")).unwrap(),
+        "SET NAMES utf8mb4;\nCREATE TABLE dept (id INT);\nINSERT INTO dept (id) VALUES (1);\nSET FOREIGN_KEY_CHECKS=1;\n"
+    );
+
+    let options = ExtractOptions { tables: vec!["EMP".to_string()], filter: Some("status = 'A'".to_string()) };
+    let mut out = Vec::new();
+    let extracted = extract_file(&dump, &options, Dialect::Mysql, &mut out).unwrap();
+    assert_eq!((extracted.statements, extracted.rows, extracted.removed), (2, 1, 1));
+    let text = String::from_utf8(out).unwrap();
+    assert!(text.contains("VALUES (1, 1, 'A');\nCOMMIT;") && !text.contains("INSERT INTO dept"));
+
+    let files = vec![dir.join("out/emp.sql"), dir.join("out/dept.sql")];
+    let mut out = Vec::new();
+    let merged = merge_files(&files, Dialect::Mysql, &mut out).unwrap();
+    assert_eq!((merged.order[0].clone(), merged.statements, merged.duplicates), (files[1].clone(), 5, 2));
+    let text = String::from_utf8(out).unwrap();
+    assert_eq!(text.matches("SET NAMES").count(), 1);
+    assert!(text.trim_end().ends_with("SET FOREIGN_KEY_CHECKS=1;"));
+}
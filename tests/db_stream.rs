@@ -0,0 +1,99 @@
+mod common;
+
+use std::fs;
+use std::io::Cursor;
+
+use synapse_cli::db::dialect::Dialect;
+use synapse_cli::db::rmid::rmid_file;
+use synapse_cli::db::stream::{Output, RawStatement, StatementReader};
+use synapse_cli::utils::files::expand_inputs;
+
+use common::{fixture, TempDir};
+
+#[test]
+fn statement_reader_splits_losslessly() {
+    let sql = fixture("mysqldump_sample.sql");
+    let reader = StatementReader::new(Cursor::new(sql.clone()), Dialect::Mysql);
+    let statements: Vec<RawStatement> = reader.map(|s| s.unwrap()).collect();
+    let joined: String = statements.iter().map(|s| s.text.as_str()).collect();
+    assert_eq!(joined, sql);
+
+    // 注释中的分号与字符串中的分号都不会切断语句
+    let dict = statements.iter().find(|s| s.text.contains("'男'")).unwrap();
+    assert!(dict.text.trim_start().starts_with("# values inside comments"));
+    // 语句从上一条语句的分号之后开始
+    assert_eq!(dict.line, 17);
+}
+
+fn split(sql: &str, dialect: Dialect) -> Vec<String> {
+    StatementReader::new(Cursor::new(sql.to_string()), dialect)
+        .map(|s| s.unwrap().text.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+#[test]
+fn oracle_plsql_blocks_end_at_slash() {
+    let sql = fixture("oracle_sample.sql");
+    let statements = split(&sql, Dialect::Oracle);
+    assert_eq!(statements.len(), 5);
+    assert!(statements[1].starts_with("CREATE OR REPLACE PROCEDURE") && statements[1].ends_with("END;\n/"));
+    assert!(statements[2].starts_with("BEGIN") && statements[2].ends_with('/'));
+    assert!(statements[3].starts_with("INSERT ALL"));
+}
+
+#[test]
+fn dialect_controls_escapes() {
+    let sql = "INSERT INTO t (a) VALUES ('C:\\dir\\'); SELECT 1;";
+    // PostgreSQL 中反斜杠不是转义符，第一条语句在 '...\' 处结束
+    assert_eq!(split(sql, Dialect::Postgres).len(), 2);
+    assert_eq!(split("SELECT $$a; b$$; SELECT 2;", Dialect::Postgres).len(), 2);
+    assert_eq!(split("SELECT 'a\\'; b'; SELECT 2;", Dialect::Mysql).len(), 2);
+}
+
+#[test]
+fn multiline_tokens_resume_across_lines() {
+    // 跨多行的字符串、注释中的分号不切断语句，且不随行数重复扫描
+    let body: String = (0..20000).map(|i| format!("line {};\n", i)).collect();
+    let sql = format!("INSERT INTO t VALUES ('{body}');\n/* {body} */ SELECT 1;\n");
+    let statements = split(&sql, Dialect::Mysql);
+    assert_eq!(statements.len(), 2);
+    assert!(statements[1].ends_with("SELECT 1;"));
+
+    assert_eq!(split("SELECT 'a\\\n'';\nb';\nSELECT 2;", Dialect::Mysql).len(), 2);
+    assert_eq!(split("SELECT $tag$a;\n$tag;\n$tag$;\nSELECT 2;", Dialect::Postgres).len(), 2);
+    assert_eq!(split("SELECT q'[a';\n]' FROM dual;\nSELECT 2 FROM dual;", Dialect::Oracle).len(), 2);
+    assert_eq!(split("/* a;\n*\n/; */ SELECT 1;\nSELECT 2;", Dialect::Postgres).len(), 2);
+}
+
+#[test]
+fn output_modes_and_input_expansion() {
+    let dir = TempDir::new("output");
+    fs::create_dir_all(dir.join("sql/sub")).unwrap();
+    let sql = "INSERT INTO t (id, name) VALUES (1, 'a');\n";
+    for name in ["sql/a.sql", "sql/sub/b.sql", "sql/sub/c.txt", "sql/.hidden.sql"] {
+        fs::write(dir.join(name), sql).unwrap();
+    }
+    let path = dir.join("sql/a.sql");
+
+    let out = dir.join("a.out.sql");
+    rmid_file(&path, &Output::File(out.clone()), Dialect::Mysql).unwrap();
+    assert_eq!(fs::read_to_string(&out).unwrap(), "INSERT INTO t (name) VALUES ('a');\n");
+    assert_eq!(fs::read_to_string(&path).unwrap(), sql);
+    assert!(rmid_file(&path, &Output::File(path.clone()), Dialect::Mysql).unwrap_err().contains("不能与输入文件相同"));
+
+    rmid_file(&path, &Output::InPlace { backup: None }, Dialect::Mysql).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "INSERT INTO t (name) VALUES ('a');\n");
+    assert!(!dir.join("sql/a.sql.bak").exists());
+    rmid_file(&path, &Output::InPlace { backup: Some(".orig".to_string()) }, Dialect::Mysql).unwrap();
+    assert!(dir.join("sql/a.sql.orig").exists());
+
+    let root = dir.join("sql").display().to_string();
+    let files = expand_inputs(&[root.clone(), path.display().to_string()], &["sql".to_string()]).unwrap();
+    let relative: Vec<String> = files.iter().map(|f| f.relative.display().to_string()).collect();
+    assert_eq!(relative, ["a.sql", "sub/b.sql"]);
+    let files = expand_inputs(&[format!("{}/**/*.sql", root)], &["sql".to_string()]).unwrap();
+    assert_eq!(files.iter().map(|f| f.relative.display().to_string()).collect::<Vec<_>>(), ["a.sql", "sub/b.sql"]);
+    assert!(expand_inputs(&[format!("{}/*.csv", root)], &["sql".to_string()]).unwrap_err().contains("没有匹配"));
+    assert!(expand_inputs(&[format!("{}/none.sql", root)], &["sql".to_string()]).unwrap_err().contains("不存在"));
+}
@@ -0,0 +1,45 @@
+use synapse_cli::db::dialect::Dialect;
+use synapse_cli::db::upsert::{upsert_sql, UpsertReport};
+
+#[test]
+fn upsert_per_dialect() {
+    let keys = ["id".to_string()];
+    let mut report = UpsertReport::default();
+    let out = upsert_sql("INSERT INTO `t` (`id`, `v`) VALUES (1, 'a'), (2, 'b');", 1, &keys, Dialect::Mysql, &mut report);
+    assert_eq!(out, "INSERT INTO `t` (`id`, `v`) VALUES (1, 'a'), (2, 'b')\nON DUPLICATE KEY UPDATE `v` = VALUES(`v`);");
+    assert_eq!((report.statements, report.rows), (1, 2));
+
+    let out = upsert_sql("INSERT INTO t (ID, v) VALUES (1, 'a');", 1, &keys, Dialect::Postgres, &mut report);
+    assert_eq!(out, "INSERT INTO t (ID, v) VALUES (1, 'a')\nON CONFLICT (ID) DO UPDATE SET v = EXCLUDED.v;");
+
+    let out = upsert_sql("INSERT INTO t (id, v) VALUES (1, 'a');", 1, &keys, Dialect::Oracle, &mut report);
+    assert_eq!(
+        out,
+        "MERGE INTO t dst\nUSING (SELECT 1 AS id, 'a' AS v FROM DUAL) src\nON (dst.id = src.id)\n\
+         WHEN MATCHED THEN UPDATE SET dst.v = src.v\nWHEN NOT MATCHED THEN INSERT (id, v) VALUES (src.id, src.v);"
+    );
+
+    // 缺少主键列的语句保持原样
+    let sql = "INSERT INTO t (v) VALUES ('a');";
+    assert_eq!(upsert_sql(sql, 7, &keys, Dialect::Mysql, &mut report), sql);
+    assert_eq!(report.warnings[0].0, 7);
+}
+
+#[test]
+fn upsert_types_nulls_and_drops_repeated_keys() {
+    let keys = ["id".to_string()];
+    let mut report = UpsertReport::default();
+    let sql = "INSERT INTO t (id, v, d) VALUES (1, NULL, NULL), (2, 5, DATE '2024-01-01');";
+    let out = upsert_sql(sql, 1, &keys, Dialect::Oracle, &mut report);
+    assert!(out.contains("USING (SELECT 1 AS id, CAST(NULL AS NUMBER) AS v, CAST(NULL AS DATE) AS d FROM DUAL\n  UNION ALL SELECT 2 AS id, 5 AS v"));
+    assert!(report.warnings.is_empty());
+
+    // 同一语句中重复的主键只保留最后一行
+    let out = upsert_sql("INSERT INTO t (id, v) VALUES (1, 'a'), (2, 'b'), (1, 'c');", 3, &keys, Dialect::Postgres, &mut report);
+    assert_eq!(out, "INSERT INTO t (id, v) VALUES (2, 'b'), (1, 'c')\nON CONFLICT (id) DO UPDATE SET v = EXCLUDED.v;");
+    assert_eq!(report.rows, 4);
+    assert_eq!(report.warnings, vec![(3, "t: 同一语句中有 1 行主键重复，只保留最后一行".to_string())]);
+
+    let out = upsert_sql("INSERT INTO t (id, v) VALUES (1, 'a'), ('1', 'c');", 1, &keys, Dialect::Dm, &mut report);
+    assert!(out.starts_with("MERGE INTO t dst\nUSING (SELECT '1' AS id, 'c' AS v FROM DUAL) src"));
+}
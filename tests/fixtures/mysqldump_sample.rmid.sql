@@ -0,0 +1,21 @@
+-- MySQL dump 10.13  Distrib 8.0.33, for Linux (x86_64)
+--
+-- Host: 127.0.0.1    Database: core_bank
+-- ------------------------------------------------------
+
+/*!40101 SET NAMES utf8mb4 */;
+/*!40103 SET TIME_ZONE='+00:00' */;
+
+--
+-- Dumping data for table `sys_user`
+--
+
+LOCK TABLES `sys_user` WRITE;
+/*!40000 ALTER TABLE `sys_user` DISABLE KEYS */;
+INSERT INTO `sys_user` (`user_name`, `nick_name`, `remark`, `inserted_at`) VALUES ('admin','管理员','超级管理员，拥有所有权限','2024-01-01 00:00:00'),('zhang\'san','张三','It''s a (test), with comma','2024-01-02 08:30:00'),('li_si','李四',NULL,'2024-01-03 09:00:00');
+/*!40000 ALTER TABLE `sys_user` ENABLE KEYS */;
+UNLOCK TABLES;
+
+# values inside comments must be left alone: INSERT INTO x (id) VALUES (1, 2);
+INSERT INTO sys_dict (dict_code, dict_label) VALUES ('gender', '男');
+INSERT INTO sys_dict (dict_code, dict_label) VALUES ('gender', '女');
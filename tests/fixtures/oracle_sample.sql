@@ -0,0 +1,20 @@
+-- Oracle 初始化脚本
+DELETE FROM T_DICT WHERE CODE IN ('a', 'b');
+CREATE OR REPLACE PROCEDURE P_INIT_DICT IS
+  V_COUNT NUMBER;
+BEGIN
+  SELECT COUNT(*) INTO V_COUNT FROM T_DICT;
+  IF V_COUNT = 0 THEN
+    INSERT INTO T_DICT (ID, CODE) VALUES (1, 'x');
+  END IF;
+END;
+/
+BEGIN
+  P_INIT_DICT;
+END;
+/
+INSERT ALL
+  INTO T_DICT (ID, CODE, LABEL) VALUES (1, 'a', '甲')
+  INTO T_DICT (ID, CODE, LABEL) VALUES (2, 'b', q'[It's; a 'quoted' value]')
+SELECT * FROM DUAL;
+COMMIT;
@@ -0,0 +1,72 @@
+use regex::Regex;
+
+use synapse_cli::repo::changelog::{classify, parse_range, render_changelog, ChangelogOptions, CommitEntry, RepoCommits};
+
+fn options(group_pattern: Option<&str>, issue_url: Option<&str>) -> ChangelogOptions {
+    ChangelogOptions {
+        group_pattern: group_pattern.map(|p| Regex::new(p).unwrap()),
+        issue_pattern: Regex::new(r"[A-Z][A-Z0-9]+-\d+").unwrap(),
+        issue_url: issue_url.map(|u| u.to_string()),
+    }
+}
+
+fn commit(hash: &str, subject: &str) -> CommitEntry {
+    CommitEntry { hash: format!("{}000000", hash), short_hash: hash.to_string(), subject: subject.to_string(), author: "dev".to_string() }
+}
+
+#[test]
+fn changelog_parses_ranges() {
+    assert_eq!(parse_range("v1.0.0..release/1.1"), Ok(("v1.0.0".to_string(), "release/1.1".to_string())));
+    assert_eq!(parse_range("v1.0.0.."), Ok(("v1.0.0".to_string(), "HEAD".to_string())));
+    assert!(parse_range("..HEAD").unwrap_err().contains("缺少起始标签"));
+    assert!(parse_range("v1.0.0").is_err());
+}
+
+#[test]
+fn changelog_classifies_commits() {
+    let conventional = options(None, None);
+    let cases = [
+        ("feat(api): add login", ("新功能", "**api**: add login")),
+        ("FIX: null check", ("问题修复", "null check")),
+        ("refactor(core)!: drop v1", ("代码重构", "⚠️ **BREAKING** **core**: drop v1")),
+        ("feat(): empty scope", ("新功能", "empty scope")),
+        ("wip: something", ("其他", "something")),
+        ("Merge branch 'dev'", ("其他", "Merge branch 'dev'")),
+    ];
+    for (subject, (group, description)) in cases {
+        assert_eq!(classify(subject, &conventional), (group.to_string(), description.to_string()), "{}", subject);
+    }
+
+    let custom = options(Some(r"^\[(?P<group>[^\]]+)\]"), None);
+    assert_eq!(classify("[支付] 修复退款", &custom), ("支付".to_string(), "[支付] 修复退款".to_string()));
+    assert_eq!(classify("feat: no bracket", &custom), ("其他".to_string(), "feat: no bracket".to_string()));
+}
+
+#[test]
+fn changelog_renders_groups_in_order() {
+    let repos = vec![
+        RepoCommits {
+            name: "core".to_string(),
+            commits: Ok(vec![
+                commit("a1", "chore: bump deps"),
+                commit("b2", "fix: crash on empty input PAY-12"),
+                commit("c3", "update readme"),
+                commit("d4", "feat(api): add login"),
+                commit("e5", "fix(db): retry"),
+            ]),
+        },
+        RepoCommits { name: "empty".to_string(), commits: Ok(Vec::new()) },
+        RepoCommits { name: "broken".to_string(), commits: Err("unknown revision".to_string()) },
+    ];
+    let out = render_changelog("v1.0.0..HEAD", &repos, &options(None, Some("https://jira.example.com/browse/{id}")));
+    assert_eq!(
+        out,
+        "# 变更日志 v1.0.0..HEAD\n\n## core\n\n\
+         ### 新功能\n\n- **api**: add login (`d4`, dev)\n\n\
+         ### 问题修复\n\n- crash on empty input [PAY-12](https://jira.example.com/browse/PAY-12) (`b2`, dev)\n- **db**: retry (`e5`, dev)\n\n\
+         ### 其他\n\n- bump deps (`a1`, dev)\n- update readme (`c3`, dev)\n"
+    );
+
+    let custom = render_changelog("v1..v2", &repos[..1], &options(Some(r"^(\w+)"), None));
+    assert!(custom.contains("### chore\n\n- chore: bump deps") && custom.contains("PAY-12 (`b2`"));
+}
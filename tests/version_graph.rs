@@ -0,0 +1,141 @@
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use synapse_cli::version::graph::bump_version;
+use synapse_cli::version::{apply_plan, plan_bump, BumpLevel, DepGraph};
+
+use common::TempDir;
+
+fn write(dir: &Path, file: &str, text: &str) -> PathBuf {
+    let path = dir.join(file);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, text).unwrap();
+    path
+}
+
+/// core <- api (cargo) <- web (npm)；parent <- child (maven，版本继承)
+fn sample_repos(root: &Path) -> Vec<PathBuf> {
+    write(root, "core/Cargo.toml", "[package]\nname = \"core\"\nversion = \"1.0.0\"\n");
+    write(root, "api/Cargo.toml", "[package]\nname = \"api\"\nversion = \"0.3.0\"\n\n[dependencies]\ncore = \"^1.0.0\"\nserde = \"1\"\n");
+    write(root, "web/package.json", "{\n  \"name\": \"web\",\n  \"version\": \"2.0.0\",\n  \"dependencies\": {\n    \"api\": \"~0.3.0\"\n  }\n}\n");
+    write(
+        root,
+        "platform/pom.xml",
+        "<project>\n  <groupId>com.x</groupId>\n  <artifactId>parent</artifactId>\n  <version>1.0.0</version>\n</project>\n",
+    );
+    write(
+        root,
+        "platform/child/pom.xml",
+        "<project>\n  <parent>\n    <groupId>com.x</groupId>\n    <artifactId>parent</artifactId>\n    <version>1.0.0</version>\n  </parent>\n  <artifactId>child</artifactId>\n</project>\n",
+    );
+    ["core", "api", "web", "platform"].iter().map(|r| root.join(r)).collect()
+}
+
+fn names(graph: &DepGraph, order: &[usize]) -> Vec<String> {
+    order.iter().map(|&i| graph.modules[i].name.clone()).collect()
+}
+
+#[test]
+fn graph_build_and_dependent_order() {
+    let dir = TempDir::new("version_graph");
+    let graph = DepGraph::build(&sample_repos(&dir));
+    assert_eq!(graph.modules.len(), 5);
+
+    // 按完整名称、artifactId 与仓库目录名查找
+    let core = graph.find("core").unwrap();
+    assert_eq!(graph.modules[graph.find("child").unwrap()].name, "com.x:child");
+    assert_eq!(graph.modules[graph.find("platform").unwrap()].name, "com.x:parent");
+    assert!(graph.find("missing").unwrap_err().contains("未找到"));
+    assert_eq!(graph.repo_name(core), "core");
+
+    assert_eq!(names(&graph, &graph.ordered_dependents(core, false).unwrap()), ["core", "api"]);
+    assert_eq!(names(&graph, &graph.ordered_dependents(core, true).unwrap()), ["core", "api", "web"]);
+
+    // 循环依赖报错
+    let cycle = TempDir::new("version_graph_cycle");
+    write(&cycle, "a/package.json", r#"{"name": "a", "version": "1.0.0", "dependencies": {"b": "1.0.0"}}"#);
+    write(&cycle, "b/package.json", r#"{"name": "b", "version": "1.0.0", "dependencies": {"a": "1.0.0"}}"#);
+    let graph = DepGraph::build(&[cycle.join("a"), cycle.join("b")]);
+    let err = graph.ordered_dependents(graph.find("a").unwrap(), true).unwrap_err();
+    assert!(err.contains("循环依赖"), "{}", err);
+}
+
+#[test]
+fn bump_version_levels() {
+    assert_eq!(bump_version("1.2.3", BumpLevel::Major).as_deref(), Some("2.0.0"));
+    assert_eq!(bump_version("1.2.3", BumpLevel::Minor).as_deref(), Some("1.3.0"));
+    assert_eq!(bump_version("1.2", BumpLevel::Patch).as_deref(), Some("1.2.1"));
+    assert_eq!(bump_version("1.0.0-SNAPSHOT", BumpLevel::Minor).as_deref(), Some("1.1.0-SNAPSHOT"));
+    assert_eq!(bump_version("1.0.0+build.5", BumpLevel::Patch).as_deref(), Some("1.0.1+build.5"));
+    assert_eq!(bump_version("1.2.3", BumpLevel::None).as_deref(), Some("1.2.3"));
+    assert_eq!(bump_version("latest", BumpLevel::Patch), None);
+}
+
+#[test]
+fn plan_bump_cascades_and_applies() {
+    let dir = TempDir::new("version_plan");
+    let graph = DepGraph::build(&sample_repos(&dir));
+    let core = graph.find("core").unwrap();
+
+    let err = plan_bump(&graph, core, "0.9.0", "1.1.0", BumpLevel::Minor).unwrap_err();
+    assert!(err.contains("不一致"), "{}", err);
+
+    // 级联升级：依赖方自身升 minor，依赖声明保留前缀
+    let plan = plan_bump(&graph, core, "1.0.0", "1.1.0", BumpLevel::Minor).unwrap();
+    let steps: Vec<_> = plan
+        .iter()
+        .map(|s| (graph.modules[s.module].name.clone(), s.new_version.as_deref(), s.dep_updates.clone()))
+        .collect();
+    let dep = |n: &str, o: &str, v: &str| (n.to_string(), o.to_string(), v.to_string());
+    assert_eq!(
+        steps,
+        [
+            ("core".to_string(), Some("1.1.0"), vec![]),
+            ("api".to_string(), Some("0.4.0"), vec![dep("core", "^1.0.0", "^1.1.0")]),
+            ("web".to_string(), Some("2.1.0"), vec![dep("api", "~0.3.0", "~0.4.0")]),
+        ]
+    );
+
+    // 不级联：只改直接依赖方的依赖声明
+    let plan = plan_bump(&graph, core, "1.0.0", "1.1.0", BumpLevel::None).unwrap();
+    assert_eq!(plan.len(), 2);
+    assert_eq!(plan[1].new_version, None);
+    assert_eq!(plan[1].dep_updates, [dep("core", "^1.0.0", "^1.1.0")]);
+
+    assert_eq!(apply_plan(&graph, &plan).unwrap(), 2);
+    assert_eq!(fs::read_to_string(dir.join("core/Cargo.toml")).unwrap(), "[package]\nname = \"core\"\nversion = \"1.1.0\"\n");
+    assert_eq!(
+        fs::read_to_string(dir.join("api/Cargo.toml")).unwrap(),
+        "[package]\nname = \"api\"\nversion = \"0.3.0\"\n\n[dependencies]\ncore = \"^1.1.0\"\nserde = \"1\"\n"
+    );
+
+    // Maven 子模块版本继承自 parent：只改 parent 引用，计划中显示继承的新版本
+    let parent = graph.find("com.x:parent").unwrap();
+    let plan = plan_bump(&graph, parent, "1.0.0", "1.1.0", BumpLevel::Minor).unwrap();
+    assert_eq!(plan.len(), 2);
+    assert_eq!(plan[1].new_version, None);
+    assert_eq!(plan[1].inherited_version.as_deref(), Some("1.1.0"));
+    assert_eq!(plan[1].dep_updates, [dep("com.x:parent", "1.0.0", "1.1.0")]);
+
+    // 版本继承的模块不能单独升级
+    let err = plan_bump(&graph, graph.find("child").unwrap(), "1.0.0", "1.1.0", BumpLevel::Minor).unwrap_err();
+    assert!(err.contains("com.x:child") && err.contains("继承自 com.x:parent"), "{}", err);
+
+    apply_plan(&graph, &plan).unwrap();
+    let child = fs::read_to_string(dir.join("platform/child/pom.xml")).unwrap();
+    assert!(child.contains("<version>1.1.0</version>\n  </parent>\n  <artifactId>child</artifactId>\n</project>"), "{}", child);
+}
+
+#[test]
+fn plan_bump_rejects_workspace_inherited_versions() {
+    let dir = TempDir::new("version_workspace");
+    write(&dir, "ws/Cargo.toml", "[workspace]\nmembers = [\"member\"]\n\n[workspace.package]\nversion = \"1.0.0\"\n");
+    write(&dir, "ws/member/Cargo.toml", "[package]\nname = \"member\"\nversion.workspace = true\n");
+    write(&dir, "app/Cargo.toml", "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\nmember = \"1.0.0\"\n");
+    let graph = DepGraph::build(&[dir.join("ws"), dir.join("app")]);
+
+    let err = plan_bump(&graph, graph.find("member").unwrap(), "1.0.0", "1.1.0", BumpLevel::None).unwrap_err();
+    assert!(err.contains("member") && err.contains("workspace"), "{}", err);
+}
@@ -0,0 +1,132 @@
+mod common;
+
+use std::fs;
+
+use synapse_cli::version::manifest::{parse_manifest, rewrite_manifest, rewrite_version_req, ManifestKind};
+
+use common::TempDir;
+
+#[test]
+fn npm_rewrite_keeps_formatting() {
+    let dir = TempDir::new("manifest_npm");
+    let path = dir.join("package.json");
+    let text = "{\n    \"name\": \"@team/ui\",\n    \"version\": \"1.2.0\",\n    \"scripts\": { \"build\": \"tsc\" },\n    \"files\": [\"dist\", \"1.2.0\"],\n    \"dependencies\": {\"@team/core\": \"^1.2.0\", \"left-pad\": \"1.2.0\"},\n    \"devDependencies\": {\n        \"@team/core\": \"~1.2.0\"\n    }\n}";
+    fs::write(&path, text).unwrap();
+
+    let manifest = parse_manifest(&path, &dir).unwrap().unwrap();
+    assert_eq!(manifest.kind, ManifestKind::Npm);
+    assert_eq!(manifest.name, "@team/ui");
+    assert_eq!(manifest.version.as_deref(), Some("1.2.0"));
+
+    rewrite_manifest(&manifest, Some("1.3.0"), &[("@team/core".to_string(), "1.3.0".to_string())]).unwrap();
+    let expected = text
+        .replace("\"version\": \"1.2.0\"", "\"version\": \"1.3.0\"")
+        .replace("\"@team/core\": \"^1.2.0\"", "\"@team/core\": \"1.3.0\"")
+        .replace("\"@team/core\": \"~1.2.0\"", "\"@team/core\": \"1.3.0\"");
+    assert_eq!(fs::read_to_string(&path).unwrap(), expected);
+
+    // 没有 version 字段时紧跟 name 插入
+    fs::write(&path, "{\n  \"name\": \"lib\",\n  \"private\": true\n}\n").unwrap();
+    let manifest = parse_manifest(&path, &dir).unwrap().unwrap();
+    rewrite_manifest(&manifest, Some("0.1.0"), &[]).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "{\n  \"name\": \"lib\",\n  \"version\": \"0.1.0\",\n  \"private\": true\n}\n");
+}
+
+#[test]
+fn maven_rewrite_follows_properties() {
+    let dir = TempDir::new("manifest_maven");
+    let path = dir.join("pom.xml");
+    let text = r#"<?xml version="1.0"?>
+<project>
+    <!-- 服务模块 -->
+    <groupId>com.x</groupId>
+    <artifactId>svc</artifactId>
+    <version>2.0.0</version>
+    <properties>
+        <core.version>1.0.0</core.version>
+    </properties>
+    <dependencies>
+        <dependency>
+            <groupId>${project.groupId}</groupId>
+            <artifactId>core</artifactId>
+            <version>${core.version}</version>
+        </dependency>
+        <dependency>
+            <groupId>com.x</groupId>
+            <artifactId>util</artifactId>
+            <version>1.0.0</version>
+        </dependency>
+        <dependency>
+            <groupId>com.x</groupId>
+            <artifactId>self</artifactId>
+            <version>${project.version}</version>
+        </dependency>
+    </dependencies>
+</project>
+"#;
+    fs::write(&path, text).unwrap();
+
+    let manifest = parse_manifest(&path, &dir).unwrap().unwrap();
+    assert_eq!(manifest.name, "com.x:svc");
+    assert!(!manifest.version_inherited);
+    let deps: Vec<(&str, Option<&str>)> = manifest.deps.iter().map(|d| (d.name.as_str(), d.version.as_deref())).collect();
+    assert_eq!(deps, [("com.x:core", Some("1.0.0")), ("com.x:util", Some("1.0.0")), ("com.x:self", Some("2.0.0"))]);
+
+    // 属性引用改写到 properties，${project.version} 不动
+    let updates = [("com.x:core".to_string(), "1.1.0".to_string()), ("com.x:self".to_string(), "9.9.9".to_string())];
+    rewrite_manifest(&manifest, Some("2.1.0"), &updates).unwrap();
+    let expected = text
+        .replace("<version>2.0.0</version>", "<version>2.1.0</version>")
+        .replace("<core.version>1.0.0</core.version>", "<core.version>1.1.0</core.version>");
+    assert_eq!(fs::read_to_string(&path).unwrap(), expected);
+}
+
+#[test]
+fn cargo_rewrite_keeps_comments_and_layout() {
+    let dir = TempDir::new("manifest_cargo");
+    let path = dir.join("Cargo.toml");
+    let text = r#"[package]
+name = "app"
+version = "0.1.0"   # 发布前同步修改 CHANGELOG
+edition = "2021"
+
+[dependencies]
+core = { path = "../core", version = "^1.0" }
+renamed = { package = "util", version = "1.0.0" }
+serde = "1"
+
+[target.'cfg(unix)'.dependencies]
+core = "~1.0.0" # unix 专用
+"#;
+    fs::write(&path, text).unwrap();
+
+    let manifest = parse_manifest(&path, &dir).unwrap().unwrap();
+    assert_eq!(manifest.kind, ManifestKind::Cargo);
+    let names: Vec<&str> = manifest.deps.iter().map(|d| d.name.as_str()).collect();
+    assert_eq!(names, ["core", "util", "serde", "core"]);
+
+    let updates = [("core".to_string(), "^1.1".to_string()), ("util".to_string(), "1.1.0".to_string())];
+    rewrite_manifest(&manifest, Some("0.2.0"), &updates).unwrap();
+    let expected = text
+        .replace("version = \"0.1.0\"", "version = \"0.2.0\"")
+        .replace("version = \"^1.0\"", "version = \"^1.1\"")
+        .replace("version = \"1.0.0\"", "version = \"1.1.0\"")
+        .replace("core = \"~1.0.0\"", "core = \"^1.1\"");
+    assert_eq!(fs::read_to_string(&path).unwrap(), expected);
+
+    // workspace.package.version 继承
+    fs::write(&path, "[package]\nname = \"member\"\nversion.workspace = true\n").unwrap();
+    let manifest = parse_manifest(&path, &dir).unwrap().unwrap();
+    assert!(manifest.version_inherited);
+    assert_eq!(manifest.version, None);
+}
+
+#[test]
+fn version_req_keeps_prefix() {
+    assert_eq!(rewrite_version_req("^1.0.0", "1.1.0").as_deref(), Some("^1.1.0"));
+    assert_eq!(rewrite_version_req(">=1.0", "2.0.0").as_deref(), Some(">=2.0.0"));
+    assert_eq!(rewrite_version_req("1.0.0", "1.1.0").as_deref(), Some("1.1.0"));
+    assert_eq!(rewrite_version_req(">=1.0, <2.0", "1.1.0"), None);
+    assert_eq!(rewrite_version_req("1.x", "1.1.0"), None);
+    assert_eq!(rewrite_version_req("workspace:*", "1.1.0"), None);
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+use synapse_cli::workspace::config::{format_timestamp, Workspace, WorkspaceConfig, WorkspaceUpdate, CONFIG_FILE, LEGACY_FILE};
+
+use common::TempDir;
+
+#[test]
+fn workspace_config_round_trip_and_migration() {
+    assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+    assert_eq!(format_timestamp(1_700_000_000), "2023-11-14T22:13:20Z");
+    assert_eq!(format_timestamp(951_782_400), "2000-02-29T00:00:00Z");
+
+    // 修改项：空值删除，格式错误报错
+    let mut workspace = Workspace::new("app", PathBuf::from("/work/app"));
+    let update = WorkspaceUpdate {
+        description: Some("主项目".to_string()),
+        default_branch: Some("develop".to_string()),
+        groups: vec!["backend=svc-a, svc-b".to_string(), "web=ui".to_string()],
+        remotes: vec!["origin=git@host:team/app.git".to_string()],
+        env: vec!["JAVA_HOME=/opt/jdk".to_string(), "QUERY=a=b".to_string()],
+        ..Default::default()
+    };
+    workspace.apply(&update).unwrap();
+    assert_eq!(workspace.groups["backend"], vec!["svc-a", "svc-b"]);
+    assert_eq!(workspace.env["QUERY"], "a=b");
+    let removal = WorkspaceUpdate { groups: vec!["web=".to_string()], description: Some(String::new()), ..Default::default() };
+    workspace.apply(&removal).unwrap();
+    assert!(!workspace.groups.contains_key("web"));
+    assert_eq!(workspace.description, None);
+    assert!(workspace.apply(&WorkspaceUpdate { remotes: vec!["origin".to_string()], ..Default::default() }).is_err());
+
+    // TOML 往返，名称与路径中的特殊字符不受影响
+    workspace.touch();
+    let odd = Workspace { name: "a.b c".to_string(), path: PathBuf::from("/work/x=y \"q\""), ..Default::default() };
+    let config = WorkspaceConfig { workspaces: vec![workspace, odd] };
+    let text = config.to_toml();
+    assert!(text.contains("version = 1"));
+    assert_eq!(WorkspaceConfig::parse(&text).unwrap(), config);
+
+    // 缺少版本号视为 1，高于支持的版本报错
+    let parsed = WorkspaceConfig::parse("[workspaces.w]\npath = \"/w\"\nlast_used_at = \"2024-01-01T00:00:00Z\"\n").unwrap();
+    assert_eq!(parsed.get("w").unwrap().last_used_at.as_deref(), Some("2024-01-01T00:00:00Z"));
+    assert!(WorkspaceConfig::parse("version = 2\n").unwrap_err().contains("升级"));
+    assert!(WorkspaceConfig::parse("[workspaces.w]\ndescription = \"x\"\n").unwrap_err().contains("path"));
+
+    // 旧版 workspaces.txt 自动迁移，路径中的 = 保留
+    let dir = TempDir::new("workspace");
+    fs::write(dir.join(LEGACY_FILE), "app=/work/a=b\nbroken\napp=/work/dup\nlib=/work/lib").unwrap();
+    let migrated = WorkspaceConfig::load(&dir).unwrap();
+    let names: Vec<&str> = migrated.workspaces.iter().map(|w| w.name.as_str()).collect();
+    assert_eq!(names, ["app", "lib"]);
+    assert_eq!(migrated.get("app").unwrap().path, PathBuf::from("/work/a=b"));
+    assert!(dir.join(CONFIG_FILE).exists());
+    assert!(!dir.join(LEGACY_FILE).exists());
+    assert!(dir.join(format!("{}.bak", LEGACY_FILE)).exists());
+    assert_eq!(WorkspaceConfig::load(&dir).unwrap(), migrated);
+}
+
+#[test]
+fn workspace_env_keys_are_validated() {
+    let mut workspace = Workspace::new("app", PathBuf::from("/work/app"));
+    let update = |env: &str| WorkspaceUpdate { env: vec![env.to_string()], ..Default::default() };
+    workspace.apply(&update("_JAVA_HOME2=/opt/jdk")).unwrap();
+    for bad in ["1ABC=x", "A-B=x", "X'; rm -rf ~; '=x", "A B=x"] {
+        assert!(workspace.apply(&update(bad)).unwrap_err().contains("环境变量名无效"), "{}", bad);
+    }
+
+    // 手动编辑的配置在读取与保存时都会检查
+    let err = WorkspaceConfig::parse("[workspaces.app]\npath = \"/w\"\n[workspaces.app.env]\n\"A;B\" = \"x\"\n").unwrap_err();
+    assert!(err.contains("A;B"), "{}", err);
+    workspace.env.insert("$(id)".to_string(), "x".to_string());
+    let dir = TempDir::new("workspace-env");
+    assert!(WorkspaceConfig { workspaces: vec![workspace] }.save(&dir).is_err());
+    assert!(!dir.join(CONFIG_FILE).exists());
+}